@@ -0,0 +1,53 @@
+// ABOUTME: Benchmarks WatchManager::read_new_entries over a large synthetic session log
+// ABOUTME: Covers the streaming case (a few new lines at the end) and a full read
+
+use cc_log_viewer::WatchManager;
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::io::Write;
+
+/// Size of the synthetic session log.
+const LOG_BYTES: usize = 100 * 1024 * 1024;
+/// Lines appended after the streaming position.
+const NEW_LINES: usize = 10;
+
+fn log_line(i: usize) -> String {
+    format!(
+        r#"{{"type":"assistant","uuid":"u{i}","timestamp":"2024-01-15T10:00:00Z","message":{{"role":"assistant","content":[{{"type":"text","text":"{}"}}]}}}}"#,
+        "streaming output ".repeat(20)
+    ) + "\n"
+}
+
+fn bench_read_new_entries(c: &mut Criterion) {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("session.jsonl");
+    let mut file = std::io::BufWriter::new(std::fs::File::create(&path).unwrap());
+    let mut written = 0;
+    let mut lines = Vec::new();
+    while written < LOG_BYTES {
+        let line = log_line(lines.len());
+        written += line.len();
+        file.write_all(line.as_bytes()).unwrap();
+        lines.push(line.len());
+    }
+    file.flush().unwrap();
+    let tail_position = (written - lines.iter().rev().take(NEW_LINES).sum::<usize>()) as u64;
+
+    let mut group = c.benchmark_group("read_new_entries_100mb");
+    group.sample_size(10);
+    group.bench_function("tail", |b| {
+        b.iter(|| {
+            let entries = WatchManager::read_new_entries(&path, tail_position).unwrap();
+            assert_eq!(entries.len(), NEW_LINES);
+        })
+    });
+    group.bench_function("full", |b| {
+        b.iter(|| {
+            let entries = WatchManager::read_new_entries(&path, 0).unwrap();
+            assert_eq!(entries.len(), lines.len());
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_read_new_entries);
+criterion_main!(benches);
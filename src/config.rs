@@ -0,0 +1,70 @@
+// ABOUTME: Optional user config file providing defaults for the CLI
+// ABOUTME: Reads ~/.config/cc-log-viewer/config.toml for port, projects dir and watch filters
+
+use crate::pricing::ModelPricing;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub port: Option<u16>,
+    pub projects_dir: Option<PathBuf>,
+    /// Only stream live events for projects whose name contains one of
+    /// these strings. Empty means every project.
+    pub watch_projects: Vec<String>,
+    /// Regexes used by `--redact` instead of the built-in secret patterns.
+    pub redact_patterns: Vec<String>,
+    /// Token prices keyed by a model name or part of one, checked before the
+    /// built-in prices.
+    pub pricing: BTreeMap<String, ModelPricing>,
+    pub tui: TuiConfig,
+}
+
+/// The `[tui]` table: how often the terminal UI polls and refreshes.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TuiConfig {
+    /// Milliseconds between refreshes of the project and session lists.
+    pub refresh_interval_ms: Option<u64>,
+    /// Milliseconds to wait for a key press before redrawing.
+    pub poll_interval_ms: Option<u64>,
+    /// `false` turns the periodic list refresh off; `r` still refreshes.
+    pub auto_refresh: Option<bool>,
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/cc-log-viewer/config.toml`, falling back to
+    /// `~/.config/cc-log-viewer/config.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(config_home.join("cc-log-viewer").join("config.toml"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let content = fs::read_to_string(path)?;
+        let mut config: Config = toml::from_str(&content)?;
+        config.projects_dir = config.projects_dir.map(|dir| expand_home(&dir));
+        Ok(config)
+    }
+
+    /// Load the config from its default location; a missing file is not an error.
+    pub fn load_default() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        match Self::default_path() {
+            Some(path) if path.exists() => Self::load(&path),
+            _ => Ok(Self::default()),
+        }
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
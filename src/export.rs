@@ -0,0 +1,1406 @@
+// ABOUTME: Conversation export for Claude Code logs in Markdown, HTML and plain text
+// ABOUTME: Walks parsed log entries once and renders messages, tool calls and tool results
+
+use crate::{
+    flatten_message_blocks, get_tool_icon, humanize_tool_name, is_error_result, is_human_turn,
+    trim_log_line, ContentBlock, LogEntry, Redactor, TokenUsage,
+};
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde_json::{json, Value};
+use similar::{ChangeTag, TextDiff};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom, Write};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    Html,
+    PlainText,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [
+        ExportFormat::Markdown,
+        ExportFormat::Html,
+        ExportFormat::PlainText,
+    ];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Html => "html",
+            ExportFormat::PlainText => "txt",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "Markdown",
+            ExportFormat::Html => "HTML",
+            ExportFormat::PlainText => "Plain text",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        let idx = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(idx + 1) % Self::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Self {
+        let idx = Self::ALL.iter().position(|f| f == self).unwrap_or(0);
+        Self::ALL[(idx + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+}
+
+/// Knobs for a single export run.
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Tool output longer than this many lines is collapsed into a
+    /// `<details>` element in HTML exports.
+    pub collapse_lines: usize,
+    /// When set, secrets in every entry are masked before rendering.
+    pub redactor: Option<Redactor>,
+    /// Start Markdown exports with a YAML frontmatter block describing the
+    /// session, for note-taking apps and static site generators.
+    pub frontmatter: bool,
+    /// Include bookkeeping detail, such as todo ids, that is usually noise.
+    pub verbose: bool,
+    /// Tool output beyond this many bytes is cut off with a marker, so a
+    /// minified bundle or base64 blob can't swamp the export.
+    pub max_output_bytes: usize,
+    /// Timezone for the timestamps shown in headers and on each message.
+    pub timezone: Tz,
+    /// Keep only the text of user and assistant messages, dropping tool
+    /// calls, tool output, thinking and summaries, for a shareable transcript.
+    pub conversation_only: bool,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            collapse_lines: 30,
+            redactor: None,
+            frontmatter: false,
+            verbose: false,
+            max_output_bytes: 100_000,
+            timezone: Tz::UTC,
+            conversation_only: false,
+        }
+    }
+}
+
+pub fn generate_markdown_export(
+    project_name: &str,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> String {
+    generate_export(ExportFormat::Markdown, project_name, session_id, entries)
+}
+
+pub fn generate_html_export(project_name: &str, session_id: &str, entries: &[LogEntry]) -> String {
+    generate_export(ExportFormat::Html, project_name, session_id, entries)
+}
+
+pub fn generate_text_export(project_name: &str, session_id: &str, entries: &[LogEntry]) -> String {
+    generate_export(ExportFormat::PlainText, project_name, session_id, entries)
+}
+
+pub fn generate_export(
+    format: ExportFormat,
+    project_name: &str,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> String {
+    generate_export_with_options(
+        format,
+        &ExportOptions::default(),
+        project_name,
+        session_id,
+        entries,
+    )
+}
+
+pub fn generate_export_with_options(
+    format: ExportFormat,
+    options: &ExportOptions,
+    project_name: &str,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> String {
+    in_memory(|out| {
+        write_export_with_options(out, format, options, project_name, session_id, entries)
+    })
+}
+
+/// Like `generate_export_with_options`, but writes to `out` one message at a
+/// time instead of building the whole document in memory.
+pub fn write_export_with_options(
+    out: &mut impl Write,
+    format: ExportFormat,
+    options: &ExportOptions,
+    project_name: &str,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> io::Result<()> {
+    let frontmatter = (format == ExportFormat::Markdown && options.frontmatter).then(|| {
+        let mut stats = FrontmatterStats::default();
+        entries.iter().for_each(|entry| stats.add(entry));
+        stats.render(project_name, session_id)
+    });
+    write_document(
+        out,
+        format,
+        options,
+        project_name,
+        session_id,
+        frontmatter,
+        |out| render_entries(format, options, entries, 2, out),
+    )
+}
+
+/// Like `write_export_with_options`, but reads the session log at `path` a
+/// line at a time, so neither the log nor its parsed entries are ever held
+/// in memory as a whole. A first pass notes only where each tool call's
+/// result is logged, so results are still rendered beneath their call.
+pub fn write_export_from_log(
+    out: &mut impl Write,
+    format: ExportFormat,
+    options: &ExportOptions,
+    project_name: &str,
+    session_id: &str,
+    path: &Path,
+) -> io::Result<()> {
+    let with_frontmatter = format == ExportFormat::Markdown && options.frontmatter;
+    let mut outline = ToolOutline::default();
+    let mut stats = FrontmatterStats::default();
+    for_each_log_entry(path, |offset, entry| {
+        outline.add(offset, &entry);
+        if with_frontmatter {
+            stats.add(&entry);
+        }
+        Ok(())
+    })?;
+
+    let mut lookup = BufReader::new(File::open(path)?);
+    let frontmatter = with_frontmatter.then(|| stats.render(project_name, session_id));
+    write_document(
+        out,
+        format,
+        options,
+        project_name,
+        session_id,
+        frontmatter,
+        |out| {
+            let mut rendered = String::new();
+            for_each_log_entry(path, |_, entry| {
+                let entry = match &options.redactor {
+                    Some(redactor) => redactor.redact_entry(&entry),
+                    None => entry,
+                };
+                let tools = outline.tools_for(&entry, &mut lookup, options.redactor.as_ref())?;
+                rendered.clear();
+                render_entry(format, options, &entry, &tools, 2, &mut rendered);
+                out.write_all(rendered.as_bytes())
+            })
+        },
+    )
+}
+
+/// The parts of an export around its messages, which `body` writes.
+fn write_document<W: Write>(
+    out: &mut W,
+    format: ExportFormat,
+    options: &ExportOptions,
+    project_name: &str,
+    session_id: &str,
+    frontmatter: Option<String>,
+    body: impl FnOnce(&mut W) -> io::Result<()>,
+) -> io::Result<()> {
+    if format == ExportFormat::Html {
+        write!(
+            out,
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} - {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+            escape_html(project_name),
+            escape_html(session_id),
+            HTML_STYLE
+        )?;
+    }
+
+    if let Some(frontmatter) = frontmatter {
+        out.write_all(frontmatter.as_bytes())?;
+    }
+
+    out.write_all(heading(format, 1, "Claude Code Conversation Export").as_bytes())?;
+    out.write_all(field(format, "Project", project_name).as_bytes())?;
+    out.write_all(field(format, "Session", session_id).as_bytes())?;
+    out.write_all(
+        field(
+            format,
+            "Exported",
+            &format_timestamp(Some(Utc::now()), options.timezone),
+        )
+        .as_bytes(),
+    )?;
+    out.write_all(separator(format).as_bytes())?;
+
+    body(out)?;
+
+    if format == ExportFormat::Html {
+        out.write_all(b"</body>\n</html>\n")?;
+    }
+
+    Ok(())
+}
+
+/// Run a writer-based export against an in-memory buffer.
+fn in_memory(write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+    let mut out = Vec::new();
+    write(&mut out).expect("writing to memory cannot fail");
+    String::from_utf8(out).expect("exports are built from UTF-8 strings")
+}
+
+/// What the YAML frontmatter says about a session, gathered an entry at a
+/// time.
+#[derive(Default)]
+struct FrontmatterStats {
+    started_at: Option<DateTime<Utc>>,
+    message_count: usize,
+    tools_used: BTreeSet<String>,
+    tokens: TokenUsage,
+}
+
+impl FrontmatterStats {
+    fn add(&mut self, entry: &LogEntry) {
+        if let Some(ts) = entry.timestamp {
+            self.started_at = Some(self.started_at.map_or(ts, |s| s.min(ts)));
+        }
+        if matches!(
+            entry.entry_type.as_deref(),
+            Some("user") | Some("assistant")
+        ) {
+            self.message_count += 1;
+        }
+        for block in flatten_message_blocks(entry) {
+            if let ContentBlock::ToolUse { name, .. } = block {
+                self.tools_used.insert(name);
+            }
+        }
+        if let Some(usage) = TokenUsage::from_entry(entry) {
+            self.tokens.add(&usage);
+        }
+    }
+
+    /// The frontmatter block. Strings are written as JSON literals, which
+    /// YAML reads as double-quoted scalars.
+    fn render(&self, project_name: &str, session_id: &str) -> String {
+        let quote = |s: &str| Value::from(s).to_string();
+        let started_at = self
+            .started_at
+            .map(|ts| quote(&ts.to_rfc3339()))
+            .unwrap_or_else(|| "null".to_string());
+        let tools_used: Vec<String> = self.tools_used.iter().map(|t| quote(t)).collect();
+
+        format!(
+            "---\nproject: {}\nsession_id: {}\nstarted_at: {}\nmessage_count: {}\ntools_used: [{}]\ntotal_tokens: {}\n---\n\n",
+            quote(project_name),
+            quote(session_id),
+            started_at,
+            self.message_count,
+            tools_used.join(", "),
+            self.tokens.total()
+        )
+    }
+}
+
+/// Call `f` with the byte offset and entry of every line of the session log
+/// at `path` that parses, reading one line at a time.
+fn for_each_log_entry(
+    path: &Path,
+    mut f: impl FnMut(u64, LogEntry) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = Vec::new();
+    let mut offset = 0;
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            return Ok(());
+        }
+        let content = line.strip_suffix(b"\n").unwrap_or(&line);
+        if let Ok(entry) = serde_json::from_slice::<LogEntry>(trim_log_line(content)) {
+            f(offset, entry)?;
+        }
+        offset += read as u64;
+    }
+}
+
+/// The content blocks of an entry's message, if it has a list of them.
+fn content_blocks(entry: &LogEntry) -> &[Value] {
+    match entry.message.as_ref().and_then(|m| m.get("content")) {
+        Some(Value::Array(blocks)) => blocks.as_slice(),
+        _ => &[],
+    }
+}
+
+/// Which tool calls a session makes and the offset of the line holding each
+/// one's result: everything `correlate_tools` knows, minus the tool output.
+#[derive(Default)]
+struct ToolOutline {
+    calls: HashSet<String>,
+    results: HashMap<String, u64>,
+}
+
+impl ToolOutline {
+    fn add(&mut self, offset: u64, entry: &LogEntry) {
+        for block in content_blocks(entry) {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("tool_use") => {
+                    if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
+                        self.calls.insert(id.to_string());
+                    }
+                }
+                Some("tool_result") => {
+                    if let Some(id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
+                        self.results.insert(id.to_string(), offset);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// The slice of `correlate_tools` that rendering `entry` needs, reading
+    /// the results of its tool calls back from `log`.
+    fn tools_for(
+        &self,
+        entry: &LogEntry,
+        log: &mut BufReader<File>,
+        redactor: Option<&Redactor>,
+    ) -> io::Result<HashMap<String, (Value, Option<Value>)>> {
+        let mut tools = HashMap::new();
+        for block in content_blocks(entry) {
+            match block.get("type").and_then(|t| t.as_str()) {
+                Some("tool_result") => {
+                    if let Some(id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
+                        if self.calls.contains(id) {
+                            tools.entry(id.to_string()).or_insert((Value::Null, None));
+                        }
+                    }
+                }
+                Some("tool_use") => {
+                    let Some(id) = block.get("id").and_then(|i| i.as_str()) else {
+                        continue;
+                    };
+                    let result = match self.results.get(id) {
+                        Some(&offset) => read_tool_result(log, offset, id, redactor)?,
+                        None => None,
+                    };
+                    let input = block.get("input").cloned().unwrap_or(Value::Null);
+                    tools.insert(id.to_string(), (input, result));
+                }
+                _ => {}
+            }
+        }
+        Ok(tools)
+    }
+}
+
+/// The tool_result block for `id` in the log line starting at `offset`.
+fn read_tool_result(
+    log: &mut BufReader<File>,
+    offset: u64,
+    id: &str,
+    redactor: Option<&Redactor>,
+) -> io::Result<Option<Value>> {
+    log.seek(SeekFrom::Start(offset))?;
+    let mut line = Vec::new();
+    log.read_until(b'\n', &mut line)?;
+    let content = line.strip_suffix(b"\n").unwrap_or(&line);
+    let Ok(entry) = serde_json::from_slice::<LogEntry>(trim_log_line(content)) else {
+        return Ok(None);
+    };
+    let entry = match redactor {
+        Some(redactor) => redactor.redact_entry(&entry),
+        None => entry,
+    };
+    Ok(content_blocks(&entry)
+        .iter()
+        .rev()
+        .find(|block| {
+            block.get("type").and_then(|t| t.as_str()) == Some("tool_result")
+                && block.get("tool_use_id").and_then(|i| i.as_str()) == Some(id)
+        })
+        .cloned())
+}
+
+/// Concatenate every session of a project into one Markdown document with a
+/// linked table of contents. `sessions` is rendered in the order given.
+pub fn generate_combined_markdown_export(
+    options: &ExportOptions,
+    project_name: &str,
+    sessions: &[(String, Vec<LogEntry>)],
+) -> String {
+    in_memory(|out| {
+        let contents: Vec<_> = sessions
+            .iter()
+            .map(|(id, entries)| (id.clone(), entries.iter().filter_map(|e| e.timestamp).min()))
+            .collect();
+        write_combined_markdown_header(out, options, project_name, &contents)?;
+        for (session_id, entries) in sessions {
+            write_combined_markdown_section(out, options, session_id, entries)?;
+        }
+        Ok(())
+    })
+}
+
+/// Title and table of contents of a combined project export. `sessions`
+/// pairs each session id with its start time, in the order the sections
+/// will be written.
+pub fn write_combined_markdown_header(
+    out: &mut impl Write,
+    options: &ExportOptions,
+    project_name: &str,
+    sessions: &[(String, Option<DateTime<Utc>>)],
+) -> io::Result<()> {
+    let format = ExportFormat::Markdown;
+    out.write_all(heading(format, 1, &format!("Project {}", project_name)).as_bytes())?;
+    out.write_all(field(format, "Sessions", &sessions.len().to_string()).as_bytes())?;
+    out.write_all(
+        field(
+            format,
+            "Exported",
+            &format_timestamp(Some(Utc::now()), options.timezone),
+        )
+        .as_bytes(),
+    )?;
+
+    out.write_all(b"**Contents**\n\n")?;
+    for (session_id, started) in sessions {
+        writeln!(
+            out,
+            "- [Session {}](#{}) ({})",
+            session_id,
+            session_anchor(session_id),
+            format_timestamp(*started, options.timezone)
+        )?;
+    }
+    out.write_all(b"\n")
+}
+
+/// One session's section of a combined project export, so sessions can be
+/// parsed and written one at a time.
+pub fn write_combined_markdown_section(
+    out: &mut impl Write,
+    options: &ExportOptions,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> io::Result<()> {
+    let format = ExportFormat::Markdown;
+    out.write_all(separator(format).as_bytes())?;
+    out.write_all(heading(format, 2, &format!("Session {}", session_id)).as_bytes())?;
+    render_entries(format, options, entries, 3, out)
+}
+
+/// Anchor for a session heading, matching the slug Markdown renderers
+/// generate for `## Session <id>`.
+fn session_anchor(session_id: &str) -> String {
+    format!("session-{}", session_id)
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '-' || *c == '_')
+        .collect()
+}
+
+/// Render `entries` in order, with message headings at `level`, writing each
+/// message to `out` as soon as it is rendered.
+fn render_entries(
+    format: ExportFormat,
+    options: &ExportOptions,
+    entries: &[LogEntry],
+    level: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let redacted: Vec<LogEntry>;
+    let entries = match &options.redactor {
+        Some(redactor) => {
+            redacted = entries.iter().map(|e| redactor.redact_entry(e)).collect();
+            &redacted
+        }
+        None => entries,
+    };
+
+    let tools = correlate_tools(entries);
+    let mut rendered = String::new();
+    for entry in entries {
+        rendered.clear();
+        render_entry(format, options, entry, &tools, level, &mut rendered);
+        out.write_all(rendered.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Render a single entry as it arrives, e.g. while tailing a session. With
+/// no later entries to pair them with, tool calls and their results are
+/// each shown where they were logged.
+pub fn write_entry(
+    out: &mut impl Write,
+    format: ExportFormat,
+    options: &ExportOptions,
+    entry: &LogEntry,
+) -> io::Result<()> {
+    render_entries(format, options, std::slice::from_ref(entry), 2, out)
+}
+
+/// Match every tool_use to its tool_result by id, regardless of how far apart
+/// the two entries are. Values are the tool input and, once seen, the full
+/// tool_result block.
+pub fn correlate_tools(entries: &[LogEntry]) -> HashMap<String, (Value, Option<Value>)> {
+    correlate_blocks(entries.iter().flat_map(content_blocks))
+}
+
+/// `correlate_tools` over a flat sequence of content blocks.
+fn correlate_blocks<'a>(
+    blocks: impl IntoIterator<Item = &'a Value>,
+) -> HashMap<String, (Value, Option<Value>)> {
+    let mut tools: HashMap<String, (Value, Option<Value>)> = HashMap::new();
+    let mut results: HashMap<String, Value> = HashMap::new();
+
+    for block in blocks {
+        match block.get("type").and_then(|t| t.as_str()) {
+            Some("tool_use") => {
+                if let Some(id) = block.get("id").and_then(|i| i.as_str()) {
+                    let input = block.get("input").cloned().unwrap_or(Value::Null);
+                    tools.insert(id.to_string(), (input, None));
+                }
+            }
+            Some("tool_result") => {
+                if let Some(id) = block.get("tool_use_id").and_then(|i| i.as_str()) {
+                    results.insert(id.to_string(), block.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // Results can precede their call when logs are interleaved, so pair last.
+    for (id, result) in results {
+        if let Some((_, slot)) = tools.get_mut(&id) {
+            *slot = Some(result);
+        }
+    }
+
+    tools
+}
+
+fn render_entry(
+    format: ExportFormat,
+    options: &ExportOptions,
+    entry: &LogEntry,
+    tools: &HashMap<String, (Value, Option<Value>)>,
+    level: usize,
+    out: &mut String,
+) {
+    if entry.entry_type.as_deref() == Some("summary") {
+        if options.conversation_only {
+            return;
+        }
+        if let Some(summary) = &entry.summary {
+            out.push_str(&field(format, "Summary", summary));
+        }
+        return;
+    }
+
+    let Some(message) = &entry.message else {
+        return;
+    };
+
+    let role = message.get("role").and_then(|r| r.as_str());
+    let is_dialogue = is_human_turn(entry) || role == Some("assistant");
+    if options.conversation_only && !(is_dialogue && has_text(message)) {
+        return;
+    }
+    if role == Some("user") && !is_human_turn(entry) {
+        // Tool output Claude Code logs as a user turn: paired results were
+        // already rendered beneath their call, so only orphans show here.
+        out.push_str(&render_message_body(format, options, entry, tools));
+        return;
+    }
+
+    let title = match (role, entry.model()) {
+        (Some("user"), _) => "👤 User".to_string(),
+        (Some("assistant"), Some(model)) => format!("🤖 Assistant ({})", model),
+        (Some("assistant"), None) => "🤖 Assistant".to_string(),
+        _ => "ℹ️ System".to_string(),
+    };
+
+    out.push_str(&heading(format, level, &title));
+    out.push_str(&meta(
+        format,
+        &format_timestamp(entry.timestamp, options.timezone),
+    ));
+    out.push_str(&render_message_body(format, options, entry, tools));
+}
+
+/// Render just the content blocks of an entry's message, without the role
+/// heading or timestamp. The TUI uses the `PlainText` form for its detail view.
+pub fn render_message_body(
+    format: ExportFormat,
+    options: &ExportOptions,
+    entry: &LogEntry,
+    tools: &HashMap<String, (Value, Option<Value>)>,
+) -> String {
+    let mut out = String::new();
+    let Some(message) = &entry.message else {
+        return out;
+    };
+
+    match message.get("content") {
+        Some(Value::String(text)) => out.push_str(&paragraph(format, text)),
+        Some(Value::Array(blocks)) => {
+            for block in blocks {
+                render_block(format, options, block, tools, &mut out);
+            }
+        }
+        Some(other) => out.push_str(&code_block(format, "json", &pretty_json(other))),
+        None => {}
+    }
+
+    out
+}
+
+/// Whether a message has any text to show in a conversation-only export.
+fn has_text(message: &Value) -> bool {
+    match message.get("content") {
+        Some(Value::String(text)) => !text.trim().is_empty(),
+        Some(Value::Array(blocks)) => blocks.iter().any(|block| {
+            block.get("type").and_then(|t| t.as_str()) == Some("text")
+                && block
+                    .get("text")
+                    .and_then(|t| t.as_str())
+                    .is_some_and(|t| !t.trim().is_empty())
+        }),
+        _ => false,
+    }
+}
+
+fn render_block(
+    format: ExportFormat,
+    options: &ExportOptions,
+    block: &Value,
+    tools: &HashMap<String, (Value, Option<Value>)>,
+    out: &mut String,
+) {
+    let block_type = block.get("type").and_then(|t| t.as_str());
+    if options.conversation_only && block_type != Some("text") {
+        return;
+    }
+    match block_type {
+        Some("text") => {
+            let text = block.get("text").and_then(|t| t.as_str()).unwrap_or("");
+            out.push_str(&paragraph(format, text));
+        }
+        Some("thinking") => {
+            let text = block.get("thinking").and_then(|t| t.as_str()).unwrap_or("");
+            out.push_str(&heading(format, 3, "💭 Thinking"));
+            out.push_str(&paragraph(format, text));
+        }
+        Some("tool_use") => {
+            let name = block
+                .get("name")
+                .and_then(|n| n.as_str())
+                .unwrap_or("unknown");
+            let input = block.get("input").cloned().unwrap_or(Value::Null);
+            let git = match name {
+                "Bash" => input
+                    .get("command")
+                    .and_then(|c| c.as_str())
+                    .and_then(git_subcommand),
+                _ => None,
+            };
+            let title = match git {
+                Some(subcommand) => format!("{} Git: {}", GIT_ICON, subcommand),
+                None => format!("{} Tool: {}", get_tool_icon(name), humanize_tool_name(name)),
+            };
+            out.push_str(&heading(format, 3, &title));
+            render_tool_input(format, options, name, &input, out);
+
+            let paired = block
+                .get("id")
+                .and_then(|i| i.as_str())
+                .and_then(|id| tools.get(id))
+                .and_then(|(_, result)| result.as_ref());
+            if let Some(result) = paired {
+                let language = match (name, git) {
+                    ("Read", _) => input_path(&input).and_then(language_for_path),
+                    (_, Some("diff" | "show")) => Some("diff"),
+                    _ => None,
+                };
+                render_tool_result(format, options, result, language.unwrap_or(""), out);
+            }
+        }
+        Some("tool_result") => {
+            // Paired results are rendered beneath their call; only orphans here.
+            let is_paired = block
+                .get("tool_use_id")
+                .and_then(|i| i.as_str())
+                .is_some_and(|id| tools.contains_key(id));
+            if !is_paired {
+                render_tool_result(format, options, block, "", out);
+            }
+        }
+        Some("image") => out.push_str(&image_block(format, block)),
+        _ => out.push_str(&code_block(format, "json", &pretty_json(block))),
+    }
+}
+
+const GIT_ICON: &str = "🔀";
+
+/// The subcommand of a shell command that runs git, e.g. `status` for
+/// `git -C repo status --short`. Global options before it are skipped.
+pub fn git_subcommand(command: &str) -> Option<&str> {
+    let mut words = command.split_whitespace();
+    if words.next()? != "git" {
+        return None;
+    }
+    while let Some(word) = words.next() {
+        match word {
+            // These take a separate value
+            "-C" | "-c" | "--git-dir" | "--work-tree" | "--namespace" => {
+                words.next();
+            }
+            _ if word.starts_with('-') => {}
+            _ => return Some(word),
+        }
+    }
+    None
+}
+
+fn render_tool_input(
+    format: ExportFormat,
+    options: &ExportOptions,
+    name: &str,
+    input: &Value,
+    out: &mut String,
+) {
+    let str_field = |key: &str| input.get(key).and_then(|v| v.as_str());
+
+    if let ("TodoWrite", Some(todos)) = (name, input.get("todos").and_then(|t| t.as_array())) {
+        out.push_str(&todo_checklist(format, options.verbose, todos));
+        return;
+    }
+
+    match (name, str_field("command"), str_field("content")) {
+        ("Bash", Some(command), _) => out.push_str(&code_block(format, "bash", command)),
+        ("Write", _, Some(content)) => {
+            let path = input_path(input).unwrap_or("");
+            out.push_str(&field(format, "File", path));
+            let language = language_for_path(path).unwrap_or("");
+            out.push_str(&code_block(format, language, content));
+        }
+        ("Edit", _, _) => {
+            out.push_str(&field(format, "File", input_path(input).unwrap_or("")));
+            out.push_str(&diff_block(
+                format,
+                input
+                    .get("old_string")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(""),
+                input
+                    .get("new_string")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or(""),
+            ));
+        }
+        ("MultiEdit", _, _) => {
+            out.push_str(&field(format, "File", input_path(input).unwrap_or("")));
+            let edits = input.get("edits").and_then(|e| e.as_array());
+            for edit in edits.into_iter().flatten() {
+                out.push_str(&diff_block(
+                    format,
+                    edit.get("old_string")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                    edit.get("new_string")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(""),
+                ));
+            }
+        }
+        _ if name.starts_with("mcp__") => render_mcp_input(format, input, out),
+        _ => out.push_str(&code_block(format, "json", &pretty_json(input))),
+    }
+}
+
+/// Input fields common enough across MCP servers to label individually.
+const MCP_COMMON_FIELDS: [&str; 6] = ["url", "path", "query", "command", "content", "text"];
+
+/// Unknown MCP tools have no dedicated renderer, so show any well-known
+/// string fields as labeled values and leave the rest as JSON.
+fn render_mcp_input(format: ExportFormat, input: &Value, out: &mut String) {
+    let Some(object) = input.as_object() else {
+        out.push_str(&code_block(format, "json", &pretty_json(input)));
+        return;
+    };
+
+    let mut rest = object.clone();
+    for key in MCP_COMMON_FIELDS {
+        let Some(value) = object.get(key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        rest.remove(key);
+        let mut label = key.to_string();
+        label[..1].make_ascii_uppercase();
+        match key {
+            "command" => {
+                out.push_str(&field(format, &label, ""));
+                out.push_str(&code_block(format, "bash", value));
+            }
+            "content" | "text" => {
+                out.push_str(&field(format, &label, ""));
+                out.push_str(&paragraph(format, value));
+            }
+            _ => out.push_str(&field(format, &label, value)),
+        }
+    }
+
+    if !rest.is_empty() {
+        out.push_str(&code_block(
+            format,
+            "json",
+            &pretty_json(&Value::Object(rest)),
+        ));
+    }
+}
+
+/// A TodoWrite list as a GitHub-style checklist: `[x]` done, `[~]` in
+/// progress, `[ ]` pending, with the priority as a trailing tag.
+fn todo_checklist(format: ExportFormat, verbose: bool, todos: &[Value]) -> String {
+    let items: Vec<(String, Option<&str>)> = todos
+        .iter()
+        .map(|todo| {
+            let text = |key: &str| todo.get(key).and_then(|v| v.as_str());
+            let mark = match text("status") {
+                Some("completed") => "x",
+                Some("in_progress") => "~",
+                _ => " ",
+            };
+            let mut line = format!("[{}] {}", mark, text("content").unwrap_or(""));
+            if let Some(priority) = text("priority") {
+                line.push_str(&format!(" ({})", priority));
+            }
+            (line, text("id").filter(|_| verbose))
+        })
+        .collect();
+
+    match format {
+        ExportFormat::Markdown => {
+            let mut out = String::new();
+            for (line, id) in items {
+                out.push_str(&format!("- {}\n", line));
+                if let Some(id) = id {
+                    out.push_str(&format!("  ID: {}\n", id));
+                }
+            }
+            out.push('\n');
+            out
+        }
+        ExportFormat::Html => {
+            let mut out = String::from("<ul class=\"todos\">\n");
+            for (line, id) in items {
+                out.push_str(&format!("<li>{}", escape_html(&line)));
+                if let Some(id) = id {
+                    out.push_str(&format!(
+                        " <span class=\"meta\">ID: {}</span>",
+                        escape_html(id)
+                    ));
+                }
+                out.push_str("</li>\n");
+            }
+            out.push_str("</ul>\n");
+            out
+        }
+        ExportFormat::PlainText => {
+            let mut out = String::new();
+            for (line, id) in items {
+                out.push_str(&format!("    {}\n", line));
+                if let Some(id) = id {
+                    out.push_str(&format!("        ID: {}\n", id));
+                }
+            }
+            out.push('\n');
+            out
+        }
+    }
+}
+
+/// Render a line-level unified diff of an edit, so unchanged context lines
+/// appear once instead of as a full removal followed by a full addition.
+fn diff_block(format: ExportFormat, old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+
+    match format {
+        ExportFormat::Markdown | ExportFormat::PlainText => {
+            let unified = diff.unified_diff().context_radius(3).to_string();
+            code_block(format, "diff", &unified)
+        }
+        ExportFormat::Html => {
+            let mut out = String::from("<div class=\"diff\">\n");
+            for group in diff.grouped_ops(3) {
+                out.push_str("<div class=\"diff-hunk\">@@</div>\n");
+                for op in group {
+                    for change in diff.iter_changes(&op) {
+                        let (class, sign) = match change.tag() {
+                            ChangeTag::Delete => ("diff-removed", '-'),
+                            ChangeTag::Insert => ("diff-added", '+'),
+                            ChangeTag::Equal => ("diff-context", ' '),
+                        };
+                        out.push_str(&format!(
+                            "<div class=\"{}\">{}{}</div>\n",
+                            class,
+                            sign,
+                            escape_html(change.value().trim_end_matches('\n'))
+                        ));
+                    }
+                }
+            }
+            out.push_str("</div>\n");
+            out
+        }
+    }
+}
+
+/// A tool result beneath a heading. Failed results are marked so they can
+/// be found at a glance: quoted under a warning in Markdown, in a red box
+/// in HTML.
+fn render_tool_result(
+    format: ExportFormat,
+    options: &ExportOptions,
+    block: &Value,
+    language: &str,
+    out: &mut String,
+) {
+    if !is_error_result(block) {
+        out.push_str(&heading(format, 3, "📋 Tool Result"));
+        render_tool_output(format, options, block, language, out);
+        return;
+    }
+
+    let mut inner = String::new();
+    render_tool_output(format, options, block, language, &mut inner);
+    match format {
+        ExportFormat::Markdown => {
+            out.push_str(&heading(format, 3, "❌ Tool Error"));
+            out.push_str("> ⚠️ **The tool reported an error**\n>\n");
+            out.push_str(&prefix_lines(inner.trim_end(), "> "));
+        }
+        ExportFormat::Html => {
+            out.push_str("<div class=\"tool-error\">\n");
+            out.push_str(&heading(format, 3, "❌ Tool Error"));
+            out.push_str(&inner);
+            out.push_str("</div>\n");
+        }
+        ExportFormat::PlainText => {
+            out.push_str(&heading(format, 3, "❌ Tool Error"));
+            out.push_str(&inner);
+        }
+    }
+}
+
+fn render_tool_output(
+    format: ExportFormat,
+    options: &ExportOptions,
+    block: &Value,
+    language: &str,
+    out: &mut String,
+) {
+    let Some(Value::Array(parts)) = block.get("content") else {
+        out.push_str(&output_block(
+            format,
+            options,
+            language,
+            &tool_result_text(block),
+        ));
+        return;
+    };
+
+    if is_transcript(parts) {
+        render_transcript(format, options, parts, out);
+        return;
+    }
+
+    // Screenshots arrive as image parts alongside text; keep them out of the
+    // code block and render them inline instead.
+    let (images, texts): (Vec<&Value>, Vec<&Value>) = parts
+        .iter()
+        .partition(|p| p.get("type").and_then(|t| t.as_str()) == Some("image"));
+
+    if !texts.is_empty() || images.is_empty() {
+        let text = texts
+            .iter()
+            .map(|p| tool_result_part_text(p))
+            .collect::<Vec<_>>()
+            .join("\n");
+        out.push_str(&output_block(format, options, language, &text));
+    }
+    for image in images {
+        out.push_str(&image_block(format, image));
+    }
+}
+
+/// Whether tool result parts hold a conversation (as a Task subagent's
+/// result does) rather than just text and images.
+fn is_transcript(parts: &[Value]) -> bool {
+    parts.iter().any(|part| {
+        part.get("role").is_some()
+            || matches!(
+                part.get("type").and_then(|t| t.as_str()),
+                Some("tool_use" | "tool_result" | "thinking")
+            )
+    })
+}
+
+/// Render a subagent transcript indented beneath its Task call, with the
+/// same block rendering as the main conversation. Parts are either content
+/// blocks or whole messages with a `role` and their own `content`.
+fn render_transcript(
+    format: ExportFormat,
+    options: &ExportOptions,
+    parts: &[Value],
+    out: &mut String,
+) {
+    let blocks_of = |part: &Value| -> Vec<Value> {
+        match (part.get("role"), part.get("content")) {
+            (Some(_), Some(Value::Array(blocks))) => blocks.clone(),
+            (Some(_), Some(Value::String(text))) => vec![json!({"type": "text", "text": text})],
+            (Some(_), _) => Vec::new(),
+            (None, _) => vec![part.clone()],
+        }
+    };
+    let blocks: Vec<Vec<Value>> = parts.iter().map(blocks_of).collect();
+    let tools = correlate_blocks(blocks.iter().flatten());
+
+    let mut inner = String::new();
+    for (part, blocks) in parts.iter().zip(&blocks) {
+        match part.get("role").and_then(|r| r.as_str()) {
+            Some("user") if blocks.iter().any(|b| !is_tool_result(b)) => {
+                inner.push_str(&heading(format, 4, "👤 Subagent Prompt"))
+            }
+            Some("assistant") => inner.push_str(&heading(format, 4, "🤖 Subagent")),
+            _ => {}
+        }
+        for block in blocks {
+            render_block(format, options, block, &tools, &mut inner);
+        }
+    }
+
+    match format {
+        ExportFormat::Markdown => out.push_str(&prefix_lines(inner.trim_end(), "> ")),
+        ExportFormat::Html => {
+            out.push_str("<blockquote class=\"subagent\">\n");
+            out.push_str(&inner);
+            out.push_str("</blockquote>\n");
+        }
+        ExportFormat::PlainText => out.push_str(&prefix_lines(inner.trim_end(), "    ")),
+    }
+}
+
+fn is_tool_result(block: &Value) -> bool {
+    block.get("type").and_then(|t| t.as_str()) == Some("tool_result")
+}
+
+/// Prefix every line of `text` with `prefix`, trimming the trailing space
+/// from otherwise blank lines.
+fn prefix_lines(text: &str, prefix: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for line in text.lines() {
+        if line.is_empty() {
+            out.push_str(prefix.trim_end());
+        } else {
+            out.push_str(prefix);
+            out.push_str(line);
+        }
+        out.push('\n');
+    }
+    out.push('\n');
+    out
+}
+
+/// A code block for tool output; long HTML output is folded into a
+/// `<details>` element so the page stays navigable.
+fn output_block(
+    format: ExportFormat,
+    options: &ExportOptions,
+    language: &str,
+    body: &str,
+) -> String {
+    let body = &truncate_long_output(body, options.max_output_bytes);
+    let block = code_block(format, language, body);
+    let line_count = body.trim_end().lines().count();
+    if format != ExportFormat::Html || line_count <= options.collapse_lines {
+        return block;
+    }
+    format!(
+        "<details><summary>Show output ({} lines)</summary>\n{}</details>\n",
+        line_count, block
+    )
+}
+
+/// Lines longer than this are broken up by `truncate_long_output`.
+const LONG_LINE_CHARS: usize = 1000;
+
+/// Cap `content` at `max_bytes` (on a char boundary) with a
+/// "… (truncated N bytes)" marker, and break any line longer than
+/// `LONG_LINE_CHARS` into chunks so renderers don't choke on it.
+pub fn truncate_long_output(content: &str, max_bytes: usize) -> String {
+    let mut end = content.len().min(max_bytes);
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    let kept = &content[..end];
+
+    let mut out = String::with_capacity(kept.len());
+    for (i, line) in kept.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let mut chars = line.chars().peekable();
+        while chars.peek().is_some() {
+            out.extend(chars.by_ref().take(LONG_LINE_CHARS));
+            if chars.peek().is_some() {
+                out.push('\n');
+            }
+        }
+    }
+
+    if end < content.len() {
+        out.push_str(&format!("\n… (truncated {} bytes)", content.len() - end));
+    }
+    out
+}
+
+fn image_block(format: ExportFormat, image: &Value) -> String {
+    let source = image.get("source");
+    let media_type = source
+        .and_then(|s| s.get("media_type"))
+        .and_then(|m| m.as_str())
+        .unwrap_or("image");
+    let data = source
+        .and_then(|s| s.get("data"))
+        .and_then(|d| d.as_str())
+        .filter(|_| source.and_then(|s| s.get("type")).and_then(|t| t.as_str()) == Some("base64"));
+
+    match (format, data) {
+        (ExportFormat::Markdown, Some(data)) => {
+            format!("![screenshot](data:{};base64,{})\n\n", media_type, data)
+        }
+        (ExportFormat::Html, Some(data)) => format!(
+            "<img class=\"screenshot\" alt=\"screenshot\" src=\"data:{};base64,{}\">\n",
+            escape_html(media_type),
+            escape_html(data)
+        ),
+        _ => paragraph(format, &image_description(image)),
+    }
+}
+
+/// Short description of an image part, e.g. "🖼️ [image/png, 12345 bytes]".
+fn image_description(image: &Value) -> String {
+    let source = image.get("source");
+    let media_type = source
+        .and_then(|s| s.get("media_type"))
+        .and_then(|m| m.as_str())
+        .unwrap_or("image");
+    match source.and_then(|s| s.get("data")).and_then(|d| d.as_str()) {
+        Some(data) => format!("🖼️ [{}, {} bytes]", media_type, base64_decoded_len(data)),
+        None => format!("🖼️ [{}]", media_type),
+    }
+}
+
+fn base64_decoded_len(data: &str) -> usize {
+    let padding = data.bytes().rev().take_while(|&b| b == b'=').count();
+    (data.len() / 4 * 3).saturating_sub(padding)
+}
+
+fn input_path(input: &Value) -> Option<&str> {
+    input.get("file_path").and_then(|p| p.as_str())
+}
+
+/// Fence language for a file path, based on its extension (or file name for
+/// extensionless files like `Dockerfile`).
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    match file_name {
+        "Dockerfile" => return Some("dockerfile"),
+        "Makefile" | "makefile" => return Some("makefile"),
+        _ => {}
+    }
+
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let language = match extension.to_ascii_lowercase().as_str() {
+        "rs" => "rust",
+        "py" => "python",
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "go" => "go",
+        "rb" => "ruby",
+        "java" => "java",
+        "kt" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "php" => "php",
+        "sh" | "bash" | "zsh" => "bash",
+        "json" | "jsonl" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "md" => "markdown",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "sql" => "sql",
+        "xml" => "xml",
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Extract the textual output of a tool_result block, joining text parts
+/// when the content is an array.
+pub fn tool_result_text(block: &Value) -> String {
+    match block.get("content") {
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .map(tool_result_part_text)
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Some(other) => pretty_json(other),
+        None => String::new(),
+    }
+}
+
+fn tool_result_part_text(part: &Value) -> String {
+    if part.get("type").and_then(|t| t.as_str()) == Some("image") {
+        return image_description(part);
+    }
+    match part.get("text").and_then(|t| t.as_str()) {
+        Some(text) => text.to_string(),
+        None => part.to_string(),
+    }
+}
+
+fn format_timestamp(timestamp: Option<DateTime<Utc>>, timezone: Tz) -> String {
+    timestamp
+        .map(|dt| {
+            dt.with_timezone(&timezone)
+                .format("%Y-%m-%d %H:%M:%S %Z")
+                .to_string()
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn pretty_json(value: &Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn heading(format: ExportFormat, level: usize, text: &str) -> String {
+    match format {
+        ExportFormat::Markdown => format!("{} {}\n\n", "#".repeat(level), text),
+        ExportFormat::Html => format!("<h{0}>{1}</h{0}>\n", level, escape_html(text)),
+        ExportFormat::PlainText => {
+            let underline = if level == 1 { '=' } else { '-' };
+            format!(
+                "{}\n{}\n\n",
+                text,
+                underline.to_string().repeat(text.chars().count())
+            )
+        }
+    }
+}
+
+fn field(format: ExportFormat, label: &str, value: &str) -> String {
+    match format {
+        ExportFormat::Markdown => format!("**{}:** {}\n\n", label, value),
+        ExportFormat::Html => format!(
+            "<p><strong>{}:</strong> {}</p>\n",
+            escape_html(label),
+            escape_html(value)
+        ),
+        ExportFormat::PlainText => format!("{}: {}\n", label, value),
+    }
+}
+
+fn meta(format: ExportFormat, text: &str) -> String {
+    match format {
+        ExportFormat::Markdown => format!("*{}*\n\n", text),
+        ExportFormat::Html => format!("<div class=\"meta\">{}</div>\n", escape_html(text)),
+        ExportFormat::PlainText => format!("[{}]\n", text),
+    }
+}
+
+fn paragraph(format: ExportFormat, text: &str) -> String {
+    if text.trim().is_empty() {
+        return String::new();
+    }
+    match format {
+        ExportFormat::Markdown => format!("{}\n\n", text.trim_end()),
+        ExportFormat::Html => format!(
+            "<div class=\"text\">{}</div>\n",
+            escape_html(text.trim_end())
+        ),
+        ExportFormat::PlainText => format!("{}\n\n", text.trim_end()),
+    }
+}
+
+fn code_block(format: ExportFormat, language: &str, body: &str) -> String {
+    match format {
+        ExportFormat::Markdown => format!("```{}\n{}\n```\n\n", language, body.trim_end()),
+        ExportFormat::Html => format!("<pre><code>{}</code></pre>\n", escape_html(body.trim_end())),
+        ExportFormat::PlainText => {
+            let indented = body
+                .trim_end()
+                .lines()
+                .map(|line| format!("    {}", line))
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("{}\n\n", indented)
+        }
+    }
+}
+
+fn separator(format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => "\n---\n\n".to_string(),
+        ExportFormat::Html => "<hr>\n".to_string(),
+        ExportFormat::PlainText => "\n".to_string(),
+    }
+}
+
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,sans-serif;max-width:960px;margin:0 auto;padding:20px;color:#333}\
+h2{border-bottom:1px solid #eee;padding-bottom:4px}\
+.meta{color:#888;font-size:0.85em;margin-bottom:8px}\
+.text{white-space:pre-wrap;margin-bottom:12px}\
+pre{background:#f6f8fa;padding:12px;border-radius:6px;overflow-x:auto}\
+.diff{font-family:monospace;white-space:pre;background:#f6f8fa;padding:12px;border-radius:6px;overflow-x:auto;margin-bottom:12px}\
+.diff-added{background:#e6ffec;color:#1a7f37}\
+.diff-removed{background:#ffebe9;color:#cf222e}\
+.diff-hunk{color:#888}\
+ul.todos{list-style:none;padding-left:0;font-family:monospace}\
+details{margin-bottom:12px}summary{cursor:pointer;color:#555}\
+blockquote.subagent{margin:0 0 12px 0;padding-left:16px;border-left:3px solid #fed7aa}\
+.tool-error{border:1px solid #f5c2c7;border-left:4px solid #cf222e;background:#fff5f5;border-radius:6px;padding:0 12px;margin-bottom:12px}\
+.tool-error h3{color:#cf222e}\
+img.screenshot{max-width:100%;border:1px solid #eee;border-radius:6px;margin-bottom:12px}";
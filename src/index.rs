@@ -0,0 +1,495 @@
+// ABOUTME: Optional SQLite index of every log entry for fast search and project stats
+// ABOUTME: Built once from the projects directory, then kept current from watch events
+
+use crate::{
+    entry_search_text, flatten_message_blocks, match_snippet, project_dirs, session_files,
+    tool_error_count, trim_log_line, ContentBlock, LogEntry, ProjectStats, SearchHit, TokenUsage,
+    WatchEvent, DEFAULT_SCAN_DEPTH, UNKNOWN_MODEL,
+};
+use chrono::{DateTime, SecondsFormat, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast;
+
+/// Characters of entry text kept in `entries.text_preview`.
+const PREVIEW_CHARS: usize = 200;
+
+/// Bumped whenever the tables below change; an index built with another
+/// version is rebuilt from scratch.
+const SCHEMA_VERSION: &str = "3";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS files (
+    project TEXT NOT NULL,
+    session TEXT NOT NULL,
+    indexed_bytes INTEGER NOT NULL,
+    indexed_lines INTEGER NOT NULL,
+    PRIMARY KEY (project, session)
+);
+CREATE TABLE IF NOT EXISTS entries (
+    project TEXT NOT NULL,
+    session TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    uuid TEXT,
+    timestamp TEXT,
+    entry_type TEXT,
+    tool_name TEXT,
+    model TEXT,
+    text_preview TEXT NOT NULL,
+    search_text TEXT NOT NULL,
+    has_usage INTEGER NOT NULL DEFAULT 0,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_creation_input_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_input_tokens INTEGER NOT NULL DEFAULT 0,
+    error_count INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (project, session, line)
+);
+CREATE TABLE IF NOT EXISTS tool_calls (
+    project TEXT NOT NULL,
+    session TEXT NOT NULL,
+    line INTEGER NOT NULL,
+    tool_name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS tool_calls_by_entry ON tool_calls (project, session, line);
+";
+
+/// An on-disk SQLite index of the entries under one projects directory.
+///
+/// Sessions are indexed by byte offset, so appends (the only way Claude
+/// Code writes logs) only parse the new lines; a file that shrank is
+/// reindexed from scratch.
+pub struct SessionIndex {
+    conn: Mutex<Connection>,
+    projects_dir: PathBuf,
+    scan_depth: usize,
+}
+
+impl std::fmt::Debug for SessionIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionIndex")
+            .field("projects_dir", &self.projects_dir)
+            .field("scan_depth", &self.scan_depth)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SessionIndex {
+    /// Open (or create) the index database at `db_path` for `projects_dir`.
+    /// An index built for a different projects directory is cleared.
+    pub fn open(db_path: &Path, projects_dir: PathBuf) -> rusqlite::Result<Self> {
+        if let Some(parent) = db_path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let conn = Connection::open(db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL)",
+        )?;
+        let meta = |key: &str| -> rusqlite::Result<Option<String>> {
+            conn.query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+        };
+
+        if meta("schema_version")?.as_deref() != Some(SCHEMA_VERSION) {
+            conn.execute_batch(
+                "DROP TABLE IF EXISTS files; DROP TABLE IF EXISTS entries; DROP TABLE IF EXISTS tool_calls;",
+            )?;
+            conn.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('schema_version', ?1)",
+                params![SCHEMA_VERSION],
+            )?;
+        }
+        conn.execute_batch(SCHEMA)?;
+
+        let dir = projects_dir.to_string_lossy().to_string();
+        if meta("projects_dir")?.as_deref() != Some(dir.as_str()) {
+            conn.execute_batch("DELETE FROM files; DELETE FROM entries; DELETE FROM tool_calls;")?;
+            conn.execute(
+                "INSERT OR REPLACE INTO meta (key, value) VALUES ('projects_dir', ?1)",
+                params![dir],
+            )?;
+        }
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            projects_dir,
+            scan_depth: DEFAULT_SCAN_DEPTH,
+        })
+    }
+
+    /// Index sessions in folders nested up to `scan_depth` levels deep in
+    /// each project, as `AppState::with_scan_depth` lists them.
+    pub fn with_scan_depth(mut self, scan_depth: usize) -> Self {
+        self.scan_depth = scan_depth.max(1);
+        self
+    }
+
+    /// Where the index lives by default:
+    /// `$XDG_CACHE_HOME/cc-log-viewer/index.sqlite3`, falling back to
+    /// `~/.cache`.
+    pub fn default_path() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        Some(cache_home.join("cc-log-viewer").join("index.sqlite3"))
+    }
+
+    /// Bring every session up to date and drop sessions whose files are
+    /// gone, returning how many sessions are indexed.
+    pub fn sync(&self) -> rusqlite::Result<usize> {
+        let mut seen = HashSet::new();
+        for project_dir in project_dirs(&self.projects_dir) {
+            let project = project_dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            for file in session_files(&project_dir, self.scan_depth).filter_map(|f| f.ok()) {
+                self.update_session(&project, &file.id)?;
+                seen.insert((project.clone(), file.id));
+            }
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let indexed: Vec<(String, String)> = conn
+            .prepare("SELECT project, session FROM files")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+        for (project, session) in indexed {
+            if !seen.contains(&(project.clone(), session.clone())) {
+                remove_session(&conn, &project, &session)?;
+            }
+        }
+        Ok(seen.len())
+    }
+
+    /// Index whatever has been appended to one session since it was last
+    /// indexed, returning how many lines were read.
+    pub fn update_session(&self, project: &str, session: &str) -> rusqlite::Result<usize> {
+        let path = self
+            .projects_dir
+            .join(project)
+            .join(format!("{}.jsonl", session));
+        let mut conn = self.conn.lock().unwrap();
+
+        let Ok(metadata) = fs::metadata(&path) else {
+            remove_session(&conn, project, session)?;
+            return Ok(0);
+        };
+
+        let (mut offset, mut lines) = conn
+            .query_row(
+                "SELECT indexed_bytes, indexed_lines FROM files WHERE project = ?1 AND session = ?2",
+                params![project, session],
+                |row| Ok((row.get::<_, u64>(0)?, row.get::<_, usize>(1)?)),
+            )
+            .optional()?
+            .unwrap_or((0, 0));
+        if metadata.len() < offset {
+            remove_session(&conn, project, session)?;
+            (offset, lines) = (0, 0);
+        }
+
+        let mut data = Vec::new();
+        if let Ok(mut file) = File::open(&path) {
+            if file.seek(SeekFrom::Start(offset)).is_ok() {
+                let _ = file.read_to_end(&mut data);
+            }
+        }
+
+        let tx = conn.transaction()?;
+        let mut read = 0;
+        let mut rest = data.as_slice();
+        while !rest.is_empty() {
+            let (line, terminated) = match rest.iter().position(|&b| b == b'\n') {
+                Some(end) => (&rest[..end], true),
+                None => (rest, false),
+            };
+            let line_number = lines + 1;
+            if let Ok(entry) = serde_json::from_slice::<LogEntry>(trim_log_line(line)) {
+                insert_entry(&tx, project, session, line_number, &entry)?;
+            }
+            read += 1;
+            if !terminated {
+                // A line still being written: index what parses now, but
+                // read it again next time in case it grows.
+                break;
+            }
+            offset += line.len() as u64 + 1;
+            lines = line_number;
+            rest = &rest[line.len() + 1..];
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO files (project, session, indexed_bytes, indexed_lines)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![project, session, offset, lines],
+        )?;
+        tx.commit()?;
+        Ok(read)
+    }
+
+    /// Statistics for one project, equivalent to `crate::project_stats`.
+    pub fn project_stats(&self, project: &str) -> rusqlite::Result<ProjectStats> {
+        let conn = self.conn.lock().unwrap();
+        let mut stats = ProjectStats {
+            project_name: project.to_string(),
+            ..ProjectStats::default()
+        };
+
+        stats.total_sessions = conn.query_row(
+            "SELECT COUNT(*) FROM files WHERE project = ?1",
+            params![project],
+            |row| row.get(0),
+        )?;
+
+        let (messages, first, last, input, output, cache_creation, cache_read, errors) = conn
+            .query_row(
+                "SELECT
+                     COUNT(CASE WHEN entry_type IN ('user', 'assistant') THEN 1 END),
+                     MIN(timestamp),
+                     MAX(timestamp),
+                     COALESCE(SUM(input_tokens), 0),
+                     COALESCE(SUM(output_tokens), 0),
+                     COALESCE(SUM(cache_creation_input_tokens), 0),
+                     COALESCE(SUM(cache_read_input_tokens), 0),
+                     COALESCE(SUM(error_count), 0)
+                 FROM entries WHERE project = ?1",
+                params![project],
+                |row| {
+                    Ok((
+                        row.get::<_, usize>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, u64>(3)?,
+                        row.get::<_, u64>(4)?,
+                        row.get::<_, u64>(5)?,
+                        row.get::<_, u64>(6)?,
+                        row.get::<_, usize>(7)?,
+                    ))
+                },
+            )?;
+        stats.total_messages = messages;
+        stats.error_count = errors;
+        stats.first_activity = first.as_deref().and_then(parse_timestamp);
+        stats.last_activity = last.as_deref().and_then(parse_timestamp);
+        stats.token_usage = TokenUsage {
+            input_tokens: input,
+            output_tokens: output,
+            cache_creation_input_tokens: cache_creation,
+            cache_read_input_tokens: cache_read,
+        };
+
+        let mut query = conn.prepare(
+            "SELECT COALESCE(model, ?2),
+                 SUM(input_tokens),
+                 SUM(output_tokens),
+                 SUM(cache_creation_input_tokens),
+                 SUM(cache_read_input_tokens)
+             FROM entries WHERE project = ?1 AND has_usage
+             GROUP BY COALESCE(model, ?2)",
+        )?;
+        let model_usage = query.query_map(params![project, UNKNOWN_MODEL], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                TokenUsage {
+                    input_tokens: row.get(1)?,
+                    output_tokens: row.get(2)?,
+                    cache_creation_input_tokens: row.get(3)?,
+                    cache_read_input_tokens: row.get(4)?,
+                },
+            ))
+        })?;
+        for usage in model_usage {
+            let (model, usage) = usage?;
+            stats.usage_by_model.insert(model, usage);
+        }
+
+        let mut query = conn.prepare(
+            "SELECT tool_name, COUNT(*) FROM tool_calls WHERE project = ?1 GROUP BY tool_name",
+        )?;
+        let tool_counts = query.query_map(params![project], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, usize>(1)?))
+        })?;
+        for count in tool_counts {
+            let (name, count) = count?;
+            stats.total_tool_calls += count;
+            stats.tool_calls.insert(name, count);
+        }
+
+        Ok(stats)
+    }
+
+    /// Case-insensitive search, equivalent to `crate::search_project` across
+    /// all projects (or just `project`) in project and session order.
+    pub fn search(
+        &self,
+        query: &str,
+        project: Option<&str>,
+        limit: usize,
+    ) -> rusqlite::Result<Vec<SearchHit>> {
+        let needle = query.to_lowercase();
+        // LIKE only folds ASCII case, so it can narrow the rows for ASCII
+        // queries; `match_snippet` makes the final call either way.
+        let pattern = if needle.is_ascii() {
+            format!("%{}%", escape_like(&needle))
+        } else {
+            "%".to_string()
+        };
+
+        let conn = self.conn.lock().unwrap();
+        let mut statement = conn.prepare(
+            "SELECT project, session, line, timestamp, search_text FROM entries
+             WHERE (?1 IS NULL OR project = ?1) AND search_text LIKE ?2 ESCAPE '\\'
+             ORDER BY project, session, line",
+        )?;
+        let mut rows = statement.query(params![project, pattern])?;
+
+        let mut hits = Vec::new();
+        while hits.len() < limit {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+            let text: String = row.get(4)?;
+            if let Some(snippet) = match_snippet(&text, &needle) {
+                hits.push(SearchHit {
+                    project: row.get(0)?,
+                    session: row.get(1)?,
+                    line: row.get(2)?,
+                    snippet,
+                    timestamp: row
+                        .get::<_, Option<String>>(3)?
+                        .as_deref()
+                        .and_then(parse_timestamp),
+                });
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Keep the index current from watch events until the channel closes.
+    /// A lagged receiver falls back to a full `sync`.
+    pub fn follow(
+        self: Arc<Self>,
+        mut events: broadcast::Receiver<WatchEvent>,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                let index = Arc::clone(&self);
+                let result = match events.recv().await {
+                    Ok(event) if event.event_type == "log_entry" => {
+                        let Some(session) = event.session else {
+                            continue;
+                        };
+                        tokio::task::spawn_blocking(move || {
+                            index.update_session(&event.project, &session).map(|_| ())
+                        })
+                        .await
+                    }
+                    Ok(_) => continue,
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        tokio::task::spawn_blocking(move || index.sync().map(|_| ())).await
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                if let Ok(Err(e)) = result {
+                    tracing::warn!(error = %e, "failed to update session index");
+                }
+            }
+        })
+    }
+}
+
+fn insert_entry(
+    conn: &Connection,
+    project: &str,
+    session: &str,
+    line: usize,
+    entry: &LogEntry,
+) -> rusqlite::Result<()> {
+    let tool_names: Vec<String> = flatten_message_blocks(entry)
+        .into_iter()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { name, .. } => Some(name),
+            _ => None,
+        })
+        .collect();
+    let text = entry_search_text(entry);
+    let preview: String = text.chars().take(PREVIEW_CHARS).collect();
+    let usage = TokenUsage::from_entry(entry);
+    let has_usage = usage.is_some();
+    let usage = usage.unwrap_or_default();
+
+    conn.execute(
+        "INSERT OR REPLACE INTO entries (
+             project, session, line, uuid, timestamp, entry_type, tool_name, model,
+             text_preview, search_text, has_usage, input_tokens, output_tokens,
+             cache_creation_input_tokens, cache_read_input_tokens, error_count
+         ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16)",
+        params![
+            project,
+            session,
+            line,
+            entry.uuid,
+            entry
+                .timestamp
+                .map(|ts| ts.to_rfc3339_opts(SecondsFormat::Micros, true)),
+            entry.entry_type,
+            tool_names.first(),
+            entry.model(),
+            preview,
+            text,
+            has_usage,
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_creation_input_tokens,
+            usage.cache_read_input_tokens,
+            tool_error_count(entry),
+        ],
+    )?;
+
+    conn.execute(
+        "DELETE FROM tool_calls WHERE project = ?1 AND session = ?2 AND line = ?3",
+        params![project, session, line],
+    )?;
+    for name in tool_names {
+        conn.execute(
+            "INSERT INTO tool_calls (project, session, line, tool_name) VALUES (?1, ?2, ?3, ?4)",
+            params![project, session, line, name],
+        )?;
+    }
+    Ok(())
+}
+
+fn remove_session(conn: &Connection, project: &str, session: &str) -> rusqlite::Result<()> {
+    for table in ["files", "entries", "tool_calls"] {
+        conn.execute(
+            &format!("DELETE FROM {} WHERE project = ?1 AND session = ?2", table),
+            params![project, session],
+        )?;
+    }
+    Ok(())
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|ts| ts.with_timezone(&Utc))
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -2,23 +2,45 @@
 // ABOUTME: Exposes types and handlers for real-time WebSocket monitoring and rich tool rendering
 
 use axum::{
+    body::Body,
     extract::{
         ws::{Message, WebSocket},
-        Path, State, WebSocketUpgrade,
+        Path, Query, Request, State, WebSocketUpgrade,
+    },
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{
+        sse::{Event as SseEvent, KeepAlive, Sse},
+        Html, IntoResponse, Json, Response,
     },
-    http::StatusCode,
-    response::{Html, Json, Response},
 };
 use chrono::{DateTime, Utc};
-use dashmap::DashMap;
+use dashmap::{DashMap, DashSet};
 use futures_util::{SinkExt, StreamExt};
+use include_dir::{include_dir, Dir};
+use index::SessionIndex;
 use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use pricing::PricingTable;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::{fs, path::PathBuf, sync::Arc, time::SystemTime};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fs,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Path as FsPath, PathBuf},
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
 use tokio::sync::broadcast;
+use tool_config::ToolConfig;
 use walkdir::WalkDir;
 
+pub mod config;
+pub mod export;
+pub mod index;
+pub mod pricing;
+pub mod tool_config;
 pub mod tui;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,21 +69,196 @@ pub struct LogEntry {
     pub tool_use_result: Option<Value>,
 }
 
+impl LogEntry {
+    /// The model that produced an assistant message (`message.model`), when
+    /// recorded.
+    pub fn model(&self) -> Option<&str> {
+        if self.entry_type.as_deref() != Some("assistant") {
+            return None;
+        }
+        self.message.as_ref()?.get("model")?.as_str()
+    }
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct ProjectSummary {
+    /// Directory name, used for API routing.
     pub name: String,
     pub path: String,
+    /// The project's real working directory, for display.
+    pub display_path: String,
     pub session_count: usize,
     pub latest_activity: Option<DateTime<Utc>>,
 }
 
+/// Key in per-model usage for entries that don't name their model.
+pub const UNKNOWN_MODEL: &str = "unknown";
+
+/// Token counts summed from `message.usage` on assistant entries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Usage recorded on a single entry, if any.
+    pub fn from_entry(entry: &LogEntry) -> Option<Self> {
+        let usage = entry.message.as_ref()?.get("usage")?;
+        let field = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(Self {
+            input_tokens: field("input_tokens"),
+            output_tokens: field("output_tokens"),
+            cache_creation_input_tokens: field("cache_creation_input_tokens"),
+            cache_read_input_tokens: field("cache_read_input_tokens"),
+        })
+    }
+
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    /// Add this entry's usage, if any, to `total` and to its model's share
+    /// in `by_model`.
+    pub fn accumulate(
+        entry: &LogEntry,
+        total: &mut TokenUsage,
+        by_model: &mut BTreeMap<String, TokenUsage>,
+    ) {
+        if let Some(usage) = Self::from_entry(entry) {
+            total.add(&usage);
+            by_model
+                .entry(entry.model().unwrap_or(UNKNOWN_MODEL).to_string())
+                .or_default()
+                .add(&usage);
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProjectStats {
+    pub project_name: String,
+    pub total_sessions: usize,
+    pub total_messages: usize,
+    pub total_tool_calls: usize,
+    pub tool_calls: BTreeMap<String, usize>,
+    pub token_usage: TokenUsage,
+    /// `token_usage` split by the model that used it.
+    pub usage_by_model: BTreeMap<String, TokenUsage>,
+    /// Filled in from the server's pricing table.
+    pub estimated_cost_usd: f64,
+    pub first_activity: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Tool results that reported a failure.
+    pub error_count: usize,
+}
+
+/// Lightweight facts about one session, for showing before its log loads.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionMeta {
+    pub session_id: String,
+    /// User and assistant messages only.
+    pub message_count: usize,
+    pub token_usage: TokenUsage,
+    /// `token_usage` split by the model that used it.
+    pub usage_by_model: BTreeMap<String, TokenUsage>,
+    /// Filled in from the server's pricing table.
+    pub estimated_cost_usd: f64,
+    /// Tool calls per tool name.
+    pub tools_used: BTreeMap<String, usize>,
+    /// Every `message.model` seen on assistant entries, in first-use order.
+    pub models: Vec<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub has_sidechains: bool,
+    /// Tool results that reported a failure.
+    pub error_count: usize,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SessionSummary {
     pub id: String,
     pub summary: String,
+    /// Same as `started_at`, kept for existing clients; falls back to now
+    /// when the session has no timestamps at all.
     pub timestamp: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    /// User and assistant messages only.
     pub message_count: usize,
+    /// Every parseable entry, including summaries and meta entries.
+    pub entry_count: usize,
     pub project_name: String,
+    /// Working directory recorded on the first entry that has one.
+    pub cwd: Option<String>,
+    /// Filled in from the server's pricing table.
+    pub estimated_cost_usd: f64,
+    #[serde(skip)]
+    pub usage_by_model: BTreeMap<String, TokenUsage>,
+    /// `(leafUuid, summary)` pairs in this file that describe a different
+    /// session; see `resolve_linked_summaries`.
+    #[serde(skip)]
+    pub(crate) linked_summaries: Vec<(String, String)>,
+    /// Hashed uuids of this session's entries, in file order, kept only
+    /// while it has no summary of its own.
+    #[serde(skip)]
+    pub(crate) unsummarized_uuids: Vec<u64>,
+}
+
+/// A single content block of a message, in the order Claude produced it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: Option<String>,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        id: Option<String>,
+        content: Value,
+    },
+    Thinking {
+        thinking: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EntryBlocks {
+    pub uuid: Option<String>,
+    #[serde(rename = "type")]
+    pub entry_type: Option<String>,
+    pub role: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub blocks: Vec<ContentBlock>,
+    /// Filled in from the server's pricing table for entries with usage.
+    pub estimated_cost_usd: Option<f64>,
+}
+
+/// A main-thread entry together with the sidechain (subagent) entries that
+/// branched off it.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationNode {
+    pub entry: LogEntry,
+    pub children: Vec<ConversationNode>,
+    /// Set on a sidechain entry whose parent is not in the session, which is
+    /// promoted to the top level so its subagent work still shows up.
+    pub orphaned: bool,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -89,25 +286,216 @@ pub struct WatchManager {
     _watcher: RecommendedWatcher,
     active_sessions: Arc<DashMap<String, SessionState>>,
     broadcast_tx: broadcast::Sender<WatchEvent>,
+    broadcast_capacity: usize,
     projects_dir: PathBuf,
+    read_tx: std::sync::mpsc::Sender<ReaderMessage>,
+    watcher_alive: Arc<std::sync::atomic::AtomicBool>,
+    reader_alive: Arc<std::sync::atomic::AtomicBool>,
+}
+
+/// Work for the session reader thread.
+#[derive(Debug)]
+enum ReaderMessage {
+    /// A session file was written to.
+    Read(PathBuf),
+    Stop,
+}
+
+/// Held by a background thread (or the callback it runs) and cleared when
+/// dropped, including by a panic, so `WatchManager::is_alive` sees the
+/// thread go.
+struct AliveFlag(Arc<std::sync::atomic::AtomicBool>);
+
+impl AliveFlag {
+    fn new() -> (Self, Arc<std::sync::atomic::AtomicBool>) {
+        let alive = Arc::new(std::sync::atomic::AtomicBool::new(true));
+        (Self(alive.clone()), alive)
+    }
+}
+
+impl Drop for AliveFlag {
+    fn drop(&mut self) {
+        self.0.store(false, std::sync::atomic::Ordering::Relaxed);
+    }
+}
+
+/// A session's read position as saved between runs.
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedPosition {
+    last_position: u64,
+    last_modified: SystemTime,
+}
+
+/// How long position changes may sit in memory before being written out.
+const POSITIONS_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// How long to wait for further events on a file before reading it.
+const READ_COALESCE_WINDOW: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Entries broadcast per session per read.
+const MAX_ENTRIES_PER_READ: usize = 10;
+
+/// Watch events buffered for subscribers before the slowest starts missing
+/// them (and is told so with a `lagged` event).
+pub const DEFAULT_BROADCAST_CAPACITY: usize = 1000;
+
+/// How many directory levels below a project are searched for session
+/// files. Claude Code keeps them directly in the project directory.
+pub const DEFAULT_SCAN_DEPTH: usize = 1;
+
+/// The folder inside a project that archived sessions are moved to. It is
+/// never scanned, so they drop out of every listing.
+pub const ARCHIVE_DIR: &str = "archived";
+
+/// Whether `path` lies in the archive folder of a project under
+/// `projects_dir`.
+fn is_archived(projects_dir: &FsPath, path: &FsPath) -> bool {
+    path.strip_prefix(projects_dir)
+        .ok()
+        .and_then(|relative| relative.components().nth(1))
+        .is_some_and(|folder| folder.as_os_str() == ARCHIVE_DIR)
+}
+
+/// The id of the session logged at `path` inside `project_dir`: its path
+/// relative to the project without the `.jsonl` extension, so sessions in
+/// nested folders get ids like `2024-06-01/4f1c2e9a`.
+pub fn session_id_for(project_dir: &FsPath, path: &FsPath) -> Option<String> {
+    let relative = path.strip_prefix(project_dir).ok()?.with_extension("");
+    let parts = relative
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+/// The project name and session id of a session file under `projects_dir`.
+/// Files outside it fall back to their parent directory and file stem.
+fn session_path_parts(projects_dir: &FsPath, path: &FsPath) -> Option<(String, String)> {
+    if let Ok(relative) = path.strip_prefix(projects_dir) {
+        let project = relative.components().next()?.as_os_str().to_str()?;
+        let session = session_id_for(&projects_dir.join(project), path)?;
+        return Some((project.to_string(), session));
+    }
+    let project = path.parent()?.file_name()?.to_str()?;
+    let session = path.file_stem()?.to_str()?;
+    Some((project.to_string(), session.to_string()))
+}
+
+/// A session log found by `session_files`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionFile {
+    /// The session id, as given by `session_id_for`.
+    pub id: String,
+    pub path: PathBuf,
+}
+
+/// Every session log in `project_dir`, down to `scan_depth` levels, in path
+/// order, skipping the project's `ARCHIVE_DIR`. This is the one place that
+/// decides what counts as a session, so listings, stats, search, the index
+/// and exports all agree.
+pub fn session_files(
+    project_dir: &FsPath,
+    scan_depth: usize,
+) -> impl Iterator<Item = Result<SessionFile, walkdir::Error>> {
+    let project_dir = project_dir.to_path_buf();
+    WalkDir::new(&project_dir)
+        .min_depth(1)
+        .max_depth(scan_depth.max(1))
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| !(entry.depth() == 1 && entry.file_name() == ARCHIVE_DIR))
+        .filter_map(move |entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => return Some(Err(e)),
+            };
+            let is_log = entry.file_type().is_file()
+                && entry.path().extension().is_some_and(|ext| ext == "jsonl");
+            if !is_log {
+                return None;
+            }
+            let id = session_id_for(&project_dir, entry.path())?;
+            Some(Ok(SessionFile {
+                id,
+                path: entry.into_path(),
+            }))
+        })
+}
+
+/// The project directories directly inside `projects_dir`, in name order.
+pub fn project_dirs(projects_dir: &FsPath) -> Vec<PathBuf> {
+    WalkDir::new(projects_dir)
+        .min_depth(1)
+        .max_depth(1)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .map(|e| e.into_path())
+        .collect()
 }
 
 impl WatchManager {
     pub fn new(projects_dir: PathBuf) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let (broadcast_tx, _) = broadcast::channel(1000);
+        Self::with_positions_file(projects_dir, None)
+    }
+
+    /// Like `new`, but resumes session read positions from `positions_file`
+    /// (when given) and keeps it up to date, so a restart doesn't replay
+    /// whole sessions on their next write.
+    pub fn with_positions_file(
+        projects_dir: PathBuf,
+        positions_file: Option<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::with_options(projects_dir, positions_file, DEFAULT_BROADCAST_CAPACITY)
+    }
+
+    /// Like `with_positions_file`, buffering up to `broadcast_capacity`
+    /// events for subscribers instead of `DEFAULT_BROADCAST_CAPACITY`.
+    pub fn with_options(
+        projects_dir: PathBuf,
+        positions_file: Option<PathBuf>,
+        broadcast_capacity: usize,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if broadcast_capacity == 0 {
+            return Err("broadcast capacity must be at least 1".into());
+        }
+        let (broadcast_tx, _) = broadcast::channel(broadcast_capacity);
         let active_sessions = Arc::new(DashMap::new());
+        let positions_dirty = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+        if let Some(path) = &positions_file {
+            Self::load_positions(path, &projects_dir, &active_sessions);
+            Self::spawn_positions_writer(
+                path.clone(),
+                Arc::downgrade(&active_sessions),
+                positions_dirty.clone(),
+            );
+        }
 
-        let tx_clone = broadcast_tx.clone();
-        let sessions_clone = active_sessions.clone();
-        let projects_dir_clone = projects_dir.clone();
+        let (read_tx, read_rx) = std::sync::mpsc::channel();
+        let (reader_flag, reader_alive) = AliveFlag::new();
+        Self::spawn_session_reader(
+            projects_dir.clone(),
+            read_rx,
+            broadcast_tx.clone(),
+            broadcast_capacity,
+            active_sessions.clone(),
+            positions_dirty,
+            reader_flag,
+        );
 
+        let watched_dir = projects_dir.clone();
+        let event_tx = broadcast_tx.clone();
+        let watcher_read_tx = read_tx.clone();
+        let (watcher_flag, watcher_alive) = AliveFlag::new();
         let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
-            if let Ok(event) = res {
-                if let Err(e) =
-                    Self::handle_fs_event(event, &tx_clone, &sessions_clone, &projects_dir_clone)
-                {
-                    eprintln!("Error handling file system event: {}", e);
+            let _alive = &watcher_flag;
+            match res {
+                Ok(event) => {
+                    Self::handle_fs_event(event, &watched_dir, &watcher_read_tx, &event_tx)
                 }
+                Err(e) => tracing::warn!(error = %e, "file watcher error"),
             }
         })?;
 
@@ -117,146 +505,354 @@ impl WatchManager {
             _watcher: watcher,
             active_sessions,
             broadcast_tx,
+            broadcast_capacity,
             projects_dir,
+            read_tx,
+            watcher_alive,
+            reader_alive,
         })
     }
 
+    /// Number of events buffered for subscribers.
+    pub fn broadcast_capacity(&self) -> usize {
+        self.broadcast_capacity
+    }
+
+    /// Whether both the file watcher and the session reader thread are
+    /// still running, i.e. live updates are still being produced.
+    pub fn is_alive(&self) -> bool {
+        self.watcher_alive
+            .load(std::sync::atomic::Ordering::Relaxed)
+            && self.reader_alive.load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    /// Stop the session reader thread. File changes are no longer read or
+    /// broadcast, and `is_alive` reports false once it has exited.
+    pub fn stop_session_reader(&self) {
+        let _ = self.read_tx.send(ReaderMessage::Stop);
+    }
+
+    /// Where read positions are kept by default:
+    /// `$XDG_CACHE_HOME/cc-log-viewer/watch-positions.json`, falling back to
+    /// `~/.cache`.
+    pub fn default_positions_path() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        Some(
+            cache_home
+                .join("cc-log-viewer")
+                .join("watch-positions.json"),
+        )
+    }
+
+    /// Restore saved positions for session files under `projects_dir`.
+    /// Entries for files that are gone or have shrunk below the saved
+    /// position (rewritten or truncated) are dropped, so those sessions
+    /// start from the beginning again.
+    fn load_positions(
+        path: &FsPath,
+        projects_dir: &FsPath,
+        active_sessions: &DashMap<String, SessionState>,
+    ) {
+        let Ok(content) = fs::read_to_string(path) else {
+            return;
+        };
+        let saved: HashMap<PathBuf, PersistedPosition> = match serde_json::from_str(&content) {
+            Ok(saved) => saved,
+            Err(e) => {
+                tracing::warn!(path = %path.display(), error = %e, "ignoring unreadable positions file");
+                return;
+            }
+        };
+
+        for (session_file, position) in saved {
+            let still_valid = session_file.starts_with(projects_dir)
+                && fs::metadata(&session_file)
+                    .is_ok_and(|metadata| metadata.len() >= position.last_position);
+            if !still_valid {
+                continue;
+            }
+            let Some((project_name, session_id)) = session_path_parts(projects_dir, &session_file)
+            else {
+                continue;
+            };
+
+            active_sessions.insert(
+                format!("{}:{}", project_name, session_id),
+                SessionState {
+                    project_name,
+                    session_file: session_file.clone(),
+                    last_position: position.last_position,
+                    last_modified: position.last_modified,
+                },
+            );
+        }
+    }
+
+    /// Write positions to `path` at most once per flush interval, and only
+    /// when something changed. Stops once the watch manager is dropped.
+    fn spawn_positions_writer(
+        path: PathBuf,
+        active_sessions: std::sync::Weak<DashMap<String, SessionState>>,
+        dirty: Arc<std::sync::atomic::AtomicBool>,
+    ) {
+        std::thread::spawn(move || loop {
+            std::thread::sleep(POSITIONS_FLUSH_INTERVAL);
+            let Some(sessions) = active_sessions.upgrade() else {
+                break;
+            };
+            if !dirty.swap(false, std::sync::atomic::Ordering::Relaxed) {
+                continue;
+            }
+
+            let positions: HashMap<PathBuf, PersistedPosition> = sessions
+                .iter()
+                .map(|s| {
+                    (
+                        s.session_file.clone(),
+                        PersistedPosition {
+                            last_position: s.last_position,
+                            last_modified: s.last_modified,
+                        },
+                    )
+                })
+                .collect();
+            drop(sessions);
+
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| {
+                    let json = serde_json::to_string(&positions)?;
+                    fs::write(&path, json)
+                });
+            if let Err(e) = result {
+                tracing::warn!(path = %path.display(), error = %e, "could not save watch positions");
+            }
+        });
+    }
+
+    /// Queue a read of every session file touched by `event`, and announce
+    /// project directories created directly under `projects_dir`.
     fn handle_fs_event(
         event: Event,
+        projects_dir: &FsPath,
+        read_tx: &std::sync::mpsc::Sender<ReaderMessage>,
         broadcast_tx: &broadcast::Sender<WatchEvent>,
-        active_sessions: &DashMap<String, SessionState>,
-        _projects_dir: &std::path::Path,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        match event.kind {
-            EventKind::Create(_) | EventKind::Modify(_) => {
-                for path in event.paths {
-                    if path.extension().is_some_and(|ext| ext == "jsonl") {
-                        if let Some(project_name) = path
-                            .parent()
-                            .and_then(|p| p.file_name())
-                            .and_then(|n| n.to_str())
-                        {
-                            let session_id = path
-                                .file_stem()
-                                .and_then(|s| s.to_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-
-                            // Read new entries from the file
-                            if let Ok(metadata) = fs::metadata(&path) {
-                                let key = format!("{}:{}", project_name, session_id);
-                                let current_pos =
-                                    if let Some(session_state) = active_sessions.get(&key) {
-                                        session_state.last_position
-                                    } else {
-                                        0
-                                    };
-
-                                if let Ok(entries_with_positions) =
-                                    Self::read_new_entries(&path, current_pos)
-                                {
-                                    // Broadcast new entries (limit to prevent spam)
-                                    let max_entries_per_event = 10;
-                                    let mut last_processed_position = current_pos;
-
-                                    for (entry, entry_position) in entries_with_positions
-                                        .into_iter()
-                                        .take(max_entries_per_event)
-                                    {
-                                        let watch_event = WatchEvent {
-                                            event_type: "log_entry".to_string(),
-                                            project: project_name.to_string(),
-                                            session: Some(session_id.clone()),
-                                            entry: Some(entry),
-                                            timestamp: Utc::now(),
-                                        };
-
-                                        if broadcast_tx.send(watch_event).is_err() {
-                                            // Channel is closed, stop trying to send
-                                            break;
-                                        }
-
-                                        last_processed_position = entry_position;
-                                    }
-
-                                    // Update session state with the position of the last entry actually processed
-                                    active_sessions.insert(
-                                        key,
-                                        SessionState {
-                                            project_name: project_name.to_string(),
-                                            session_file: path.clone(),
-                                            last_position: last_processed_position,
-                                            last_modified: metadata
-                                                .modified()
-                                                .unwrap_or(SystemTime::now()),
-                                        },
-                                    );
-                                }
-                            }
+    ) {
+        tracing::trace!(kind = ?event.kind, paths = ?event.paths, "file system event");
+        if let EventKind::Create(_) | EventKind::Modify(_) = event.kind {
+            for path in event.paths {
+                if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    // Archiving moves a session here; that's not new activity
+                    if !is_archived(projects_dir, &path) {
+                        let _ = read_tx.send(ReaderMessage::Read(path));
+                    }
+                } else if matches!(event.kind, EventKind::Create(_))
+                    && path.parent() == Some(projects_dir)
+                    && path.is_dir()
+                {
+                    let Some(project) = path.file_name().and_then(|n| n.to_str()) else {
+                        continue;
+                    };
+                    tracing::debug!(project, "new project directory");
+                    let _ = broadcast_tx.send(WatchEvent {
+                        event_type: "project_created".to_string(),
+                        project: project.to_string(),
+                        session: None,
+                        entry: None,
+                        timestamp: Utc::now(),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Read queued session files on one thread. A single write usually
+    /// fires several events (CREATE then MODIFY), so paths queued within
+    /// `READ_COALESCE_WINDOW` of each other are read once. A session with
+    /// more new entries than one batch allows is read again next window.
+    /// Stops when told to or once the watch manager (and with it every
+    /// sender) is dropped.
+    fn spawn_session_reader(
+        projects_dir: PathBuf,
+        read_rx: std::sync::mpsc::Receiver<ReaderMessage>,
+        broadcast_tx: broadcast::Sender<WatchEvent>,
+        broadcast_capacity: usize,
+        active_sessions: Arc<DashMap<String, SessionState>>,
+        dirty: Arc<std::sync::atomic::AtomicBool>,
+        alive: AliveFlag,
+    ) {
+        use std::sync::mpsc::RecvTimeoutError;
+
+        std::thread::spawn(move || {
+            let _alive = alive;
+            let mut pending = std::collections::BTreeSet::new();
+            let mut disconnected = false;
+            let mut near_capacity = false;
+            loop {
+                if pending.is_empty() {
+                    if disconnected {
+                        break;
+                    }
+                    match read_rx.recv() {
+                        Ok(ReaderMessage::Read(path)) => pending.insert(path),
+                        Ok(ReaderMessage::Stop) | Err(_) => break,
+                    };
+                }
+
+                let deadline = std::time::Instant::now() + READ_COALESCE_WINDOW;
+                while !disconnected {
+                    let wait = deadline.saturating_duration_since(std::time::Instant::now());
+                    match read_rx.recv_timeout(wait) {
+                        Ok(ReaderMessage::Read(path)) => {
+                            pending.insert(path);
                         }
+                        Ok(ReaderMessage::Stop) => return,
+                        Err(RecvTimeoutError::Timeout) => break,
+                        Err(RecvTimeoutError::Disconnected) => disconnected = true,
+                    }
+                }
+
+                for path in std::mem::take(&mut pending) {
+                    if Self::read_session_updates(
+                        &projects_dir,
+                        &path,
+                        &broadcast_tx,
+                        &active_sessions,
+                        &dirty,
+                    ) {
+                        pending.insert(path);
                     }
                 }
+
+                // Warn once each time a slow subscriber lets the buffer fill
+                // past 90%, before it starts dropping events.
+                let queued = broadcast_tx.len();
+                if queued >= broadcast_capacity - broadcast_capacity / 10 {
+                    if !near_capacity {
+                        tracing::warn!(
+                            queued,
+                            capacity = broadcast_capacity,
+                            "watch event buffer is nearly full; slow clients will miss events (see --broadcast-capacity)"
+                        );
+                    }
+                    near_capacity = true;
+                } else {
+                    near_capacity = false;
+                }
+            }
+        });
+    }
+
+    /// Broadcast up to `MAX_ENTRIES_PER_READ` entries written to `path`
+    /// since its last read, returning whether more are waiting. Marks
+    /// `dirty` when the session's position moved, so it gets saved.
+    fn read_session_updates(
+        projects_dir: &FsPath,
+        path: &FsPath,
+        broadcast_tx: &broadcast::Sender<WatchEvent>,
+        active_sessions: &DashMap<String, SessionState>,
+        dirty: &std::sync::atomic::AtomicBool,
+    ) -> bool {
+        let Some((project_name, session_id)) = session_path_parts(projects_dir, path) else {
+            return false;
+        };
+
+        // Read new entries from the file
+        let Ok(metadata) = fs::metadata(path) else {
+            return false;
+        };
+        let key = format!("{}:{}", project_name, session_id);
+        let current_pos = if let Some(session_state) = active_sessions.get(&key) {
+            session_state.last_position
+        } else {
+            0
+        };
+
+        let Ok(entries_with_positions) = Self::read_new_entries(path, current_pos) else {
+            return false;
+        };
+        let more_waiting = entries_with_positions.len() > MAX_ENTRIES_PER_READ;
+
+        // Broadcast new entries (limit to prevent spam)
+        let mut last_processed_position = current_pos;
+        for (entry, entry_position) in entries_with_positions
+            .into_iter()
+            .take(MAX_ENTRIES_PER_READ)
+        {
+            let watch_event = WatchEvent {
+                event_type: "log_entry".to_string(),
+                project: project_name.to_string(),
+                session: Some(session_id.clone()),
+                entry: Some(entry),
+                timestamp: Utc::now(),
+            };
+
+            if broadcast_tx.send(watch_event).is_err() {
+                // Channel is closed, stop trying to send
+                break;
             }
-            _ => {}
+
+            last_processed_position = entry_position;
         }
-        Ok(())
+
+        // Update session state with the position of the last entry actually processed
+        active_sessions.insert(
+            key,
+            SessionState {
+                project_name: project_name.to_string(),
+                session_file: path.to_path_buf(),
+                last_position: last_processed_position,
+                last_modified: metadata.modified().unwrap_or(SystemTime::now()),
+            },
+        );
+        if last_processed_position != current_pos {
+            dirty.store(true, std::sync::atomic::Ordering::Relaxed);
+        }
+
+        more_waiting && last_processed_position > current_pos
     }
 
-    fn read_new_entries(
-        path: &PathBuf,
+    /// Parse the complete entries written at or after `from_position`,
+    /// each paired with the byte position just past its line. Only the
+    /// bytes from `from_position` on are read.
+    pub fn read_new_entries(
+        path: &FsPath,
         from_position: u64,
     ) -> Result<Vec<(LogEntry, u64)>, Box<dyn std::error::Error + Send + Sync>> {
         // Handle potential file access errors gracefully
-        let content = match fs::read_to_string(path) {
-            Ok(content) => content,
-            Err(e) => {
-                eprintln!("Warning: Could not read file {}: {}", path.display(), e);
-                return Ok(Vec::new());
-            }
-        };
+        let mut content = Vec::new();
+        let read = fs::File::open(path).and_then(|mut file| {
+            file.seek(SeekFrom::Start(from_position))?;
+            file.read_to_end(&mut content)
+        });
+        if let Err(e) = read {
+            tracing::warn!(path = %path.display(), error = %e, "could not read session file");
+            return Ok(Vec::new());
+        }
 
-        let content_bytes = content.as_bytes();
         let mut entries_with_positions = Vec::new();
-
-        // Split content into lines while tracking actual byte positions
         let mut line_start = 0usize;
-        while line_start < content_bytes.len() {
-            // Find the end of the current line
-            let mut line_end = line_start;
-            while line_end < content_bytes.len() && content_bytes[line_end] != b'\n' {
-                line_end += 1;
-            }
-
-            // Calculate the byte position of this line
-            let line_byte_start = line_start as u64;
-            let line_byte_end = if line_end < content_bytes.len() {
-                // Include newline character
-                (line_end + 1) as u64
-            } else {
+        while line_start < content.len() {
+            let (line_end, next_start) = match memchr::memchr(b'\n', &content[line_start..]) {
+                Some(offset) => (line_start + offset, line_start + offset + 1),
                 // Last line without newline
-                line_end as u64
+                None => (content.len(), content.len()),
             };
+            let line_content =
+                std::str::from_utf8(trim_log_line(&content[line_start..line_end])).unwrap_or("");
 
-            // Process line if it's past our starting position
-            if line_byte_start >= from_position {
-                // Extract the line content (excluding newline)
-                let line_content =
-                    std::str::from_utf8(&content_bytes[line_start..line_end]).unwrap_or("");
-
-                // Only parse lines that look like JSON to avoid errors
-                if line_content.trim().starts_with('{') && line_content.trim().ends_with('}') {
-                    if let Ok(entry) = serde_json::from_str::<LogEntry>(line_content) {
-                        entries_with_positions.push((entry, line_byte_end));
-                    }
+            // Only parse lines that look like JSON to avoid errors
+            if line_content.trim().starts_with('{') && line_content.trim().ends_with('}') {
+                if let Ok(entry) = serde_json::from_str::<LogEntry>(line_content) {
+                    entries_with_positions.push((entry, from_position + next_start as u64));
                 }
             }
 
-            // Move to next line
-            line_start = if line_end < content_bytes.len() {
-                line_end + 1 // Skip the newline
-            } else {
-                break;
-            };
+            line_start = next_start;
         }
 
         Ok(entries_with_positions)
@@ -265,6 +861,32 @@ impl WatchManager {
     pub fn subscribe(&self) -> broadcast::Receiver<WatchEvent> {
         self.broadcast_tx.subscribe()
     }
+
+    /// Only broadcast entries of `project`/`session` written past `position`,
+    /// instead of the whole file on its next change.
+    pub fn set_session_position(&self, project: &str, session: &str, position: u64) {
+        let session_file = self
+            .projects_dir
+            .join(project)
+            .join(format!("{}.jsonl", session));
+        let last_modified = fs::metadata(&session_file)
+            .and_then(|m| m.modified())
+            .unwrap_or(SystemTime::now());
+        self.active_sessions.insert(
+            format!("{}:{}", project, session),
+            SessionState {
+                project_name: project.to_string(),
+                session_file,
+                last_position: position,
+                last_modified,
+            },
+        );
+    }
+
+    /// Send an event to every subscriber, returning how many received it.
+    pub fn publish(&self, event: WatchEvent) -> usize {
+        self.broadcast_tx.send(event).unwrap_or(0)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -272,199 +894,1959 @@ pub struct AppState {
     pub projects_dir: PathBuf,
     pub cached_projects: Arc<tokio::sync::RwLock<Vec<ProjectSummary>>>,
     pub watch_manager: Arc<WatchManager>,
+    pub tool_config: Arc<ToolConfig>,
+    pub watch_projects: Arc<Vec<String>>,
+    /// Parsed session summaries keyed by file path, reused until the file's
+    /// modified time changes.
+    pub session_cache: Arc<DashMap<PathBuf, (SystemTime, SessionSummary)>>,
+    /// SQLite index backing search and stats when `--index` is on.
+    pub index: Option<Arc<SessionIndex>>,
+    /// Prices used for the cost estimates in stats and summaries.
+    pub pricing: Arc<PricingTable>,
+    /// Directory levels below each project searched for sessions.
+    pub scan_depth: usize,
 }
 
 impl AppState {
     pub fn new(projects_dir: PathBuf) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let watch_manager = Arc::new(WatchManager::new(projects_dir.clone())?);
+        Self::new_with_positions_file(projects_dir, None)
+    }
+
+    /// Like `new`, with watch positions persisted to `positions_file`; see
+    /// `WatchManager::with_positions_file`.
+    pub fn new_with_positions_file(
+        projects_dir: PathBuf,
+        positions_file: Option<PathBuf>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Self::new_with_watch_options(projects_dir, positions_file, DEFAULT_BROADCAST_CAPACITY)
+    }
+
+    /// Like `new_with_positions_file`, with the watch event buffer size; see
+    /// `WatchManager::with_options`.
+    pub fn new_with_watch_options(
+        projects_dir: PathBuf,
+        positions_file: Option<PathBuf>,
+        broadcast_capacity: usize,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let watch_manager = Arc::new(WatchManager::with_options(
+            projects_dir.clone(),
+            positions_file,
+            broadcast_capacity,
+        )?);
 
         Ok(Self {
             projects_dir,
             cached_projects: Arc::new(tokio::sync::RwLock::new(Vec::new())),
             watch_manager,
+            tool_config: Arc::new(ToolConfig::default()),
+            watch_projects: Arc::new(Vec::new()),
+            session_cache: Arc::new(DashMap::new()),
+            index: None,
+            pricing: Arc::new(PricingTable::default()),
+            scan_depth: DEFAULT_SCAN_DEPTH,
         })
     }
 
-    async fn refresh_cache(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let mut projects = Vec::new();
+    pub fn with_tool_config(mut self, tool_config: ToolConfig) -> Self {
+        self.tool_config = Arc::new(tool_config);
+        self
+    }
 
-        for entry in WalkDir::new(&self.projects_dir).min_depth(1).max_depth(1) {
-            let entry = entry?;
-            if entry.file_type().is_dir() {
-                let project_name = entry.file_name().to_string_lossy().to_string();
-                let project_path = entry.path().to_string_lossy().to_string();
-
-                let mut session_count = 0;
-                let mut latest_activity: Option<DateTime<Utc>> = None;
-
-                for log_entry in WalkDir::new(entry.path()).min_depth(1).max_depth(1) {
-                    let log_entry = log_entry?;
-                    if log_entry.file_type().is_file()
-                        && log_entry
-                            .path()
-                            .extension()
-                            .is_some_and(|ext| ext == "jsonl")
-                    {
-                        session_count += 1;
-
-                        if let Ok(content) = fs::read_to_string(log_entry.path()) {
-                            for line in content.lines().take(5) {
-                                if let Ok(entry) = serde_json::from_str::<LogEntry>(line) {
-                                    if let Some(timestamp) = entry.timestamp {
-                                        match latest_activity {
-                                            None => latest_activity = Some(timestamp),
-                                            Some(latest) if timestamp > latest => {
-                                                latest_activity = Some(timestamp)
-                                            }
-                                            _ => {}
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
+    pub fn with_pricing(mut self, pricing: PricingTable) -> Self {
+        self.pricing = Arc::new(pricing);
+        self
+    }
 
-                projects.push(ProjectSummary {
-                    name: project_name,
-                    path: project_path,
-                    session_count,
-                    latest_activity,
-                });
-            }
-        }
+    pub fn with_index(mut self, index: SessionIndex) -> Self {
+        self.index = Some(Arc::new(index));
+        self
+    }
+
+    /// Also find sessions in folders nested up to `scan_depth` levels deep
+    /// in each project; see `session_id_for` for the ids they get.
+    pub fn with_scan_depth(mut self, scan_depth: usize) -> Self {
+        self.scan_depth = scan_depth.max(1);
+        self
+    }
 
-        projects.sort_by(|a, b| b.latest_activity.cmp(&a.latest_activity));
+    pub fn with_watch_projects(mut self, watch_projects: Vec<String>) -> Self {
+        self.watch_projects = Arc::new(watch_projects);
+        self
+    }
 
-        *self.cached_projects.write().await = projects;
-        Ok(())
+    /// Whether live events for `project` should be streamed to clients.
+    pub fn watches_project(&self, project: &str) -> bool {
+        self.watch_projects.is_empty()
+            || self
+                .watch_projects
+                .iter()
+                .any(|filter| project.contains(filter.as_str()))
     }
-}
 
-pub async fn index() -> Html<&'static str> {
-    Html(include_str!("../static/index.html"))
-}
+    /// Summary for a session file, served from `session_cache` when the
+    /// file hasn't been modified since it was last parsed.
+    pub fn cached_session_summary(
+        &self,
+        project_name: &str,
+        path: &FsPath,
+    ) -> Option<SessionSummary> {
+        let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
 
-pub async fn live_activity() -> Html<&'static str> {
-    Html(include_str!("../static/live.html"))
-}
+        if let Some(cached) = self.session_cache.get(path) {
+            if cached.0 == modified {
+                return Some(cached.1.clone());
+            }
+        }
 
-pub async fn get_projects(
-    State(state): State<AppState>,
-) -> Result<Json<Vec<ProjectSummary>>, StatusCode> {
-    if let Err(e) = state.refresh_cache().await {
-        eprintln!("Failed to refresh project cache: {}", e);
-        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+        let content = fs::read_to_string(path).ok()?;
+        let session_id = match session_id_for(&self.projects_dir.join(project_name), path) {
+            Some(id) => id,
+            None => path.file_stem()?.to_string_lossy().to_string(),
+        };
+        let mut summary = summarize_session(project_name, &session_id, &content);
+        summary.estimated_cost_usd = self.pricing.total_cost(&summary.usage_by_model);
+        self.session_cache
+            .insert(path.to_path_buf(), (modified, summary.clone()));
+        Some(summary)
     }
 
-    let projects = state.cached_projects.read().await;
-    Ok(Json(projects.clone()))
-}
+    async fn refresh_cache(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut project_dirs = Vec::new();
+        for entry in WalkDir::new(&self.projects_dir).min_depth(1).max_depth(1) {
+            let entry = entry?;
+            if entry.file_type().is_dir() {
+                project_dirs.push(entry.into_path());
+            }
+        }
 
-pub async fn get_sessions(
-    Path(project_name): Path<String>,
-    State(state): State<AppState>,
-) -> Result<Json<Vec<SessionSummary>>, StatusCode> {
-    let project_path = state.projects_dir.join(&project_name);
+        // Projects are independent, so scan each on the blocking pool and
+        // collect the results in directory order.
+        let tasks: Vec<_> = project_dirs
+            .into_iter()
+            .map(|dir| {
+                let state = self.clone();
+                tokio::task::spawn_blocking(move || state.scan_project(&dir))
+            })
+            .collect();
 
-    if !project_path.exists() {
-        return Err(StatusCode::NOT_FOUND);
+        let mut projects = Vec::with_capacity(tasks.len());
+        for task in tasks {
+            projects.push(task.await??);
+        }
+
+        // Newest first; ties broken by name so the order is stable.
+        projects.sort_by(|a, b| {
+            b.latest_activity
+                .cmp(&a.latest_activity)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+
+        *self.cached_projects.write().await = projects;
+        Ok(())
     }
 
-    let mut sessions = Vec::new();
+    fn scan_project(&self, project_dir: &FsPath) -> Result<ProjectSummary, walkdir::Error> {
+        let project_name = project_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
 
-    for entry in WalkDir::new(&project_path).min_depth(1).max_depth(1) {
-        let entry = entry.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        if entry.file_type().is_file() && entry.path().extension().is_some_and(|ext| ext == "jsonl")
-        {
-            let session_id = entry
-                .path()
-                .file_stem()
-                .unwrap_or_default()
-                .to_string_lossy()
-                .to_string();
-
-            if let Ok(content) = fs::read_to_string(entry.path()) {
-                let mut summary = "Untitled Session".to_string();
-                let mut timestamp = Utc::now();
-                let message_count = content.lines().count();
-
-                for line in content.lines().take(10) {
-                    if let Ok(entry) = serde_json::from_str::<LogEntry>(line) {
-                        if entry.entry_type.as_deref() == Some("summary") {
-                            if let Some(s) = entry.summary {
-                                summary = s;
-                            }
-                        }
-                        if let Some(ts) = entry.timestamp {
-                            timestamp = ts;
-                            break;
-                        }
-                    }
-                }
+        let mut session_count = 0;
+        let mut latest_activity: Option<DateTime<Utc>> = None;
+        let mut cwd: Option<String> = None;
 
-                sessions.push(SessionSummary {
-                    id: session_id,
-                    summary,
-                    timestamp,
-                    message_count,
-                    project_name: project_name.clone(),
-                });
+        for file in session_files(project_dir, self.scan_depth) {
+            let file = file?;
+            session_count += 1;
+
+            let Some(summary) = self.cached_session_summary(&project_name, &file.path) else {
+                continue;
+            };
+            // Prefer the working directory of the most recent session.
+            if summary.last_activity > latest_activity {
+                latest_activity = summary.last_activity;
+                cwd = summary.cwd.or(cwd);
+            } else if cwd.is_none() {
+                cwd = summary.cwd;
             }
         }
+
+        Ok(ProjectSummary {
+            display_path: cwd.unwrap_or_else(|| demangle_project_name(&project_name)),
+            name: project_name,
+            path: project_dir.to_string_lossy().to_string(),
+            session_count,
+            latest_activity,
+        })
     }
+}
 
-    sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-    Ok(Json(sessions))
+/// Best-effort reversal of Claude Code's project directory naming, which
+/// replaces every `/` in the working directory with `-`. Dashes that were in
+/// the original path can't be told apart, so prefer an entry's `cwd`.
+pub fn demangle_project_name(name: &str) -> String {
+    name.replace('-', "/")
 }
 
-pub async fn get_session_logs(
-    Path((project_name, session_id)): Path<(String, String)>,
-    State(state): State<AppState>,
-) -> Result<Json<Vec<LogEntry>>, StatusCode> {
-    let log_path = state
-        .projects_dir
-        .join(&project_name)
-        .join(format!("{}.jsonl", session_id));
+/// Every tool with dedicated rendering and its icon. This is the single
+/// source for icons on the backend; the web UI's tool handlers mirror it.
+pub const TOOL_ICONS: &[(&str, &str)] = &[
+    ("Bash", "💻"),
+    ("Read", "📖"),
+    ("Edit", "✏️"),
+    ("MultiEdit", "🔄"),
+    ("Write", "📝"),
+    ("LS", "📁"),
+    ("Grep", "🔍"),
+    ("Glob", "🗂️"),
+    ("TodoWrite", "📝"),
+    ("Task", "🎯"),
+    ("WebFetch", "🌐"),
+    ("mcp__private-journal__process_thoughts", "🧠"),
+    ("mcp__socialmedia__login", "🔐"),
+    ("mcp__socialmedia__create_post", "📱"),
+];
 
-    if !log_path.exists() {
-        return Err(StatusCode::NOT_FOUND);
-    }
+/// Icon for tools without an entry in `TOOL_ICONS`.
+pub const DEFAULT_TOOL_ICON: &str = "🔧";
 
-    let content = fs::read_to_string(&log_path).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+#[derive(Debug, Clone, Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub icon: String,
+    /// `"core"` for Claude Code's built-in tools, `"mcp"` for MCP server tools.
+    pub kind: &'static str,
+}
 
-    let mut entries = Vec::new();
-    for line in content.lines() {
-        if let Ok(entry) = serde_json::from_str::<LogEntry>(line) {
-            entries.push(entry);
+impl ToolInfo {
+    fn new(name: &str, icon: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            icon: icon.to_string(),
+            kind: if name.starts_with("mcp__") {
+                "mcp"
+            } else {
+                "core"
+            },
         }
     }
-
-    Ok(Json(entries))
 }
 
-pub async fn websocket_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
-    ws.on_upgrade(|socket| handle_websocket(socket, state))
-}
+/// Every tool the viewer knows how to render, including ones defined in the
+/// tool config (whose icons take precedence).
+pub async fn get_tools(State(state): State<AppState>) -> Json<Vec<ToolInfo>> {
+    let mut tools: Vec<ToolInfo> = TOOL_ICONS
+        .iter()
+        .map(|&(name, icon)| {
+            let icon = state
+                .tool_config
+                .get(name)
+                .and_then(|spec| spec.icon.as_deref())
+                .unwrap_or(icon);
+            ToolInfo::new(name, icon)
+        })
+        .collect();
+
+    let mut custom: Vec<ToolInfo> = state
+        .tool_config
+        .tools
+        .iter()
+        .filter(|(name, _)| !TOOL_ICONS.iter().any(|(known, _)| known == name))
+        .map(|(name, spec)| ToolInfo::new(name, spec.icon.as_deref().unwrap_or(DEFAULT_TOOL_ICON)))
+        .collect();
+    custom.sort_by(|a, b| a.name.cmp(&b.name));
+    tools.extend(custom);
+
+    Json(tools)
+}
+
+/// Icon shown next to a tool call, kept in sync with the web UI's tool handlers.
+pub fn get_tool_icon(tool_name: &str) -> &'static str {
+    TOOL_ICONS
+        .iter()
+        .find(|(name, _)| *name == tool_name)
+        .map_or(DEFAULT_TOOL_ICON, |&(_, icon)| icon)
+}
+
+/// Readable form of a tool name for headers: `mcp__private-journal__process_thoughts`
+/// becomes `Private Journal: Process Thoughts`. Built-in tools are unchanged.
+pub fn humanize_tool_name(name: &str) -> String {
+    let Some(rest) = name.strip_prefix("mcp__") else {
+        return name.to_string();
+    };
+    let title_case = |s: &str| {
+        s.split(['-', '_'])
+            .filter(|word| !word.is_empty())
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match rest.split_once("__") {
+        Some((server, action)) => format!("{}: {}", title_case(server), title_case(action)),
+        None => title_case(rest),
+    }
+}
+
+/// Whether a user-role entry was typed by a person. Claude Code also logs
+/// tool results as user turns; those carry a non-human `userType` or consist
+/// of nothing but tool_result blocks. Entries without a `userType` (older
+/// logs) count as human unless their content says otherwise.
+pub fn is_human_turn(entry: &LogEntry) -> bool {
+    let Some(message) = &entry.message else {
+        return false;
+    };
+    if message.get("role").and_then(|r| r.as_str()) != Some("user") {
+        return false;
+    }
+    if !matches!(
+        entry.user_type.as_deref(),
+        None | Some("external") | Some("human")
+    ) {
+        return false;
+    }
+    match message.get("content") {
+        Some(Value::Array(blocks)) => {
+            blocks.is_empty()
+                || !blocks
+                    .iter()
+                    .all(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+        }
+        _ => true,
+    }
+}
+
+/// Whether a tool_result block reports a failure: flagged with `is_error`,
+/// or, in logs that predate the flag, wrapped in `<tool_use_error>`.
+pub fn is_error_result(block: &Value) -> bool {
+    if block.get("is_error").and_then(|e| e.as_bool()) == Some(true) {
+        return true;
+    }
+    let text = match block.get("content") {
+        Some(Value::String(text)) => text.as_str(),
+        Some(Value::Array(parts)) => parts
+            .first()
+            .and_then(|part| part.get("text"))
+            .and_then(|t| t.as_str())
+            .unwrap_or_default(),
+        _ => "",
+    };
+    text.trim_start().starts_with("<tool_use_error>")
+}
+
+/// Number of failed tool results in an entry's message.
+pub fn tool_error_count(entry: &LogEntry) -> usize {
+    match entry.message.as_ref().and_then(|m| m.get("content")) {
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_result"))
+            .filter(|b| is_error_result(b))
+            .count(),
+        _ => 0,
+    }
+}
+
+/// Flatten an entry's message content into typed blocks, preserving the
+/// interleaving of text, tool calls, tool results and thinking. Plain string
+/// content becomes a single `Text` block; unknown block types are skipped.
+pub fn flatten_message_blocks(entry: &LogEntry) -> Vec<ContentBlock> {
+    let content = match entry.message.as_ref().and_then(|m| m.get("content")) {
+        Some(content) => content,
+        None => return Vec::new(),
+    };
+
+    let blocks = match content {
+        Value::String(text) => return vec![ContentBlock::Text { text: text.clone() }],
+        Value::Array(blocks) => blocks,
+        _ => return Vec::new(),
+    };
+
+    let str_field = |block: &Value, key: &str| {
+        block
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    };
+
+    blocks
+        .iter()
+        .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => Some(ContentBlock::Text {
+                text: str_field(block, "text").unwrap_or_default(),
+            }),
+            Some("tool_use") => Some(ContentBlock::ToolUse {
+                id: str_field(block, "id"),
+                name: str_field(block, "name").unwrap_or_else(|| "unknown".to_string()),
+                input: block.get("input").cloned().unwrap_or(Value::Null),
+            }),
+            Some("tool_result") => Some(ContentBlock::ToolResult {
+                id: str_field(block, "tool_use_id"),
+                content: block.get("content").cloned().unwrap_or(Value::Null),
+            }),
+            Some("thinking") => Some(ContentBlock::Thinking {
+                thinking: str_field(block, "thinking").unwrap_or_default(),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+const REDACTED: &str = "‹redacted›";
+
+/// Built-in secret patterns. When a pattern has a capture group, the group
+/// is kept as a prefix (e.g. the `Authorization: Bearer ` part) and only the
+/// rest of the match is replaced.
+const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    r#"sk-[A-Za-z0-9_\-]{20,}"#,
+    r#"gh[pousr]_[A-Za-z0-9]{30,}"#,
+    r#"github_pat_[A-Za-z0-9_]{22,}"#,
+    r#"xox[abprs]-[A-Za-z0-9\-]{10,}"#,
+    r#"AKIA[0-9A-Z]{16}"#,
+    r#"(?i)(aws_secret_access_key["']?\s*[:=]\s*["']?)[A-Za-z0-9/+=]{40}"#,
+    r#"(?i)(authorization["']?\s*[:=]\s*["']?(?:bearer|basic|token)\s+)[^\s"']+"#,
+    r#"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----"#,
+];
+
+/// Replaces secrets in log content with a `‹redacted›` marker before it is
+/// shared in an export.
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::new(DEFAULT_REDACT_PATTERNS).expect("built-in redaction patterns are valid")
+    }
+}
+
+impl Redactor {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self, regex::Error> {
+        let patterns = patterns
+            .iter()
+            .map(|p| Regex::new(p.as_ref()))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { patterns })
+    }
+
+    pub fn redact(&self, text: &str) -> String {
+        let mut redacted = text.to_string();
+        for pattern in &self.patterns {
+            redacted = pattern
+                .replace_all(&redacted, |caps: &regex::Captures| match caps.get(1) {
+                    Some(prefix) => format!("{}{}", prefix.as_str(), REDACTED),
+                    None => REDACTED.to_string(),
+                })
+                .into_owned();
+        }
+        redacted
+    }
+
+    /// Redact every string inside a JSON value, leaving keys untouched.
+    pub fn redact_value(&self, value: &Value) -> Value {
+        match value {
+            Value::String(s) => Value::String(self.redact(s)),
+            Value::Array(items) => {
+                Value::Array(items.iter().map(|v| self.redact_value(v)).collect())
+            }
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), self.redact_value(v)))
+                    .collect(),
+            ),
+            other => other.clone(),
+        }
+    }
+
+    pub fn redact_entry(&self, entry: &LogEntry) -> LogEntry {
+        let mut entry = entry.clone();
+        entry.summary = entry.summary.map(|s| self.redact(&s));
+        entry.message = entry.message.map(|m| self.redact_value(&m));
+        entry.tool_use_result = entry.tool_use_result.map(|r| self.redact_value(&r));
+        entry
+    }
+}
+
+/// Redact secrets using the built-in pattern set.
+pub fn redact(text: &str) -> String {
+    static DEFAULT: OnceLock<Redactor> = OnceLock::new();
+    DEFAULT.get_or_init(Redactor::default).redact(text)
+}
+
+const AUTH_COOKIE: &str = "cc_log_viewer_token";
+
+/// `token` percent-encoded, so it can go in a URL query or a cookie whatever
+/// characters it contains.
+pub fn encode_auth_token(token: &str) -> String {
+    percent_encoding::utf8_percent_encode(token, percent_encoding::NON_ALPHANUMERIC).to_string()
+}
+
+#[derive(Deserialize)]
+struct TokenQuery {
+    token: Option<String>,
+}
+
+/// Middleware that requires the configured token on every request, taken
+/// from an `Authorization: Bearer` header, a `?token=` query parameter or
+/// the cookie set after a successful `?token=` login, so that page
+/// navigation and the WebSocket keep working once the browser has it.
+pub async fn require_auth_token(
+    State(token): State<Arc<String>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Health probes come from the orchestrator, which has no token, and
+    // browsers send CORS preflights without credentials.
+    let preflight = request.method() == axum::http::Method::OPTIONS
+        && request
+            .headers()
+            .contains_key(header::ACCESS_CONTROL_REQUEST_METHOD);
+    if request.uri().path() == "/healthz" || preflight {
+        return next.run(request).await;
+    }
+
+    let headers = request.headers();
+    let bearer = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    let cookie = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(';'))
+        .find_map(|c| c.trim().strip_prefix(AUTH_COOKIE)?.strip_prefix('='))
+        .and_then(|c| percent_encoding::percent_decode_str(c).decode_utf8().ok());
+    let query = Query::<TokenQuery>::try_from_uri(request.uri())
+        .ok()
+        .and_then(|Query(q)| q.token);
+
+    let matches = |candidate: Option<&str>| {
+        candidate.is_some_and(|c| constant_time_eq(c.as_bytes(), token.as_bytes()))
+    };
+
+    if matches(bearer) || matches(cookie.as_deref()) {
+        return next.run(request).await;
+    }
+
+    if matches(query.as_deref()) {
+        let mut response = next.run(request).await;
+        let cookie = format!(
+            "{}={}; Path=/; HttpOnly; SameSite=Strict",
+            AUTH_COOKIE,
+            encode_auth_token(&token)
+        );
+        if let Ok(value) = HeaderValue::from_str(&cookie) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+        return response;
+    }
+
+    StatusCode::UNAUTHORIZED.into_response()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[derive(Debug, Serialize)]
+pub struct Health {
+    pub status: &'static str,
+    pub projects_dir_exists: bool,
+    pub watcher_alive: bool,
+}
+
+/// Liveness/readiness probe; exempt from the auth token.
+pub async fn healthz(State(state): State<AppState>) -> Json<Health> {
+    Json(Health {
+        status: "ok",
+        projects_dir_exists: state.projects_dir.is_dir(),
+        watcher_alive: state.watch_manager.is_alive(),
+    })
+}
+
+pub async fn index() -> Html<&'static str> {
+    Html(include_str!("../static/index.html"))
+}
+
+pub async fn live_activity() -> Html<&'static str> {
+    Html(include_str!("../static/live.html"))
+}
+
+/// The `static/` directory, compiled in so an installed binary doesn't need
+/// to run from the source tree.
+static STATIC_ASSETS: Dir<'static> = include_dir!("$CARGO_MANIFEST_DIR/static");
+
+/// Serve `/static/*path` from the embedded assets.
+pub async fn static_asset(Path(path): Path<String>) -> Response {
+    let Some(file) = STATIC_ASSETS.get_file(&path) else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let content_type = match file.path().extension().and_then(|ext| ext.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("json") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        _ => "application/octet-stream",
+    };
+    ([(header::CONTENT_TYPE, content_type)], file.contents()).into_response()
+}
+
+/// An API failure, rendered as `{ "error": "...", "code": "..." }` with the
+/// matching status so clients can tell failures apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApiError {
+    ProjectsDirMissing(PathBuf),
+    ProjectNotFound(String),
+    SessionNotFound { project: String, session: String },
+    EntryNotFound { session: String, uuid: String },
+    InvalidPathComponent(String),
+    BadRequest(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::ProjectsDirMissing(_)
+            | ApiError::ProjectNotFound(_)
+            | ApiError::SessionNotFound { .. }
+            | ApiError::EntryNotFound { .. } => StatusCode::NOT_FOUND,
+            ApiError::InvalidPathComponent(_) | ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            ApiError::ProjectsDirMissing(_) => "projects_dir_missing",
+            ApiError::ProjectNotFound(_) => "project_not_found",
+            ApiError::SessionNotFound { .. } => "session_not_found",
+            ApiError::EntryNotFound { .. } => "entry_not_found",
+            ApiError::InvalidPathComponent(_) => "invalid_path",
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::Internal(_) => "internal_error",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApiError::ProjectsDirMissing(dir) => {
+                write!(f, "Projects directory {} does not exist", dir.display())
+            }
+            ApiError::ProjectNotFound(project) => write!(f, "Project '{}' not found", project),
+            ApiError::SessionNotFound { project, session } => {
+                write!(
+                    f,
+                    "Session '{}' not found in project '{}'",
+                    session, project
+                )
+            }
+            ApiError::EntryNotFound { session, uuid } => {
+                write!(f, "Entry '{}' not found in session '{}'", uuid, session)
+            }
+            ApiError::InvalidPathComponent(component) => {
+                write!(f, "Invalid project or session name '{}'", component)
+            }
+            ApiError::BadRequest(message) | ApiError::Internal(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = serde_json::json!({ "error": self.to_string(), "code": self.code() });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+/// Accept a user-supplied project or session name only if it is a single,
+/// plain path component, so joining it can't escape the projects directory.
+pub fn safe_component(s: &str) -> Result<&str, ApiError> {
+    let unsafe_component = s.is_empty()
+        || s == "."
+        || s.contains("..")
+        || s.contains('/')
+        || s.contains('\\')
+        || s.contains('\0');
+    if unsafe_component {
+        return Err(ApiError::InvalidPathComponent(s.to_string()));
+    }
+    Ok(s)
+}
+
+/// Like `safe_component`, but allowing the `/`-separated ids of sessions
+/// in nested folders, each part of which must be a plain component.
+pub fn safe_session_id(s: &str) -> Result<&str, ApiError> {
+    for part in s.split('/') {
+        safe_component(part).map_err(|_| ApiError::InvalidPathComponent(s.to_string()))?;
+    }
+    Ok(s)
+}
+
+pub async fn get_projects(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ProjectSummary>>, ApiError> {
+    list_projects(&state).await.map(Json)
+}
+
+/// Rescan the projects directory and return every project, newest first.
+async fn list_projects(state: &AppState) -> Result<Vec<ProjectSummary>, ApiError> {
+    // A missing directory is a setup problem worth reporting; an empty one
+    // just means Claude Code hasn't logged anything yet and yields `[]`.
+    if !state.projects_dir.is_dir() {
+        return Err(ApiError::ProjectsDirMissing(state.projects_dir.clone()));
+    }
+    if let Err(e) = state.refresh_cache().await {
+        tracing::error!(error = %e, "failed to refresh project cache");
+        return Err(ApiError::Internal(format!(
+            "Failed to scan projects directory: {}",
+            e
+        )));
+    }
+
+    let projects = state.cached_projects.read().await;
+    Ok(projects.clone())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionQuery {
+    pub since: Option<String>,
+    pub until: Option<String>,
+}
+
+/// An optional, inclusive date range parsed from RFC 3339 bounds.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeRange {
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl TimeRange {
+    pub fn parse(since: Option<&str>, until: Option<&str>) -> Result<Self, chrono::ParseError> {
+        let parse = |value: Option<&str>| {
+            value
+                .map(|v| DateTime::parse_from_rfc3339(v).map(|dt| dt.with_timezone(&Utc)))
+                .transpose()
+        };
+        Ok(Self {
+            since: parse(since)?,
+            until: parse(until)?,
+        })
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.since.is_none() && self.until.is_none()
+    }
+
+    /// Whether `timestamp` falls in the range. A missing timestamp only
+    /// matches an unbounded range.
+    pub fn contains(&self, timestamp: Option<DateTime<Utc>>) -> bool {
+        match timestamp {
+            Some(ts) => {
+                self.since.is_none_or(|since| ts >= since)
+                    && self.until.is_none_or(|until| ts <= until)
+            }
+            None => self.is_unbounded(),
+        }
+    }
+}
+
+pub async fn get_sessions(
+    Path(project_name): Path<String>,
+    Query(query): Query<SessionQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SessionSummary>>, ApiError> {
+    let range = TimeRange::parse(query.since.as_deref(), query.until.as_deref())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid since/until date: {}", e)))?;
+    list_sessions(&state, &project_name, range).map(Json)
+}
+
+/// A project's sessions started within `range`, newest first.
+fn list_sessions(
+    state: &AppState,
+    project_name: &str,
+    range: TimeRange,
+) -> Result<Vec<SessionSummary>, ApiError> {
+    let project_path = state.projects_dir.join(safe_component(project_name)?);
+
+    if !project_path.exists() {
+        return Err(ApiError::ProjectNotFound(project_name.to_string()));
+    }
+
+    let mut sessions = Vec::new();
+
+    for file in session_files(&project_path, state.scan_depth) {
+        let file = file.map_err(|e| {
+            ApiError::Internal(format!("Failed to list project '{}': {}", project_name, e))
+        })?;
+        if let Some(session) = state.cached_session_summary(project_name, &file.path) {
+            sessions.push(session);
+        }
+    }
+    // Before filtering, as a summary may sit in a session outside the range
+    resolve_linked_summaries(&mut sessions);
+    sessions.retain(|session| range.contains(session.started_at));
+
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.timestamp));
+    Ok(sessions)
+}
+
+/// Build a session summary from a whole `.jsonl` file. Entries are not
+/// guaranteed to be in time order, so the start and end are the minimum and
+/// maximum timestamps seen anywhere in the file.
+pub fn summarize_session(project_name: &str, session_id: &str, content: &str) -> SessionSummary {
+    let mut started_at: Option<DateTime<Utc>> = None;
+    let mut last_activity: Option<DateTime<Utc>> = None;
+    let mut message_count = 0;
+    let mut entry_count = 0;
+    let mut cwd = None;
+    let mut token_usage = TokenUsage::default();
+    let mut usage_by_model = BTreeMap::new();
+    let mut uuid_hashes = Vec::new();
+
+    let entries = parse_log_entries(content);
+    for entry in &entries {
+        TokenUsage::accumulate(entry, &mut token_usage, &mut usage_by_model);
+        if let Some(uuid) = &entry.uuid {
+            uuid_hashes.push(uuid_hash(uuid));
+        }
+        match entry.entry_type.as_deref() {
+            Some("user") | Some("assistant") => {
+                entry_count += 1;
+                message_count += 1;
+            }
+            Some(_) => entry_count += 1,
+            None => {}
+        }
+
+        if cwd.is_none() {
+            cwd = entry.cwd.clone();
+        }
+        if let Some(ts) = entry.timestamp {
+            started_at = Some(started_at.map_or(ts, |s| s.min(ts)));
+            last_activity = Some(last_activity.map_or(ts, |l| l.max(ts)));
+        }
+    }
+
+    let uuids: HashSet<&str> = entries.iter().filter_map(|e| e.uuid.as_deref()).collect();
+    let linked_summaries = summary_entries(&entries)
+        .filter_map(|(leaf, text)| Some((leaf?, text)))
+        .filter(|(leaf, _)| !uuids.contains(leaf))
+        .map(|(leaf, text)| (leaf.to_string(), text.to_string()))
+        .collect();
+    let unsummarized_uuids = if explicit_summary(&entries).is_none() {
+        uuid_hashes
+    } else {
+        Vec::new()
+    };
+    let summary = match derive_summary(&entries) {
+        // Files whose entries never mention their session still have one
+        title if title == UNTITLED_SESSION => session_id.to_string(),
+        title => title,
+    };
+
+    SessionSummary {
+        id: session_id.to_string(),
+        summary,
+        timestamp: started_at.unwrap_or_else(Utc::now),
+        started_at,
+        last_activity,
+        message_count,
+        entry_count,
+        project_name: project_name.to_string(),
+        cwd,
+        estimated_cost_usd: 0.0,
+        usage_by_model,
+        linked_summaries,
+        unsummarized_uuids,
+    }
+}
+
+/// Title sessions that have no summary entry of their own with a summary
+/// written into another session of the same project. Claude Code does
+/// this when a conversation is resumed: the new file starts with summaries
+/// of the earlier one, naming it by the uuid of its last entry
+/// (`leafUuid`).
+pub fn resolve_linked_summaries(sessions: &mut [SessionSummary]) {
+    let mut linked: HashMap<u64, &str> = HashMap::new();
+    for session in sessions.iter() {
+        for (leaf, text) in &session.linked_summaries {
+            linked.entry(uuid_hash(leaf)).or_insert(text);
+        }
+    }
+    if linked.is_empty() {
+        return;
+    }
+
+    let titles: Vec<Option<String>> = sessions
+        .iter()
+        .map(|session| {
+            // A summary written at a later entry covers more of the session
+            session
+                .unsummarized_uuids
+                .iter()
+                .rev()
+                .find_map(|hash| linked.get(hash))
+                .map(|text| text.to_string())
+        })
+        .collect();
+    for (session, title) in sessions.iter_mut().zip(titles) {
+        if let Some(title) = title {
+            session.summary = title;
+        }
+    }
+}
+
+fn uuid_hash(uuid: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    uuid.hash(&mut hasher);
+    hasher.finish()
+}
+
+const UNTITLED_SESSION: &str = "Untitled Session";
+
+/// A title for a session: its own summary entry if it has one, else the
+/// opening of the first prompt a person typed, else the session id.
+pub fn derive_summary(entries: &[LogEntry]) -> String {
+    if let Some(summary) = explicit_summary(entries) {
+        let summary = collapse_whitespace(summary);
+        if !summary.is_empty() {
+            return summary;
+        }
+    }
+    entries
+        .iter()
+        .filter(|entry| is_human_turn(entry))
+        .find_map(prompt_title)
+        .or_else(|| entries.iter().find_map(|entry| entry.session_id.clone()))
+        .unwrap_or_else(|| UNTITLED_SESSION.to_string())
+}
+
+/// The `(leafUuid, text)` of each summary entry, in file order.
+fn summary_entries(entries: &[LogEntry]) -> impl Iterator<Item = (Option<&str>, &str)> {
+    entries
+        .iter()
+        .filter(|entry| entry.entry_type.as_deref() == Some("summary"))
+        .filter_map(|entry| Some((entry.leaf_uuid.as_deref(), entry.summary.as_deref()?)))
+}
+
+/// A summary describes the session holding its `leafUuid`, so prefer one
+/// that points into these entries; resumed sessions also carry summaries of
+/// the ones they continue, which still beat having no title.
+fn explicit_summary(entries: &[LogEntry]) -> Option<&str> {
+    let uuids: HashSet<&str> = entries.iter().filter_map(|e| e.uuid.as_deref()).collect();
+    summary_entries(entries)
+        .find(|(leaf, _)| leaf.is_none_or(|leaf| uuids.contains(leaf)))
+        .or_else(|| summary_entries(entries).next())
+        .map(|(_, text)| text)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Up to 60 characters of a prompt's text, on one line, as a stand-in title.
+/// Tool results, interruption notices and tag-wrapped text injected by
+/// Claude Code (command output, system reminders) are left out.
+fn prompt_title(entry: &LogEntry) -> Option<String> {
+    let text: String = flatten_message_blocks(entry)
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } if !is_injected_text(text) => Some(text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let line = collapse_whitespace(&text);
+    if line.is_empty() {
+        return None;
+    }
+    if line.chars().count() <= 60 {
+        return Some(line);
+    }
+    let cut: String = line.chars().take(59).collect();
+    Some(format!("{}…", cut.trim_end()))
+}
+
+fn is_injected_text(text: &str) -> bool {
+    let text = text.trim();
+    text.starts_with("[Request interrupted") || (text.starts_with('<') && text.ends_with('>'))
+}
+
+pub async fn get_tool_config(State(state): State<AppState>) -> Json<ToolConfig> {
+    Json(state.tool_config.as_ref().clone())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SessionLogsQuery {
+    /// Comma-separated entry types, e.g. `assistant,tool_use`.
+    #[serde(rename = "type")]
+    pub entry_types: Option<String>,
+}
+
+/// Whether `entry` is of the given type. Besides the raw `type` field this
+/// understands the synthetic `tool_use` and `tool_result` types, which match
+/// entries carrying at least one block of that kind.
+pub fn entry_has_type(entry: &LogEntry, wanted: &str) -> bool {
+    match wanted {
+        "tool_use" | "toolUse" => flatten_message_blocks(entry)
+            .iter()
+            .any(|block| matches!(block, ContentBlock::ToolUse { .. })),
+        "tool_result" | "toolResult" => flatten_message_blocks(entry)
+            .iter()
+            .any(|block| matches!(block, ContentBlock::ToolResult { .. })),
+        other => entry.entry_type.as_deref() == Some(other),
+    }
+}
+
+pub async fn get_session_logs(
+    Path((project_name, session_id)): Path<(String, String)>,
+    Query(query): Query<SessionLogsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LogEntry>>, ApiError> {
+    let mut entries = read_session_entries(&state, &project_name, &session_id)?;
+
+    let wanted: Vec<&str> = query
+        .entry_types
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect();
+    if !wanted.is_empty() {
+        entries.retain(|entry| wanted.iter().any(|t| entry_has_type(entry, t)));
+    }
+
+    Ok(Json(entries))
+}
+
+pub async fn get_session_entry(
+    Path((project_name, session_id, uuid)): Path<(String, String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<LogEntry>, ApiError> {
+    read_session_entries(&state, &project_name, &session_id)?
+        .into_iter()
+        .find(|entry| entry.uuid.as_deref() == Some(uuid.as_str()))
+        .map(Json)
+        .ok_or(ApiError::EntryNotFound {
+            session: session_id,
+            uuid,
+        })
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportQuery {
+    /// `conversation` for just the user and assistant text.
+    pub mode: Option<String>,
+}
+
+/// Size of the pieces a session export is sent in.
+const EXPORT_CHUNK_BYTES: usize = 64 * 1024;
+
+/// Download a session as Markdown. The log is read a line at a time and the
+/// document rendered on a blocking thread, then sent in chunks as it is
+/// written, so neither a huge session nor its export is ever held in memory
+/// as a whole. `?mode=conversation` leaves out tool calls and their output.
+pub async fn export_session_markdown(
+    Path((project_name, session_id)): Path<(String, String)>,
+    Query(query): Query<ExportQuery>,
+    State(state): State<AppState>,
+) -> Result<Response, ApiError> {
+    let options = match query.mode.as_deref() {
+        None | Some("full") => export::ExportOptions::default(),
+        Some("conversation") => export::ExportOptions {
+            conversation_only: true,
+            ..export::ExportOptions::default()
+        },
+        Some(other) => {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown export mode '{}', expected 'full' or 'conversation'",
+                other
+            )))
+        }
+    };
+    let log_path = session_log_path(&state, &project_name, &session_id)?;
+    let disposition = format!("attachment; filename=\"{}.md\"", session_id);
+
+    let (tx, rx) = tokio::sync::mpsc::channel(4);
+    tokio::task::spawn_blocking(move || {
+        let mut out = ChunkWriter {
+            buffer: Vec::with_capacity(EXPORT_CHUNK_BYTES),
+            tx,
+        };
+        let written = export::write_export_from_log(
+            &mut out,
+            export::ExportFormat::Markdown,
+            &options,
+            &project_name,
+            &session_id,
+            &log_path,
+        )
+        .and_then(|()| out.flush());
+        match written {
+            Ok(()) => {}
+            // The client went away
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            Err(e) => {
+                tracing::warn!(project = %project_name, session = %session_id, error = %e, "Export failed");
+                let _ = out.tx.blocking_send(Err(e));
+            }
+        }
+    });
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/markdown; charset=utf-8".to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        Body::from_stream(tokio_stream::wrappers::ReceiverStream::new(rx)),
+    )
+        .into_response())
+}
+
+/// Buffers writes and hands them to a response body `EXPORT_CHUNK_BYTES` at
+/// a time. The bounded channel holds the writer back while the client is
+/// slow to read.
+struct ChunkWriter {
+    buffer: Vec<u8>,
+    tx: tokio::sync::mpsc::Sender<io::Result<Vec<u8>>>,
+}
+
+impl Write for ChunkWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= EXPORT_CHUNK_BYTES {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(EXPORT_CHUNK_BYTES));
+        self.tx
+            .blocking_send(Ok(chunk))
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SinceQuery {
+    pub after: Option<String>,
+}
+
+/// Entries written after the one with uuid `after`, so a client that
+/// remembers the last entry it saw (e.g. from a permalink) can fetch only
+/// what's new.
+pub async fn get_session_entries_since(
+    Path((project_name, session_id)): Path<(String, String)>,
+    Query(query): Query<SinceQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LogEntry>>, ApiError> {
+    let after = query
+        .after
+        .filter(|uuid| !uuid.is_empty())
+        .ok_or_else(|| ApiError::BadRequest("Missing 'after' entry uuid".to_string()))?;
+
+    let mut entries = read_session_entries(&state, &project_name, &session_id)?;
+    let Some(position) = entries
+        .iter()
+        .position(|entry| entry.uuid.as_deref() == Some(after.as_str()))
+    else {
+        return Err(ApiError::EntryNotFound {
+            session: session_id,
+            uuid: after,
+        });
+    };
+
+    Ok(Json(entries.split_off(position + 1)))
+}
+
+/// Nest sidechain entries under the main-thread entry they branch from,
+/// following `parent_uuid` through any intermediate sidechain entries.
+/// Sidechain runs whose chain ends at a missing parent hang off their first
+/// entry, which becomes an orphaned top-level node. Order is preserved.
+pub fn build_conversation_tree(entries: &[LogEntry]) -> Vec<ConversationNode> {
+    let index_by_uuid: HashMap<&str, usize> = entries
+        .iter()
+        .enumerate()
+        .filter_map(|(i, e)| e.uuid.as_deref().map(|uuid| (uuid, i)))
+        .collect();
+
+    // Only earlier entries count as parents, which rules out cycles.
+    let parent_of = |i: usize| -> Option<usize> {
+        entries[i]
+            .parent_uuid
+            .as_deref()
+            .and_then(|p| index_by_uuid.get(p).copied())
+            .filter(|&p| p < i)
+    };
+    let is_sidechain = |i: usize| entries[i].is_sidechain.unwrap_or(false);
+
+    // anchor[i] is the top-level node entry i belongs under (itself for roots).
+    let mut anchor: Vec<usize> = Vec::with_capacity(entries.len());
+    for i in 0..entries.len() {
+        let a = if !is_sidechain(i) {
+            i
+        } else {
+            match parent_of(i) {
+                Some(p) => anchor[p],
+                None => i,
+            }
+        };
+        anchor.push(a);
+    }
+
+    let mut nodes: Vec<ConversationNode> = Vec::new();
+    let mut node_index: HashMap<usize, usize> = HashMap::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if anchor[i] == i {
+            node_index.insert(i, nodes.len());
+            nodes.push(ConversationNode {
+                entry: entry.clone(),
+                children: Vec::new(),
+                orphaned: is_sidechain(i),
+            });
+        } else {
+            nodes[node_index[&anchor[i]]]
+                .children
+                .push(ConversationNode {
+                    entry: entry.clone(),
+                    children: Vec::new(),
+                    orphaned: false,
+                });
+        }
+    }
+
+    nodes
+}
+
+pub async fn get_session_tree(
+    Path((project_name, session_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ConversationNode>>, ApiError> {
+    let entries = read_session_entries(&state, &project_name, &session_id)?;
+    Ok(Json(build_conversation_tree(&entries)))
+}
+
+pub async fn get_session_blocks(
+    Path((project_name, session_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<EntryBlocks>>, ApiError> {
+    let entries = read_session_entries(&state, &project_name, &session_id)?;
+
+    let blocks = entries
+        .iter()
+        .map(|entry| EntryBlocks {
+            uuid: entry.uuid.clone(),
+            entry_type: entry.entry_type.clone(),
+            role: entry
+                .message
+                .as_ref()
+                .and_then(|m| m.get("role"))
+                .and_then(|r| r.as_str())
+                .map(|r| r.to_string()),
+            timestamp: entry.timestamp,
+            blocks: flatten_message_blocks(entry),
+            estimated_cost_usd: state.pricing.entry_cost(entry),
+        })
+        .collect();
+
+    Ok(Json(blocks))
+}
+
+/// Path of an existing session log, validating both path components. Only
+/// sessions `session_files` would list are found: archived ones and ones
+/// nested deeper than `scan_depth` are not.
+fn session_log_path(
+    state: &AppState,
+    project_name: &str,
+    session_id: &str,
+) -> Result<PathBuf, ApiError> {
+    let log_path = state
+        .projects_dir
+        .join(safe_component(project_name)?)
+        .join(format!("{}.jsonl", safe_session_id(session_id)?));
+
+    let hidden = session_id
+        .split_once('/')
+        .is_some_and(|(folder, _)| folder == ARCHIVE_DIR)
+        || session_id.split('/').count() > state.scan_depth.max(1);
+    if hidden || !log_path.exists() {
+        return Err(ApiError::SessionNotFound {
+            project: project_name.to_string(),
+            session: session_id.to_string(),
+        });
+    }
+    Ok(log_path)
+}
+
+fn read_session_entries(
+    state: &AppState,
+    project_name: &str,
+    session_id: &str,
+) -> Result<Vec<LogEntry>, ApiError> {
+    let log_path = session_log_path(state, project_name, session_id)?;
+    let content = fs::read_to_string(&log_path).map_err(|e| {
+        ApiError::Internal(format!(
+            "Failed to read session '{}' in project '{}': {}",
+            session_id, project_name, e
+        ))
+    })?;
+
+    Ok(parse_log_entries(&content))
+}
+
+/// How much of a log `read_last_entries` reads per step back from the end.
+const TAIL_CHUNK_BYTES: u64 = 64 * 1024;
+
+/// The last `last_n` entries of the log at `path`, oldest first. The file is
+/// read backwards from the end in chunks, so the cost follows `last_n`
+/// rather than the size of the log.
+fn read_last_entries(path: &FsPath, last_n: usize) -> io::Result<Vec<LogEntry>> {
+    let mut file = fs::File::open(path)?;
+    let mut position = file.seek(SeekFrom::End(0))?;
+    // The start of the earliest line read so far, which may continue into
+    // the chunk before it.
+    let mut partial = Vec::new();
+    let mut newest_first = Vec::new();
+
+    while position > 0 && newest_first.len() < last_n {
+        let chunk_len = TAIL_CHUNK_BYTES.min(position);
+        position -= chunk_len;
+        let mut chunk = vec![0; chunk_len as usize];
+        file.seek(SeekFrom::Start(position))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&partial);
+
+        let mut lines = chunk.split(|&b| b == b'\n');
+        // Unless this chunk starts the file, its first line is incomplete.
+        partial = if position > 0 {
+            lines.next().unwrap_or_default().to_vec()
+        } else {
+            Vec::new()
+        };
+        newest_first.extend(
+            lines
+                .rev()
+                .filter_map(|line| serde_json::from_slice::<LogEntry>(trim_log_line(line)).ok())
+                .take(last_n - newest_first.len()),
+        );
+    }
+
+    newest_first.reverse();
+    Ok(newest_first)
+}
+
+/// The project and session id of the session log modified most recently,
+/// across every project.
+pub fn most_recent_session(projects_dir: &FsPath, scan_depth: usize) -> Option<(String, String)> {
+    project_dirs(projects_dir)
+        .into_iter()
+        .flat_map(|project_dir| {
+            let project = project_dir.file_name()?.to_str()?.to_string();
+            Some(
+                session_files(&project_dir, scan_depth)
+                    .filter_map(|file| file.ok())
+                    .map(move |file| (project.clone(), file)),
+            )
+        })
+        .flatten()
+        .filter_map(|(project, file)| {
+            let modified = fs::metadata(&file.path).ok()?.modified().ok()?;
+            Some((modified, project, file.id))
+        })
+        .max_by(|a, b| a.0.cmp(&b.0))
+        .map(|(_, project, session)| (project, session))
+}
+
+/// Aggregate statistics for every session in a project directory, searched
+/// `scan_depth` levels deep, reading each session file once.
+pub fn project_stats(project_dir: &FsPath, scan_depth: usize) -> ProjectStats {
+    let mut stats = ProjectStats {
+        project_name: project_dir
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        ..ProjectStats::default()
+    };
+
+    for file in session_files(project_dir, scan_depth).filter_map(|f| f.ok()) {
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        stats.total_sessions += 1;
+
+        for entry in parse_log_entries(&content) {
+            if matches!(
+                entry.entry_type.as_deref(),
+                Some("user") | Some("assistant")
+            ) {
+                stats.total_messages += 1;
+            }
+
+            if let Some(ts) = entry.timestamp {
+                stats.first_activity = Some(stats.first_activity.map_or(ts, |f| f.min(ts)));
+                stats.last_activity = Some(stats.last_activity.map_or(ts, |l| l.max(ts)));
+            }
+
+            TokenUsage::accumulate(&entry, &mut stats.token_usage, &mut stats.usage_by_model);
+            stats.error_count += tool_error_count(&entry);
+
+            for block in flatten_message_blocks(&entry) {
+                if let ContentBlock::ToolUse { name, .. } = block {
+                    stats.total_tool_calls += 1;
+                    *stats.tool_calls.entry(name).or_default() += 1;
+                }
+            }
+        }
+    }
+
+    stats
+}
+
+/// Metadata for a session log, computed in one pass over its entries. An
+/// unreadable file yields empty metadata.
+pub fn session_meta(path: &FsPath) -> SessionMeta {
+    let mut meta = SessionMeta {
+        session_id: path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string(),
+        ..SessionMeta::default()
+    };
+    let Ok(content) = fs::read_to_string(path) else {
+        return meta;
+    };
+
+    for entry in parse_log_entries(&content) {
+        if matches!(
+            entry.entry_type.as_deref(),
+            Some("user") | Some("assistant")
+        ) {
+            meta.message_count += 1;
+        }
+
+        if let Some(ts) = entry.timestamp {
+            meta.started_at = Some(meta.started_at.map_or(ts, |s| s.min(ts)));
+            meta.last_activity = Some(meta.last_activity.map_or(ts, |l| l.max(ts)));
+        }
+
+        TokenUsage::accumulate(&entry, &mut meta.token_usage, &mut meta.usage_by_model);
+
+        if entry.is_sidechain == Some(true) {
+            meta.has_sidechains = true;
+        }
+        meta.error_count += tool_error_count(&entry);
+
+        if let Some(model) = entry.model() {
+            if !meta.models.iter().any(|m| m == model) {
+                meta.models.push(model.to_string());
+            }
+        }
+
+        for block in flatten_message_blocks(&entry) {
+            if let ContentBlock::ToolUse { name, .. } = block {
+                *meta.tools_used.entry(name).or_default() += 1;
+            }
+        }
+    }
+
+    meta
+}
+
+pub async fn get_session_meta(
+    Path((project_name, session_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<SessionMeta>, ApiError> {
+    let log_path = session_log_path(&state, &project_name, &session_id)?;
+    let mut meta = tokio::task::spawn_blocking(move || session_meta(&log_path))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Metadata task failed: {}", e)))?;
+    meta.estimated_cost_usd = state.pricing.total_cost(&meta.usage_by_model);
+    Ok(Json(meta))
+}
+
+pub async fn get_project_stats(
+    Path(project_name): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<ProjectStats>, ApiError> {
+    let project_dir = state.projects_dir.join(safe_component(&project_name)?);
+    if !project_dir.is_dir() {
+        return Err(ApiError::ProjectNotFound(project_name));
+    }
+
+    let index = state.index.clone();
+    let scan_depth = state.scan_depth;
+    let mut stats = tokio::task::spawn_blocking(move || match index {
+        Some(index) => index.project_stats(&project_name).unwrap_or_else(|e| {
+            tracing::warn!(error = %e, "index query failed, scanning files instead");
+            project_stats(&project_dir, scan_depth)
+        }),
+        None => project_stats(&project_dir, scan_depth),
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Stats task failed: {}", e)))?;
+    stats.estimated_cost_usd = state.pricing.total_cost(&stats.usage_by_model);
+    Ok(Json(stats))
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchHit {
+    pub project: String,
+    pub session: String,
+    /// 1-based line number of the matching entry in the session file.
+    pub line: usize,
+    pub snippet: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchQuery {
+    pub q: Option<String>,
+    pub project: Option<String>,
+    pub limit: Option<usize>,
+}
+
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+const MAX_SEARCH_LIMIT: usize = 1000;
+const SNIPPET_CONTEXT_CHARS: usize = 60;
+
+/// Case-insensitive search through one project's sessions, searched
+/// `scan_depth` levels deep, returning at most `limit` hits (one per
+/// matching entry) in session path order.
+pub fn search_project(
+    project_dir: &FsPath,
+    query: &str,
+    limit: usize,
+    scan_depth: usize,
+) -> Vec<SearchHit> {
+    let project = project_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let needle = query.to_lowercase();
+
+    let mut hits = Vec::new();
+    for file in session_files(project_dir, scan_depth).filter_map(|f| f.ok()) {
+        let Ok(content) = fs::read_to_string(&file.path) else {
+            continue;
+        };
+        let session = file.id;
+
+        for (idx, line) in content.lines().enumerate() {
+            if hits.len() >= limit {
+                return hits;
+            }
+            let Ok(entry) = serde_json::from_slice::<LogEntry>(trim_log_line(line.as_bytes()))
+            else {
+                continue;
+            };
+            let text = entry_search_text(&entry);
+            if let Some(snippet) = match_snippet(&text, &needle) {
+                hits.push(SearchHit {
+                    project: project.clone(),
+                    session: session.clone(),
+                    line: idx + 1,
+                    snippet,
+                    timestamp: entry.timestamp,
+                });
+            }
+        }
+    }
+
+    hits
+}
+
+/// The human-meaningful text of an entry: summary, message text, thinking,
+/// tool inputs and tool output.
+fn entry_search_text(entry: &LogEntry) -> String {
+    let mut parts: Vec<String> = entry.summary.iter().cloned().collect();
+    for block in flatten_message_blocks(entry) {
+        match block {
+            ContentBlock::Text { text } => parts.push(text),
+            ContentBlock::Thinking { thinking } => parts.push(thinking),
+            ContentBlock::ToolUse { name, input, .. } => {
+                parts.push(format!("{} {}", name, input));
+            }
+            ContentBlock::ToolResult { content, .. } => parts.push(match content {
+                Value::String(s) => s,
+                other => other.to_string(),
+            }),
+        }
+    }
+    parts.join("\n")
+}
+
+/// A single-line excerpt around the first occurrence of `needle` (already
+/// lowercased), or `None` when there is no match.
+fn match_snippet(text: &str, needle: &str) -> Option<String> {
+    let lowered = text.to_lowercase();
+    let byte_idx = lowered.find(needle)?;
+
+    // Work in chars so the excerpt never splits a UTF-8 sequence.
+    let chars: Vec<char> = text.chars().collect();
+    let start_char = lowered[..byte_idx].chars().count().min(chars.len());
+    let needle_chars = needle.chars().count();
+    let from = start_char.saturating_sub(SNIPPET_CONTEXT_CHARS);
+    let to = (start_char + needle_chars + SNIPPET_CONTEXT_CHARS).min(chars.len());
+
+    let mut snippet: String = chars[from..to]
+        .iter()
+        .map(|&c| if c == '\n' { ' ' } else { c })
+        .collect();
+    if from > 0 {
+        snippet.insert(0, '…');
+    }
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+pub async fn search(
+    Query(query): Query<SearchQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<Vec<SearchHit>>, ApiError> {
+    let text = query
+        .q
+        .filter(|q| !q.trim().is_empty())
+        .ok_or_else(|| ApiError::BadRequest("Missing search query 'q'".to_string()))?;
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .min(MAX_SEARCH_LIMIT);
+
+    let project_dirs: Vec<PathBuf> = match &query.project {
+        Some(project) => {
+            let dir = state.projects_dir.join(safe_component(project)?);
+            if !dir.is_dir() {
+                return Err(ApiError::ProjectNotFound(project.clone()));
+            }
+            vec![dir]
+        }
+        None => project_dirs(&state.projects_dir),
+    };
+
+    let index = state.index.clone();
+    let scan_depth = state.scan_depth;
+    let project = query.project.clone();
+    let hits = tokio::task::spawn_blocking(move || {
+        if let Some(index) = index {
+            match index.search(&text, project.as_deref(), limit) {
+                Ok(hits) => return hits,
+                Err(e) => tracing::warn!(error = %e, "index query failed, scanning files instead"),
+            }
+        }
+
+        let mut hits = Vec::new();
+        for dir in project_dirs {
+            if hits.len() >= limit {
+                break;
+            }
+            hits.extend(search_project(&dir, &text, limit - hits.len(), scan_depth));
+        }
+        hits
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Search task failed: {}", e)))?;
+
+    Ok(Json(hits))
+}
+
+/// Parse the contents of a session `.jsonl` file, skipping lines that are
+/// not valid log entries.
+pub fn parse_log_entries(content: &str) -> Vec<LogEntry> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_slice::<LogEntry>(trim_log_line(line.as_bytes())).ok())
+        .collect()
+}
+
+/// A log line without the `\r` of a Windows line ending or a UTF-8 byte
+/// order mark, either of which would otherwise stop it parsing.
+pub(crate) fn trim_log_line(line: &[u8]) -> &[u8] {
+    let line = line.strip_suffix(b"\r").unwrap_or(line);
+    line.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(line)
+}
+
+pub async fn websocket_handler(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    ws.on_upgrade(|socket| handle_websocket(socket, state))
+}
+
+/// Anything a WebSocket client may send: a `ClientMessage`, or a request
+/// for the current projects or sessions.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ClientRequest {
+    Message(ClientMessage),
+    List(ListRequest),
+}
+
+/// `{"list":"projects"}` or `{"list":"sessions","project":"my-app"}`,
+/// answered on the same socket with a `project_list` or `session_list`
+/// message.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ListRequest {
+    pub list: ListKind,
+    pub project: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ListKind {
+    Projects,
+    Sessions,
+}
+
+/// The reply to a `ListRequest`.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ListReply {
+    ProjectList {
+        projects: Vec<ProjectSummary>,
+    },
+    SessionList {
+        project: String,
+        sessions: Vec<SessionSummary>,
+    },
+    Error {
+        error: String,
+        code: &'static str,
+    },
+}
+
+impl ListReply {
+    async fn answer(state: &AppState, request: ListRequest) -> Self {
+        let reply = match (request.list, request.project) {
+            (ListKind::Projects, _) => list_projects(state)
+                .await
+                .map(|projects| ListReply::ProjectList { projects }),
+            (ListKind::Sessions, Some(project)) => {
+                let state = state.clone();
+                tokio::task::spawn_blocking(move || {
+                    list_sessions(&state, &project, TimeRange::default())
+                        .map(|sessions| ListReply::SessionList { project, sessions })
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    Err(ApiError::Internal(format!(
+                        "Session list task failed: {}",
+                        e
+                    )))
+                })
+            }
+            (ListKind::Sessions, None) => Err(ApiError::BadRequest(
+                "Listing sessions needs a project".to_string(),
+            )),
+        };
+        reply.unwrap_or_else(|e| ListReply::Error {
+            error: e.to_string(),
+            code: e.code(),
+        })
+    }
+}
+
+/// Messages a WebSocket client may send, e.g.
+/// `{"subscribe":{"project":"my-app"}}`.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        project: String,
+    },
+    Unsubscribe {
+        project: String,
+    },
+    /// Only forward log entries that call one of these tools; an empty
+    /// list lets everything through again.
+    Filter {
+        tools: Vec<String>,
+    },
+    /// Replay the last `last_n` entries of a session, up to 1000, before
+    /// live events.
+    Catchup {
+        project: String,
+        session: String,
+        #[serde(default = "default_catchup_entries")]
+        last_n: usize,
+    },
+}
+
+fn default_catchup_entries() -> usize {
+    50
+}
+
+/// The most entries a single catch-up request replays.
+const MAX_CATCHUP_ENTRIES: usize = 1000;
+
+/// The trailing `last_n` entries of a session, at most
+/// `MAX_CATCHUP_ENTRIES`, wrapped as `log_entry` events. The log is read on a
+/// blocking thread.
+async fn catchup_events(
+    state: &AppState,
+    project: String,
+    session: String,
+    last_n: usize,
+) -> Result<Vec<WatchEvent>, ApiError> {
+    let state = state.clone();
+    let last_n = last_n.min(MAX_CATCHUP_ENTRIES);
+    tokio::task::spawn_blocking(move || {
+        let log_path = session_log_path(&state, &project, &session)?;
+        let entries = read_last_entries(&log_path, last_n).map_err(|e| {
+            ApiError::Internal(format!(
+                "Failed to read session '{}' in project '{}': {}",
+                session, project, e
+            ))
+        })?;
+
+        Ok(entries
+            .into_iter()
+            .map(|entry| WatchEvent {
+                event_type: "log_entry".to_string(),
+                project: project.clone(),
+                session: Some(session.clone()),
+                timestamp: entry.timestamp.unwrap_or_else(Utc::now),
+                entry: Some(entry),
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| ApiError::Internal(format!("Catch-up task failed: {}", e)))?
+}
+
+/// Whether a `log_entry` event calls one of `tools`. Other event types
+/// carry no entry and always pass.
+fn event_uses_tools(event: &WatchEvent, tools: &DashSet<String>) -> bool {
+    if event.event_type != "log_entry" {
+        return true;
+    }
+    event.entry.as_ref().is_some_and(|entry| {
+        flatten_message_blocks(entry).iter().any(
+            |block| matches!(block, ContentBlock::ToolUse { name, .. } if tools.contains(name)),
+        )
+    })
+}
+
+/// How often the server pings an otherwise idle WebSocket client.
+const WS_PING_INTERVAL: std::time::Duration = std::time::Duration::from_secs(30);
+/// How long past a ping the client has to answer before we hang up.
+const WS_PONG_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
 
 async fn handle_websocket(socket: WebSocket, state: AppState) {
     let (mut sender, mut receiver) = socket.split();
     let mut watch_rx = state.watch_manager.subscribe();
+    let filter_state = state.clone();
+
+    // Projects this client asked for; empty means every project.
+    let subscriptions: Arc<DashSet<String>> = Arc::new(DashSet::new());
+    let client_subscriptions = subscriptions.clone();
+
+    // Tool names this client wants to see; empty means no tool filtering.
+    let tool_filter: Arc<DashSet<String>> = Arc::new(DashSet::new());
+    let client_tool_filter = tool_filter.clone();
+
+    // Replies addressed to this client only (catch-up backfill and lists),
+    // already serialized, bypassing the broadcast filters.
+    let (direct_tx, mut direct_rx) = tokio::sync::mpsc::channel::<String>(256);
+    let catchup_state = state.clone();
+
+    // Last time the client showed signs of life; the send task closes the
+    // connection when nothing arrives within WS_PONG_TIMEOUT of a ping.
+    let last_seen = Arc::new(std::sync::Mutex::new(tokio::time::Instant::now()));
+    let client_last_seen = last_seen.clone();
 
     // Handle incoming messages from client
-    let recv_task = tokio::spawn(async move {
+    let mut recv_task = tokio::spawn(async move {
         while let Some(msg) = receiver.next().await {
+            if msg.is_ok() {
+                *client_last_seen.lock().unwrap() = tokio::time::Instant::now();
+            }
             match msg {
-                Ok(Message::Text(text)) => {
-                    println!("Received WebSocket message: {}", text);
-                    // TODO: Handle client messages for subscription management
-                }
+                // Pings are answered automatically; both only count as liveness.
+                Ok(Message::Ping(_)) | Ok(Message::Pong(_)) => {}
+                Ok(Message::Text(text)) => match serde_json::from_str::<ClientRequest>(&text) {
+                    Ok(ClientRequest::Message(ClientMessage::Subscribe { project })) => {
+                        client_subscriptions.insert(project);
+                    }
+                    Ok(ClientRequest::Message(ClientMessage::Unsubscribe { project })) => {
+                        client_subscriptions.remove(&project);
+                    }
+                    Ok(ClientRequest::Message(ClientMessage::Filter { tools })) => {
+                        client_tool_filter.clear();
+                        for tool in tools {
+                            client_tool_filter.insert(tool);
+                        }
+                    }
+                    Ok(ClientRequest::Message(ClientMessage::Catchup {
+                        project,
+                        session,
+                        last_n,
+                    })) => match catchup_events(
+                        &catchup_state,
+                        project.clone(),
+                        session.clone(),
+                        last_n,
+                    )
+                    .await
+                    {
+                        Ok(events) => {
+                            for event in events {
+                                let Ok(json) = serde_json::to_string(&event) else {
+                                    continue;
+                                };
+                                if direct_tx.send(json).await.is_err() {
+                                    return;
+                                }
+                            }
+                        }
+                        Err(e) => tracing::warn!(%project, %session, error = %e, "catch-up failed"),
+                    },
+                    Ok(ClientRequest::List(request)) => {
+                        let reply = ListReply::answer(&catchup_state, request).await;
+                        match serde_json::to_string(&reply) {
+                            Ok(json) => {
+                                if direct_tx.send(json).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Err(e) => tracing::error!(error = %e, "failed to serialize list reply"),
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!(message = %text, error = %e, "ignoring unrecognized WebSocket message");
+                    }
+                },
                 Ok(Message::Close(_)) => {
-                    println!("WebSocket connection closed");
+                    tracing::info!("WebSocket connection closed");
                     break;
                 }
                 Err(e) => {
-                    eprintln!("WebSocket error: {}", e);
+                    tracing::warn!(error = %e, "WebSocket error");
                     break;
                 }
                 _ => {}
@@ -473,12 +2855,74 @@ async fn handle_websocket(socket: WebSocket, state: AppState) {
     });
 
     // Handle outgoing messages to client
-    let send_task = tokio::spawn(async move {
-        while let Ok(watch_event) = watch_rx.recv().await {
+    let mut send_task = tokio::spawn(async move {
+        let mut ping = tokio::time::interval_at(
+            tokio::time::Instant::now() + WS_PING_INTERVAL,
+            WS_PING_INTERVAL,
+        );
+        // When the oldest unanswered ping went out
+        let mut ping_sent: Option<tokio::time::Instant> = None;
+
+        loop {
+            let pong_deadline = ping_sent.map(|sent| sent + WS_PONG_TIMEOUT);
+            let watch_event = tokio::select! {
+                _ = ping.tick() => {
+                    if sender.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                    ping_sent.get_or_insert_with(tokio::time::Instant::now);
+                    continue;
+                }
+                _ = tokio::time::sleep_until(
+                    pong_deadline.unwrap_or_else(tokio::time::Instant::now)
+                ), if pong_deadline.is_some() => {
+                    if ping_sent.is_some_and(|sent| *last_seen.lock().unwrap() >= sent) {
+                        ping_sent = None;
+                        continue;
+                    }
+                    tracing::info!("WebSocket client stopped responding; closing connection");
+                    let _ = sender.send(Message::Close(None)).await;
+                    break;
+                }
+                Some(json) = direct_rx.recv() => {
+                    if sender.send(Message::Text(json)).await.is_err() {
+                        break;
+                    }
+                    continue;
+                }
+                result = watch_rx.recv() => match result {
+                    Ok(event) => {
+                        if !filter_state.watches_project(&event.project) {
+                            continue;
+                        }
+                        if !subscriptions.is_empty() && !subscriptions.contains(&event.project) {
+                            continue;
+                        }
+                        if !tool_filter.is_empty() && !event_uses_tools(&event, &tool_filter) {
+                            continue;
+                        }
+                        event
+                    }
+                    // A slow client fell behind and the oldest events were
+                    // dropped; tell it so and skip the rest of the backlog so
+                    // it resumes at the live tail instead of replaying stale
+                    // events it will only fall behind on again.
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        let notice = serde_json::json!({ "type": "lagged", "skipped": skipped });
+                        if sender.send(Message::Text(notice.to_string())).await.is_err() {
+                            break;
+                        }
+                        watch_rx = watch_rx.resubscribe();
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+            };
+
             let json_msg = match serde_json::to_string(&watch_event) {
                 Ok(json) => json,
                 Err(e) => {
-                    eprintln!("Failed to serialize watch event: {}", e);
+                    tracing::error!(error = %e, "failed to serialize watch event");
                     continue;
                 }
             };
@@ -489,9 +2933,58 @@ async fn handle_websocket(socket: WebSocket, state: AppState) {
         }
     });
 
-    // Wait for either task to complete
+    // Once either side is done, stop the other so the socket is closed
     tokio::select! {
-        _ = recv_task => {},
-        _ = send_task => {},
+        _ = &mut recv_task => send_task.abort(),
+        _ = &mut send_task => recv_task.abort(),
     }
 }
+
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    /// Only stream events for this project.
+    pub project: Option<String>,
+}
+
+/// `GET /events`: the watch stream as Server-Sent Events, one JSON
+/// `WatchEvent` per `data:` line, for clients and proxies that handle plain
+/// HTTP better than WebSockets.
+pub async fn sse_handler(
+    State(state): State<AppState>,
+    Query(query): Query<EventsQuery>,
+) -> Sse<impl futures_util::Stream<Item = Result<SseEvent, std::convert::Infallible>>> {
+    let watch_rx = state.watch_manager.subscribe();
+    let stream = futures_util::stream::unfold(watch_rx, move |mut watch_rx| {
+        let state = state.clone();
+        let project = query.project.clone();
+        async move {
+            loop {
+                let data = match watch_rx.recv().await {
+                    Ok(event) => {
+                        if !state.watches_project(&event.project) {
+                            continue;
+                        }
+                        if project.as_ref().is_some_and(|p| *p != event.project) {
+                            continue;
+                        }
+                        match serde_json::to_string(&event) {
+                            Ok(json) => json,
+                            Err(e) => {
+                                tracing::error!(error = %e, "failed to serialize watch event");
+                                continue;
+                            }
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        watch_rx = watch_rx.resubscribe();
+                        serde_json::json!({ "type": "lagged", "skipped": skipped }).to_string()
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                };
+                return Some((Ok(SseEvent::default().data(data)), watch_rx));
+            }
+        }
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
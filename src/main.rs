@@ -2,22 +2,99 @@
 // ABOUTME: Main executable that sets up CLI parsing and starts the web server
 
 use axum::{
-    routing::{get, get_service},
+    http::{header, HeaderValue, Method},
+    middleware,
+    routing::get,
     Router,
 };
-use clap::Parser;
-use std::path::PathBuf;
-use tower_http::services::ServeDir;
+use chrono_tz::Tz;
+use clap::{
+    parser::ValueSource, Args, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum,
+};
+use std::collections::HashMap;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tower_http::{
+    cors::{AllowOrigin, CorsLayer},
+    trace::{DefaultOnResponse, TraceLayer},
+    LatencyUnit,
+};
+use tracing::Level;
+use tracing_subscriber::EnvFilter;
+use walkdir::WalkDir;
 
 use cc_log_viewer::{
-    get_projects, get_session_logs, get_sessions, index, live_activity, tui::TuiApp,
-    websocket_handler, AppState,
+    config::Config,
+    encode_auth_token,
+    export::{
+        write_combined_markdown_header, write_combined_markdown_section, write_entry,
+        write_export_with_options, ExportFormat, ExportOptions,
+    },
+    export_session_markdown, get_project_stats, get_projects, get_session_blocks,
+    get_session_entries_since, get_session_entry, get_session_logs, get_session_meta,
+    get_session_tree, get_sessions, get_tool_config, get_tools, healthz, index,
+    index::SessionIndex,
+    live_activity, most_recent_session, parse_log_entries,
+    pricing::PricingTable,
+    require_auth_token, search, session_files, sse_handler, static_asset,
+    tool_config::ToolConfig,
+    tui::{LastViewed, TuiApp, DEFAULT_POLL_INTERVAL, DEFAULT_REFRESH_INTERVAL},
+    websocket_handler, AppState, LogEntry, Redactor, TimeRange, WatchManager,
+    DEFAULT_BROADCAST_CAPACITY, DEFAULT_SCAN_DEPTH,
 };
 
 #[derive(Parser)]
 #[clap(name = "cc-log-viewer")]
 #[clap(about = "Claude Code log viewer - Web interface for viewing conversation logs")]
+#[clap(args_conflicts_with_subcommands = true)]
 struct Cli {
+    #[clap(subcommand)]
+    command: Option<Command>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "LEVEL",
+        help = "Diagnostic log level or RUST_LOG-style filter, e.g. debug or cc_log_viewer=trace [default: info, or off for tui]"
+    )]
+    log_level: Option<String>,
+
+    #[clap(
+        long,
+        global = true,
+        value_name = "LEVELS",
+        default_value_t = DEFAULT_SCAN_DEPTH,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        help = "Directory levels below each project to search for sessions, for nested layouts"
+    )]
+    scan_depth: usize,
+
+    /// Running without a subcommand is the same as `serve`.
+    #[clap(flatten)]
+    serve: ServeArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Serve the web interface (the default)
+    Serve(ServeArgs),
+    /// Browse logs in the terminal UI
+    Tui(TuiArgs),
+    /// Write a single session to stdout
+    Export(ExportArgs),
+    /// Write every session of every project into a directory
+    ExportAll(ExportAllArgs),
+    /// Print a session's new entries as they are written, like `tail -f`
+    Tail(TailArgs),
+    /// Follow whichever session was written to most recently
+    Watch(WatchArgs),
+}
+
+#[derive(Args)]
+struct ServeArgs {
     #[clap(
         help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
     )]
@@ -26,21 +103,887 @@ struct Cli {
     #[clap(short, long, default_value = "2006", help = "Port to serve on")]
     port: u16,
 
-    #[clap(long, help = "Use terminal UI instead of web interface")]
-    tui: bool,
+    #[clap(
+        long,
+        default_value = "127.0.0.1",
+        help = "Address to bind to (use 0.0.0.0 to allow LAN access)"
+    )]
+    host: IpAddr,
+
+    #[clap(long, help = "Open the viewer in a browser once the server starts")]
+    open: bool,
+
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        help = "Require this token on every request (or set CC_LOG_VIEWER_TOKEN)"
+    )]
+    auth_token: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "TOML or JSON file defining custom tool handlers"
+    )]
+    tool_config: Option<PathBuf>,
+
+    #[clap(
+        long,
+        help = "Keep an SQLite index of all sessions to speed up search and stats"
+    )]
+    index: bool,
+
+    #[clap(
+        long,
+        value_name = "EVENTS",
+        default_value_t = DEFAULT_BROADCAST_CAPACITY,
+        help = "Live update events buffered per client before slow clients miss some"
+    )]
+    broadcast_capacity: usize,
+
+    #[clap(
+        long,
+        value_name = "ORIGIN",
+        help = "Let pages from this origin call the /api routes (repeatable, or * for any)"
+    )]
+    allow_origin: Vec<String>,
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = Cli::parse();
+#[derive(Args)]
+struct TuiArgs {
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "TOML or JSON file defining custom tool handlers"
+    )]
+    tool_config: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "MS",
+        help = "Milliseconds between project and session list refreshes [default: 2000]"
+    )]
+    refresh_interval: Option<u64>,
+
+    #[clap(
+        long,
+        value_name = "MS",
+        help = "Milliseconds to wait for input between redraws [default: 100]"
+    )]
+    poll_interval: Option<u64>,
+
+    #[clap(
+        long,
+        help = "Only refresh the project and session lists when 'r' is pressed"
+    )]
+    no_auto_refresh: bool,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    #[clap(
+        value_name = "PROJECT/SESSION",
+        help = "Session to export, or just PROJECT with --combine"
+    )]
+    session: String,
+
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value = "markdown", help = "Output format")]
+    format: OutputFormat,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+
+    #[clap(
+        long,
+        help = "Export every session of PROJECT as one Markdown document with a table of contents"
+    )]
+    combine: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "combine",
+        help = "Start Markdown exports with YAML frontmatter (project, session, tools, tokens)"
+    )]
+    frontmatter: bool,
+
+    #[clap(long, help = "Include extra detail such as todo ids")]
+    verbose: bool,
+
+    #[clap(
+        long,
+        help = "Keep only user and assistant text, without tool calls or their output"
+    )]
+    conversation_only: bool,
+
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value = "100000",
+        help = "Cut tool output longer than this many bytes"
+    )]
+    max_output_bytes: usize,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or after this time (e.g. 2024-01-15T00:00:00Z)"
+    )]
+    since: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or before this time"
+    )]
+    until: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "TZ",
+        default_value = "UTC",
+        help = "Show timestamps in this IANA timezone (e.g. Europe/Berlin)"
+    )]
+    timezone: Tz,
+}
+
+#[derive(Args)]
+struct ExportAllArgs {
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        value_name = "DIR",
+        help = "Directory to write <project>/<session> files and index.json into"
+    )]
+    output: PathBuf,
+
+    #[clap(long, value_enum, default_value = "markdown", help = "Output format")]
+    format: OutputFormat,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+
+    #[clap(
+        long,
+        help = "Start Markdown exports with YAML frontmatter (project, session, tools, tokens)"
+    )]
+    frontmatter: bool,
+
+    #[clap(long, help = "Include extra detail such as todo ids")]
+    verbose: bool,
+
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value = "100000",
+        help = "Cut tool output longer than this many bytes"
+    )]
+    max_output_bytes: usize,
+
+    #[clap(long, help = "Skip sessions whose exported file is newer than the log")]
+    update: bool,
+
+    #[clap(
+        long,
+        help = "List what would be exported or skipped without writing anything"
+    )]
+    dry_run: bool,
+
+    #[clap(
+        long,
+        value_enum,
+        default_value = "archive",
+        help = "What to do with exports whose session log no longer exists"
+    )]
+    orphans: OrphanPolicy,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or after this time (e.g. 2024-01-15T00:00:00Z)"
+    )]
+    since: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or before this time"
+    )]
+    until: Option<String>,
 
-    // Default to ~/.claude/projects/ if not specified
-    let projects_dir = if let Some(dir) = cli.projects_dir {
-        dir
+    #[clap(
+        long,
+        value_name = "TZ",
+        default_value = "UTC",
+        help = "Show timestamps in this IANA timezone (e.g. Europe/Berlin)"
+    )]
+    timezone: Tz,
+}
+
+#[derive(Args)]
+struct TailArgs {
+    #[clap(value_name = "PROJECT/SESSION", help = "Session to follow")]
+    session: String,
+
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value = "text", help = "Output format")]
+    format: TailFormat,
+
+    #[clap(
+        short = 'n',
+        long,
+        value_name = "ENTRIES",
+        default_value = "10",
+        help = "Print this many existing entries before following"
+    )]
+    lines: usize,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value = "text", help = "Output format")]
+    format: TailFormat,
+
+    #[clap(
+        short = 'n',
+        long,
+        value_name = "ENTRIES",
+        default_value = "10",
+        help = "Print this many existing entries of each session switched to"
+    )]
+    lines: usize,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum TailFormat {
+    /// Rendered as plain text
+    Text,
+    /// One raw JSON entry per line
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum OrphanPolicy {
+    /// Move them under `<output>/archived/`
+    Archive,
+    /// Delete them
+    Delete,
+    /// Leave them in place
+    Keep,
+}
+
+/// Which sessions an `export-all` run wrote (or would write) and which it
+/// left alone, as paths relative to the output directory.
+#[derive(Default)]
+struct ExportAllSummary {
+    exported: Vec<String>,
+    skipped: Vec<String>,
+    /// Sessions left out by `--since`/`--until`; their old exports are kept.
+    out_of_range: Vec<String>,
+    orphaned: Vec<String>,
+}
+
+impl Command {
+    fn projects_dir(&self) -> Option<&PathBuf> {
+        match self {
+            Command::Serve(args) => args.projects_dir.as_ref(),
+            Command::Tui(args) => args.projects_dir.as_ref(),
+            Command::Export(args) => args.projects_dir.as_ref(),
+            Command::ExportAll(args) => args.projects_dir.as_ref(),
+            Command::Tail(args) => args.projects_dir.as_ref(),
+            Command::Watch(args) => args.projects_dir.as_ref(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum OutputFormat {
+    Markdown,
+    Html,
+    Json,
+}
+
+impl OutputFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Markdown => "md",
+            OutputFormat::Html => "html",
+            OutputFormat::Json => "json",
+        }
+    }
+}
+
+/// Launch the platform's default browser at `url`. Failures are only
+/// warnings; the server keeps running either way.
+fn open_browser(url: &str) {
+    let result = if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .spawn()
     } else {
-        let home = std::env::var("HOME").map_err(|_| "Could not determine home directory")?;
-        PathBuf::from(home).join(".claude").join("projects")
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+
+    if let Err(e) = result {
+        // Not the URL itself, which may carry the auth token
+        tracing::warn!(error = %e, "could not open browser");
+    }
+}
+
+/// Work out where the projects live: the positional argument, then
+/// `CC_LOG_VIEWER_DIR`, then the config file, then `~/.claude/projects`
+/// (`%USERPROFILE%` stands in for `$HOME` on non-Unix platforms). Also returns
+/// a description of which source won.
+fn resolve_projects_dir(
+    cli_dir: Option<&PathBuf>,
+    config: &Config,
+) -> Result<(PathBuf, &'static str), String> {
+    if let Some(dir) = cli_dir {
+        return Ok((dir.clone(), "command line"));
+    }
+    if let Some(dir) = std::env::var_os("CC_LOG_VIEWER_DIR").filter(|d| !d.is_empty()) {
+        return Ok((PathBuf::from(dir), "CC_LOG_VIEWER_DIR"));
+    }
+    if let Some(dir) = &config.projects_dir {
+        return Ok((dir.clone(), "config file"));
+    }
+
+    let home = std::env::var_os("HOME").filter(|h| !h.is_empty());
+    #[cfg(not(unix))]
+    let home = home.or_else(|| std::env::var_os("USERPROFILE").filter(|h| !h.is_empty()));
+    let home = home.ok_or("Could not determine home directory")?;
+
+    Ok((
+        PathBuf::from(home).join(".claude").join("projects"),
+        "default location",
+    ))
+}
+
+/// Print one session to stdout in the requested format.
+fn export_session_to_stdout(
+    projects_dir: &Path,
+    target: &str,
+    format: OutputFormat,
+    options: &ExportOptions,
+    range: TimeRange,
+) -> Result<(), String> {
+    let (project_name, session_id) = target
+        .split_once('/')
+        .ok_or_else(|| format!("Expected <project>/<session>, got '{}'", target))?;
+    let session_id = session_id.trim_end_matches(".jsonl");
+
+    let log_path = projects_dir
+        .join(project_name)
+        .join(format!("{}.jsonl", session_id));
+    if !log_path.is_file() {
+        return Err(format!("Session file not found: {}", log_path.display()));
+    }
+
+    let entries = read_session_entries(&log_path)?;
+    if !range.contains(started_at(&entries)) {
+        return Err(format!(
+            "Session {}/{} did not start within --since/--until",
+            project_name, session_id
+        ));
+    }
+
+    let mut out = BufWriter::new(std::io::stdout().lock());
+    write_session(
+        &mut out,
+        format,
+        options,
+        project_name,
+        session_id,
+        &entries,
+    )
+    .and_then(|_| writeln!(out).and_then(|_| out.flush()))
+    .map_err(|e| format!("Failed to write export: {}", e))
+}
+
+/// Print the last `--lines` entries of a session, then each new entry as the
+/// watcher sees it, until the process is stopped.
+async fn tail_session(
+    projects_dir: &Path,
+    args: &TailArgs,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let (project_name, session_id) = args
+        .session
+        .split_once('/')
+        .ok_or_else(|| format!("Expected <project>/<session>, got '{}'", args.session))?;
+    let session_id = session_id.trim_end_matches(".jsonl");
+
+    let log_path = projects_dir
+        .join(project_name)
+        .join(format!("{}.jsonl", session_id));
+    if !log_path.is_file() {
+        return Err(format!("Session file not found: {}", log_path.display()));
+    }
+
+    let watch_manager = WatchManager::new(projects_dir.to_path_buf())
+        .map_err(|e| format!("Failed to watch {}: {}", projects_dir.display(), e))?;
+    let mut events = watch_manager.subscribe();
+
+    // Start following from the last complete entry printed here, so nothing
+    // is shown twice or skipped in between.
+    let existing = WatchManager::read_new_entries(&log_path, 0)
+        .map_err(|e| format!("Failed to read {}: {}", log_path.display(), e))?;
+    let position = existing.last().map_or(0, |(_, position)| *position);
+    watch_manager.set_session_position(project_name, session_id, position);
+
+    let skip = existing.len().saturating_sub(args.lines);
+    for (entry, _) in existing.iter().skip(skip) {
+        print_tail_entry(args.format, options, entry)?;
+    }
+
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                if event.event_type != "log_entry"
+                    || event.project != project_name
+                    || event.session.as_deref() != Some(session_id)
+                {
+                    continue;
+                }
+                if let Some(entry) = &event.entry {
+                    print_tail_entry(args.format, options, entry)?;
+                }
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(skipped, "fell behind; entries were skipped");
+            }
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+/// Print one entry to stdout for `tail` and `watch`, flushing so it shows
+/// up straight away.
+fn print_tail_entry(
+    format: TailFormat,
+    options: &ExportOptions,
+    entry: &LogEntry,
+) -> Result<(), String> {
+    let mut out = std::io::stdout().lock();
+    let result = match format {
+        TailFormat::Text => write_entry(&mut out, ExportFormat::PlainText, options, entry),
+        TailFormat::Json => {
+            let entry = match &options.redactor {
+                Some(redactor) => redactor.redact_entry(entry),
+                None => entry.clone(),
+            };
+            serde_json::to_writer(&mut out, &entry)
+                .map_err(std::io::Error::from)
+                .and_then(|_| writeln!(out))
+        }
     };
+    result
+        .and_then(|_| out.flush())
+        .map_err(|e| format!("Failed to write entry: {}", e))
+}
+
+/// The session `watch` is currently printing, and how far it has read.
+struct FollowedSession {
+    project: String,
+    session: String,
+    path: PathBuf,
+    position: u64,
+}
+
+/// Print the entries of `followed` written since it was last read.
+fn print_new_entries(
+    followed: &mut FollowedSession,
+    format: TailFormat,
+    options: &ExportOptions,
+) -> Result<(), String> {
+    let entries = WatchManager::read_new_entries(&followed.path, followed.position)
+        .map_err(|e| format!("Failed to read {}: {}", followed.path.display(), e))?;
+    for (entry, position) in entries {
+        print_tail_entry(format, options, &entry)?;
+        followed.position = position;
+    }
+    Ok(())
+}
+
+/// Switch to the most recently modified session if it isn't the one being
+/// followed, printing a header and its last `--lines` entries.
+fn follow_latest_session(
+    projects_dir: &Path,
+    args: &WatchArgs,
+    options: &ExportOptions,
+    scan_depth: usize,
+    followed: &mut Option<FollowedSession>,
+) -> Result<(), String> {
+    let Some((project, session)) = most_recent_session(projects_dir, scan_depth) else {
+        return Ok(());
+    };
+    if followed
+        .as_ref()
+        .is_some_and(|f| f.project == project && f.session == session)
+    {
+        return Ok(());
+    }
+
+    let path = projects_dir
+        .join(&project)
+        .join(format!("{}.jsonl", session));
+    let existing = WatchManager::read_new_entries(&path, 0)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    eprintln!("==> {}/{} <==", project, session);
+    let skip = existing.len().saturating_sub(args.lines);
+    for (entry, _) in existing.iter().skip(skip) {
+        print_tail_entry(args.format, options, entry)?;
+    }
+
+    *followed = Some(FollowedSession {
+        project,
+        session,
+        path,
+        position: existing.last().map_or(0, |(_, position)| *position),
+    });
+    Ok(())
+}
+
+/// Tail whichever session was modified most recently. Every write to
+/// another session re-checks which is newest, so the output switches to a
+/// session as soon as Claude starts working in it.
+async fn watch_latest_session(
+    projects_dir: &Path,
+    args: &WatchArgs,
+    options: &ExportOptions,
+    scan_depth: usize,
+) -> Result<(), String> {
+    let watch_manager = WatchManager::new(projects_dir.to_path_buf())
+        .map_err(|e| format!("Failed to watch {}: {}", projects_dir.display(), e))?;
+    let mut events = watch_manager.subscribe();
+
+    let mut followed = None;
+    follow_latest_session(projects_dir, args, options, scan_depth, &mut followed)?;
+    if followed.is_none() {
+        eprintln!("Waiting for a session to be written...");
+    }
+
+    loop {
+        // Entries are read from the file itself; events only say when and
+        // where something was written.
+        let written_to = match events.recv().await {
+            Ok(event) if event.event_type == "log_entry" => Some((event.project, event.session)),
+            Ok(_) => continue,
+            // Events were dropped, so anything may have changed
+            Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => None,
+            Err(tokio::sync::broadcast::error::RecvError::Closed) => return Ok(()),
+        };
+
+        let is_followed = matches!(
+            (&followed, &written_to),
+            (Some(f), Some((project, Some(session))))
+                if f.project == *project && f.session == *session
+        );
+        if !is_followed {
+            follow_latest_session(projects_dir, args, options, scan_depth, &mut followed)?;
+        }
+        if let Some(followed) = &mut followed {
+            print_new_entries(followed, args.format, options)?;
+        }
+    }
+}
+
+/// Write one session's entries to `out` in the requested output format.
+fn write_session(
+    out: &mut impl Write,
+    format: OutputFormat,
+    options: &ExportOptions,
+    project_name: &str,
+    session_id: &str,
+    entries: &[LogEntry],
+) -> std::io::Result<()> {
+    let mut export =
+        |format| write_export_with_options(out, format, options, project_name, session_id, entries);
+    match format {
+        OutputFormat::Markdown => export(ExportFormat::Markdown),
+        OutputFormat::Html => export(ExportFormat::Html),
+        OutputFormat::Json => {
+            let entries: Vec<_> = match &options.redactor {
+                Some(redactor) => entries.iter().map(|e| redactor.redact_entry(e)).collect(),
+                None => entries.to_vec(),
+            };
+            serde_json::to_writer_pretty(out, &entries).map_err(std::io::Error::from)
+        }
+    }
+}
+
+/// Read and parse a session log, keeping only the parsed entries.
+fn read_session_entries(log_path: &Path) -> Result<Vec<LogEntry>, String> {
+    let content = std::fs::read_to_string(log_path)
+        .map_err(|e| format!("Failed to read {}: {}", log_path.display(), e))?;
+    Ok(parse_log_entries(&content))
+}
+
+/// When a session started: its earliest timestamp, as `list_sessions` and
+/// `--since`/`--until` use it.
+fn started_at(entries: &[LogEntry]) -> Option<chrono::DateTime<chrono::Utc>> {
+    entries.iter().filter_map(|e| e.timestamp).min()
+}
+
+/// Export every session of every project to `output/<project>/<session>.<ext>`,
+/// then write `output/index.json` listing every file. Sessions are parsed and
+/// written one at a time, so memory use doesn't grow with the number of
+/// sessions. Sessions that didn't start within `--since`/`--until` are left
+/// out. With `--update`, sessions whose export is newer than the log are left
+/// as they are and, when the last `index.json` lists them, not read at all;
+/// exports left over from deleted sessions are handled per `--orphans`. With
+/// `--dry-run` nothing is written at all.
+fn export_all_projects(
+    projects_dir: &Path,
+    args: &ExportAllArgs,
+    options: &ExportOptions,
+    scan_depth: usize,
+) -> Result<ExportAllSummary, String> {
+    let (output, format, orphans) = (args.output.as_path(), args.format, args.orphans);
+    let range = TimeRange::parse(args.since.as_deref(), args.until.as_deref())
+        .map_err(|e| format!("Invalid --since/--until date: {}", e))?;
+    let previous_rows = if args.update {
+        previous_manifest_rows(output)
+    } else {
+        HashMap::new()
+    };
+    let mut project_dirs: Vec<_> = std::fs::read_dir(projects_dir)
+        .map_err(|e| format!("Failed to read {}: {}", projects_dir.display(), e))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect();
+    project_dirs.sort();
+
+    let mut summary = ExportAllSummary::default();
+    let mut files = Vec::new();
+    for project_dir in project_dirs {
+        let Some(project_name) = project_dir.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        for file in session_files(&project_dir, scan_depth) {
+            let file =
+                file.map_err(|e| format!("Failed to read {}: {}", project_dir.display(), e))?;
+            let (session_id, log_path) = (file.id.as_str(), &file.path);
+
+            let relative_path = format!("{}/{}.{}", project_name, session_id, format.extension());
+            let target = output.join(&relative_path);
+            let modified = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            let up_to_date = args.update
+                && matches!(
+                    (modified(&target), modified(log_path)),
+                    (Some(exported), Some(logged)) if exported >= logged
+                );
+
+            // An unchanged session's row is carried over rather than
+            // re-reading its log; anything else is parsed.
+            let carried = up_to_date
+                .then(|| previous_rows.get(&relative_path).cloned())
+                .flatten();
+            let (row, entries) = match carried {
+                Some(row) => (row, None),
+                None => {
+                    let entries = read_session_entries(log_path)?;
+                    let row = manifest_row(project_name, session_id, &relative_path, &entries);
+                    (row, Some(entries))
+                }
+            };
+
+            let started = row["timestamp"]
+                .as_str()
+                .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+                .map(|ts| ts.with_timezone(&chrono::Utc));
+            if !range.contains(started) {
+                summary.out_of_range.push(relative_path);
+                continue;
+            }
+
+            match entries {
+                Some(entries) if !up_to_date => {
+                    if !args.dry_run {
+                        if let Some(parent) = target.parent() {
+                            std::fs::create_dir_all(parent).map_err(|e| {
+                                format!("Failed to create {}: {}", parent.display(), e)
+                            })?;
+                        }
+                        std::fs::File::create(&target)
+                            .and_then(|file| {
+                                let mut out = BufWriter::new(file);
+                                write_session(
+                                    &mut out,
+                                    format,
+                                    options,
+                                    project_name,
+                                    session_id,
+                                    &entries,
+                                )?;
+                                out.flush()
+                            })
+                            .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+                    }
+                    summary.exported.push(relative_path);
+                }
+                _ => summary.skipped.push(relative_path),
+            }
+            files.push(row);
+        }
+    }
+
+    summary.orphaned = find_orphaned_exports(output, format, &summary)?;
+    if orphans != OrphanPolicy::Keep && !args.dry_run {
+        for relative_path in &summary.orphaned {
+            let path = output.join(relative_path);
+            let result = if orphans == OrphanPolicy::Delete {
+                std::fs::remove_file(&path)
+            } else {
+                let archived = output.join("archived").join(relative_path);
+                archived
+                    .parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .and_then(|_| std::fs::rename(&path, &archived))
+            };
+            result.map_err(|e| format!("Failed to clean up {}: {}", path.display(), e))?;
+        }
+    }
+
+    if args.dry_run {
+        return Ok(summary);
+    }
+
+    // Written last so its presence means the export finished.
+    let manifest = serde_json::json!({
+        "generator": "cc-log-viewer",
+        "version": env!("CARGO_PKG_VERSION"),
+        "generated_at": chrono::Utc::now(),
+        "files": files,
+    });
+    let manifest_path = output.join("index.json");
+    let manifest = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    std::fs::write(&manifest_path, manifest)
+        .map_err(|e| format!("Failed to write {}: {}", manifest_path.display(), e))?;
+
+    Ok(summary)
+}
+
+/// Print every session in a project that started within `range` to stdout as
+/// one Markdown document, oldest session first. Sessions are read once to
+/// order the table of contents and again as each is written, so only one is
+/// held in memory.
+fn export_project_to_stdout(
+    projects_dir: &Path,
+    project_name: &str,
+    format: OutputFormat,
+    options: &ExportOptions,
+    range: TimeRange,
+    scan_depth: usize,
+) -> Result<(), String> {
+    if !matches!(format, OutputFormat::Markdown) {
+        return Err("--combine only supports --format markdown".to_string());
+    }
+    let project_name = project_name.trim_end_matches('/');
+    if project_name.contains('/') {
+        return Err(format!(
+            "Expected a project name with --combine, got '{}'",
+            project_name
+        ));
+    }
+
+    let project_dir = projects_dir.join(project_name);
+    std::fs::read_dir(&project_dir)
+        .map_err(|e| format!("Failed to read {}: {}", project_dir.display(), e))?;
+
+    let mut sessions = Vec::new();
+    for file in session_files(&project_dir, scan_depth).flatten() {
+        let entries = read_session_entries(&file.path)?;
+        let started = started_at(&entries);
+        if !entries.is_empty() && range.contains(started) {
+            sessions.push((file.id, started));
+        }
+    }
+    sessions.sort_by(|(a_id, a), (b_id, b)| a.cmp(b).then_with(|| a_id.cmp(b_id)));
+
+    let write_error = |e: std::io::Error| format!("Failed to write export: {}", e);
+    let mut out = BufWriter::new(std::io::stdout().lock());
+    write_combined_markdown_header(&mut out, options, project_name, &sessions)
+        .map_err(write_error)?;
+    for (session_id, _) in &sessions {
+        let entries = read_session_entries(&project_dir.join(format!("{}.jsonl", session_id)))?;
+        write_combined_markdown_section(&mut out, options, session_id, &entries)
+            .map_err(write_error)?;
+    }
+    writeln!(out).and_then(|_| out.flush()).map_err(write_error)
+}
+
+/// Send diagnostics to stderr, filtered by `--log-level`, then `RUST_LOG`,
+/// then `default_level`.
+fn init_tracing(log_level: Option<&str>, default_level: &str) -> Result<(), String> {
+    let filter = match log_level {
+        Some(level) => EnvFilter::try_new(level)
+            .map_err(|e| format!("Invalid --log-level {:?}: {}", level, e))?,
+        None => EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level)),
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .init();
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches)?;
+    // Log lines would scribble over the terminal UI, so it stays quiet
+    // unless asked.
+    let default_level = match cli.command {
+        Some(Command::Tui(_)) => "off",
+        _ => "info",
+    };
+    init_tracing(cli.log_level.as_deref(), default_level)?;
+    let config = Config::load_default().map_err(|e| format!("Failed to load config: {}", e))?;
+    let scan_depth = cli.scan_depth;
+    let command = cli.command.unwrap_or(Command::Serve(cli.serve));
+
+    let (projects_dir, source) = resolve_projects_dir(command.projects_dir(), &config)?;
+    // stderr, so `export` output on stdout stays clean
+    eprintln!(
+        "📂 Using projects directory {} (from {})",
+        projects_dir.display(),
+        source
+    );
 
     if !projects_dir.exists() {
         eprintln!(
@@ -50,41 +993,478 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("Tip: Claude Code logs are typically stored in ~/.claude/projects/");
         std::process::exit(1);
     }
+    let has_projects = std::fs::read_dir(&projects_dir)
+        .map(|mut entries| entries.any(|e| e.is_ok_and(|e| e.path().is_dir())))
+        .unwrap_or(false);
+    if !has_projects {
+        eprintln!(
+            "No Claude Code projects found in {} yet; they will appear once a session is logged",
+            projects_dir.display()
+        );
+    }
+
+    match command {
+        Command::Export(args) => {
+            let range = TimeRange::parse(args.since.as_deref(), args.until.as_deref())
+                .map_err(|e| format!("Invalid --since/--until date: {}", e))?;
+
+            let mut options = export_options(&config, args.redact, args.frontmatter, args.verbose)?;
+            options.max_output_bytes = args.max_output_bytes;
+            options.timezone = args.timezone;
+            options.conversation_only = args.conversation_only;
+            let result = if args.combine {
+                export_project_to_stdout(
+                    &projects_dir,
+                    &args.session,
+                    args.format,
+                    &options,
+                    range,
+                    scan_depth,
+                )
+            } else {
+                export_session_to_stdout(&projects_dir, &args.session, args.format, &options, range)
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::ExportAll(args) => {
+            let mut options = export_options(&config, args.redact, args.frontmatter, args.verbose)?;
+            options.max_output_bytes = args.max_output_bytes;
+            options.timezone = args.timezone;
+            let result = export_all_projects(&projects_dir, &args, &options, scan_depth);
+            match result {
+                Ok(summary) if args.dry_run => {
+                    for path in &summary.exported {
+                        println!("would export {}", path);
+                    }
+                    for path in &summary.skipped {
+                        println!("would skip {}", path);
+                    }
+                    let orphan_action = match args.orphans {
+                        OrphanPolicy::Archive => "archive",
+                        OrphanPolicy::Delete => "delete",
+                        OrphanPolicy::Keep => "keep",
+                    };
+                    for path in &summary.orphaned {
+                        println!("would {} {}", orphan_action, path);
+                    }
+                    eprintln!(
+                        "Dry run: would export {} sessions, skip {} unchanged and {} {} orphaned",
+                        summary.exported.len(),
+                        summary.skipped.len(),
+                        orphan_action,
+                        summary.orphaned.len()
+                    );
+                }
+                Ok(summary) => {
+                    let orphan_note = match args.orphans {
+                        OrphanPolicy::Archive => "archived",
+                        OrphanPolicy::Delete => "deleted",
+                        OrphanPolicy::Keep => "kept",
+                    };
+                    eprintln!(
+                        "Exported {} sessions to {} ({} unchanged, {} orphaned {})",
+                        summary.exported.len(),
+                        args.output.display(),
+                        summary.skipped.len(),
+                        summary.orphaned.len(),
+                        orphan_note
+                    );
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Command::Tail(args) => {
+            let options = export_options(&config, args.redact, false, false)?;
+            if let Err(e) = tail_session(&projects_dir, &args, &options).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Watch(args) => {
+            let options = export_options(&config, args.redact, false, false)?;
+            if let Err(e) = watch_latest_session(&projects_dir, &args, &options, scan_depth).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        Command::Tui(args) => {
+            let state = build_state(
+                projects_dir,
+                &config,
+                args.tool_config.as_deref(),
+                DEFAULT_BROADCAST_CAPACITY,
+                scan_depth,
+            )?;
+
+            println!("🖥️  Starting Claude Code Log Viewer in Terminal UI mode");
+            println!("Press 'q' to quit, '↑/↓' to navigate, 'Enter' to select");
+
+            let tui_config = &config.tui;
+            let auto_refresh = !args.no_auto_refresh && tui_config.auto_refresh != Some(false);
+            let refresh = args
+                .refresh_interval
+                .or(tui_config.refresh_interval_ms)
+                .map_or(DEFAULT_REFRESH_INTERVAL, Duration::from_millis);
+            let poll = args
+                .poll_interval
+                .or(tui_config.poll_interval_ms)
+                .map_or(DEFAULT_POLL_INTERVAL, Duration::from_millis);
+
+            let mut tui_app =
+                TuiApp::new(state).with_intervals(auto_refresh.then_some(refresh), poll);
+            if let Some(path) = LastViewed::default_path() {
+                tui_app = tui_app.with_state_file(path);
+            }
+            tui_app.run().await?;
+        }
+        Command::Serve(mut args) => {
+            // Settings resolve as: CLI flag > environment variable > config
+            // file > built-in default. `port` always has a clap default, so
+            // only treat it as explicit when it came from the command line.
+            let serve_matches = matches.subcommand_matches("serve").unwrap_or(&matches);
+            if serve_matches.value_source("port") != Some(ValueSource::CommandLine) {
+                let env_port = std::env::var("CC_LOG_VIEWER_PORT")
+                    .ok()
+                    .and_then(|p| p.parse().ok());
+                if let Some(port) = env_port.or(config.port) {
+                    args.port = port;
+                }
+            }
+
+            let mut state = build_state(
+                projects_dir,
+                &config,
+                args.tool_config.as_deref(),
+                args.broadcast_capacity,
+                scan_depth,
+            )?;
+            if args.index {
+                state = attach_index(state).await?;
+            }
+            serve(state, &args).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The `index.json` row listing one exported session.
+fn manifest_row(
+    project_name: &str,
+    session_id: &str,
+    relative_path: &str,
+    entries: &[LogEntry],
+) -> serde_json::Value {
+    let message_count = entries
+        .iter()
+        .filter(|e| matches!(e.entry_type.as_deref(), Some("user") | Some("assistant")))
+        .count();
+    serde_json::json!({
+        "project": project_name,
+        "session_id": session_id,
+        "timestamp": started_at(entries),
+        "message_count": message_count,
+        "path": relative_path,
+    })
+}
+
+/// The rows of the `index.json` a previous run left in `output`, by path.
+/// A missing or unreadable manifest just means every log is read again.
+fn previous_manifest_rows(output: &Path) -> HashMap<String, serde_json::Value> {
+    let Ok(content) = std::fs::read(output.join("index.json")) else {
+        return HashMap::new();
+    };
+    let Ok(mut manifest) = serde_json::from_slice::<serde_json::Value>(&content) else {
+        return HashMap::new();
+    };
+    match manifest["files"].take() {
+        serde_json::Value::Array(rows) => rows
+            .into_iter()
+            .filter_map(|row| Some((row["path"].as_str()?.to_string(), row)))
+            .collect(),
+        _ => HashMap::new(),
+    }
+}
 
-    let state = AppState::new(projects_dir)
-        .map_err(|e| format!("Failed to initialize watch manager: {}", e))?;
+/// Exports under `output/<project>/` with this run's extension that don't
+/// belong to any current session, as paths relative to `output`.
+fn find_orphaned_exports(
+    output: &Path,
+    format: OutputFormat,
+    summary: &ExportAllSummary,
+) -> Result<Vec<String>, String> {
+    let Ok(project_dirs) = std::fs::read_dir(output) else {
+        // Nothing exported yet
+        return Ok(Vec::new());
+    };
 
-    if cli.tui {
-        // Terminal UI mode
-        println!("🖥️  Starting Claude Code Log Viewer in Terminal UI mode");
-        println!("Press 'q' to quit, '↑/↓' to navigate, 'Enter' to select");
+    let current: std::collections::HashSet<&String> = summary
+        .exported
+        .iter()
+        .chain(&summary.skipped)
+        .chain(&summary.out_of_range)
+        .collect();
+    let mut orphaned = Vec::new();
+    for project_dir in project_dirs.flatten() {
+        let project_path = project_dir.path();
+        let Some(project_name) = project_path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !project_path.is_dir() || project_name == "archived" {
+            continue;
+        }
+        // Sessions in nested folders are exported to matching subfolders
+        for file in WalkDir::new(&project_path).min_depth(1) {
+            let file =
+                file.map_err(|e| format!("Failed to read {}: {}", project_path.display(), e))?;
+            let path = file.path();
+            if !file.file_type().is_file()
+                || path.extension().and_then(|e| e.to_str()) != Some(format.extension())
+            {
+                continue;
+            }
+            let Some(relative_path) = path
+                .strip_prefix(output)
+                .ok()
+                .and_then(|p| p.components().map(|c| c.as_os_str().to_str()).collect())
+                .map(|parts: Vec<_>| parts.join("/"))
+            else {
+                continue;
+            };
+            if !current.contains(&relative_path) {
+                orphaned.push(relative_path);
+            }
+        }
+    }
+    orphaned.sort();
+    Ok(orphaned)
+}
 
-        let mut tui_app = TuiApp::new(state);
-        tui_app.run().await?;
+/// Export settings shared by `export` and `export-all`. `--redact` uses the
+/// config file's patterns when it has any.
+fn export_options(
+    config: &Config,
+    redact: bool,
+    frontmatter: bool,
+    verbose: bool,
+) -> Result<ExportOptions, String> {
+    let redactor = if !redact {
+        None
+    } else if config.redact_patterns.is_empty() {
+        Some(Redactor::default())
     } else {
-        // Web UI mode (default)
-        let app = Router::new()
-            .route("/", get(index))
-            .route("/live", get(live_activity))
-            .route("/api/projects", get(get_projects))
-            .route("/api/projects/:project/sessions", get(get_sessions))
-            .route(
-                "/api/projects/:project/sessions/:session",
-                get(get_session_logs),
-            )
-            .route("/ws/watch", get(websocket_handler))
-            .nest_service("/static", get_service(ServeDir::new("static")))
-            .fallback(index) // Serve index.html for all other routes (SPA routing)
-            .with_state(state);
-
-        let listener = tokio::net::TcpListener::bind(format!("0.0.0.0:{}", cli.port)).await?;
-        println!(
-            "🚀 Claude Code Log Viewer running on http://localhost:{}",
-            cli.port
+        Some(
+            Redactor::new(&config.redact_patterns)
+                .map_err(|e| format!("Invalid redact pattern in config: {}", e))?,
+        )
+    };
+
+    Ok(ExportOptions {
+        redactor,
+        frontmatter,
+        verbose,
+        ..ExportOptions::default()
+    })
+}
+
+/// Set up shared state for the interactive modes, applying `--tool-config`.
+fn build_state(
+    projects_dir: PathBuf,
+    config: &Config,
+    tool_config: Option<&Path>,
+    broadcast_capacity: usize,
+    scan_depth: usize,
+) -> Result<AppState, String> {
+    let mut state = AppState::new_with_watch_options(
+        projects_dir,
+        WatchManager::default_positions_path(),
+        broadcast_capacity,
+    )
+    .map_err(|e| format!("Failed to initialize watch manager: {}", e))?
+    .with_watch_projects(config.watch_projects.clone())
+    .with_scan_depth(scan_depth)
+    .with_pricing(PricingTable::with_overrides(config.pricing.clone()));
+
+    if let Some(path) = tool_config {
+        let tool_config = ToolConfig::load(path)
+            .map_err(|e| format!("Failed to load tool config {}: {}", path.display(), e))?;
+        state = state.with_tool_config(tool_config);
+    }
+
+    Ok(state)
+}
+
+/// Open the session index, bring it up to date and keep it current from
+/// the state's watch events.
+async fn attach_index(state: AppState) -> Result<AppState, String> {
+    let path = SessionIndex::default_path()
+        .ok_or_else(|| "Cannot locate a cache directory for the index".to_string())?;
+    let index = SessionIndex::open(&path, state.projects_dir.clone())
+        .map_err(|e| format!("Failed to open index {}: {}", path.display(), e))?
+        .with_scan_depth(state.scan_depth);
+    let state = state.with_index(index);
+
+    let index = state.index.clone().expect("index was just attached");
+    let events = state.watch_manager.subscribe();
+    let sessions = tokio::task::spawn_blocking({
+        let index = Arc::clone(&index);
+        move || index.sync()
+    })
+    .await
+    .map_err(|e| format!("Index task failed: {}", e))?
+    .map_err(|e| format!("Failed to build index {}: {}", path.display(), e))?;
+    println!("📇 Indexed {} sessions in {}", sessions, path.display());
+
+    index.follow(events);
+    Ok(state)
+}
+
+/// CORS for `--allow-origin`; `None` when no origin is allowed, so no CORS
+/// headers are sent at all.
+fn cors_layer(origins: &[String]) -> Result<Option<CorsLayer>, String> {
+    if origins.is_empty() {
+        return Ok(None);
+    }
+    let allow_origin = if origins.iter().any(|o| o == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins = origins
+            .iter()
+            .map(|o| {
+                HeaderValue::from_str(o.trim_end_matches('/'))
+                    .map_err(|_| format!("Invalid --allow-origin {:?}", o))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        AllowOrigin::list(origins)
+    };
+    Ok(Some(
+        CorsLayer::new()
+            .allow_origin(allow_origin)
+            .allow_methods([Method::GET])
+            .allow_headers([header::AUTHORIZATION]),
+    ))
+}
+
+/// Run the web viewer until the process is stopped.
+async fn serve(state: AppState, args: &ServeArgs) -> Result<(), Box<dyn std::error::Error>> {
+    let api = Router::new()
+        .route("/api/projects", get(get_projects))
+        .route("/api/tool-config", get(get_tool_config))
+        .route("/api/search", get(search))
+        .route("/api/tools", get(get_tools))
+        .route("/api/projects/:project/sessions", get(get_sessions))
+        .route("/api/projects/:project/stats", get(get_project_stats))
+        .route(
+            "/api/projects/:project/sessions/:session",
+            get(get_session_logs),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/blocks",
+            get(get_session_blocks),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/entry/:uuid",
+            get(get_session_entry),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/tree",
+            get(get_session_tree),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/export.md",
+            get(export_session_markdown),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/meta",
+            get(get_session_meta),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/since",
+            get(get_session_entries_since),
         );
+    let pages = Router::new()
+        .route("/", get(index))
+        .route("/live", get(live_activity))
+        .route("/healthz", get(healthz))
+        .route("/ws/watch", get(websocket_handler))
+        .route("/events", get(sse_handler))
+        .route("/static/*path", get(static_asset))
+        .fallback(index); // Serve index.html for all other routes (SPA routing)
+
+    let auth_token = args
+        .auth_token
+        .clone()
+        .or_else(|| std::env::var("CC_LOG_VIEWER_TOKEN").ok())
+        .filter(|t| !t.is_empty());
+    let (pages, api) = match &auth_token {
+        Some(token) => {
+            let auth = middleware::from_fn_with_state(Arc::new(token.clone()), require_auth_token);
+            (pages.layer(auth.clone()), api.layer(auth))
+        }
+        None => (pages, api),
+    };
+    // Only the JSON API is opened up to other origins. CORS sits outside
+    // the token check, so a rejected request's 401 still reaches the page.
+    let api = match cors_layer(&args.allow_origin)? {
+        Some(cors) => api.layer(cors),
+        None => api,
+    };
+
+    let app = pages.merge(api).with_state(state);
+    // One debug line per request with its status and latency; the span
+    // records the path only, so a `?token=` never reaches the logs.
+    let app = app.layer(
+        TraceLayer::new_for_http()
+            .make_span_with(|request: &axum::extract::Request| {
+                // Same target as the layer's events, so `tower_http=debug`
+                // turns both on
+                tracing::debug_span!(
+                    target: "tower_http::trace",
+                    "request",
+                    method = %request.method(),
+                    path = %request.uri().path()
+                )
+            })
+            .on_request(())
+            .on_response(
+                DefaultOnResponse::new()
+                    .level(Level::DEBUG)
+                    .latency_unit(LatencyUnit::Millis),
+            ),
+    );
+
+    let listener = tokio::net::TcpListener::bind(SocketAddr::new(args.host, args.port)).await?;
+    let bound = listener.local_addr()?;
+    // A wildcard bind isn't a browsable address, so point at localhost.
+    let url = if bound.ip().is_unspecified() {
+        format!("http://localhost:{}", bound.port())
+    } else {
+        format!("http://{}", bound)
+    };
+    println!(
+        "🚀 Claude Code Log Viewer running on {} (bound to {})",
+        url, bound
+    );
+
+    // The first visit needs the token in the URL; after that a cookie carries it.
+    let url = match &auth_token {
+        Some(token) => format!("{}/?token={}", url, encode_auth_token(token)),
+        None => url,
+    };
+    if auth_token.is_some() {
+        println!("🔒 Token required; open {}", url);
+    }
 
-        axum::serve(listener, app).await?;
+    if args.open {
+        open_browser(&url);
     }
 
+    axum::serve(listener, app).await?;
     Ok(())
 }
@@ -0,0 +1,104 @@
+// ABOUTME: Estimated USD cost of Claude API usage from per-model token prices
+// ABOUTME: Built-in prices for Claude model families, overridable from the config file
+
+use crate::{LogEntry, TokenUsage, UNKNOWN_MODEL};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Prices in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Writing to the prompt cache (`cache_creation_input_tokens`).
+    pub cache_write: f64,
+    /// Reading from the prompt cache (`cache_read_input_tokens`).
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in prices, matched against model names in order, so more specific
+/// patterns come first.
+const BUILTIN_PRICING: &[(&str, ModelPricing)] = &[
+    ("opus-4-5", ModelPricing::new(5.0, 25.0, 6.25, 0.50)),
+    ("opus", ModelPricing::new(15.0, 75.0, 18.75, 1.50)),
+    ("sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.30)),
+    ("haiku-4", ModelPricing::new(1.0, 5.0, 1.25, 0.10)),
+    ("3-5-haiku", ModelPricing::new(0.80, 4.0, 1.0, 0.08)),
+    ("haiku", ModelPricing::new(0.25, 1.25, 0.30, 0.03)),
+];
+
+/// Looks up prices by model name. A pattern applies to every model whose
+/// name contains it; overrides are checked before the built-in table.
+#[derive(Debug, Clone, Default)]
+pub struct PricingTable {
+    overrides: BTreeMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    pub fn with_overrides(overrides: BTreeMap<String, ModelPricing>) -> Self {
+        Self { overrides }
+    }
+
+    pub fn pricing_for(&self, model: &str) -> Option<ModelPricing> {
+        if let Some(pricing) = self.overrides.get(model) {
+            return Some(*pricing);
+        }
+        // Longest matching override wins, so `sonnet-4` beats `sonnet`.
+        let overridden = self
+            .overrides
+            .iter()
+            .filter(|(pattern, _)| model.contains(pattern.as_str()))
+            .max_by_key(|(pattern, _)| pattern.len())
+            .map(|(_, pricing)| *pricing);
+        overridden.or_else(|| {
+            BUILTIN_PRICING
+                .iter()
+                .find(|(pattern, _)| model.contains(pattern))
+                .map(|(_, pricing)| *pricing)
+        })
+    }
+
+    /// Estimated cost of `usage` on `model`; 0 for models with no known price.
+    pub fn estimate_cost(&self, model: &str, usage: &TokenUsage) -> f64 {
+        self.pricing_for(model)
+            .map_or(0.0, |pricing| pricing.cost(usage))
+    }
+
+    /// Estimated cost of a single entry, for entries that record usage.
+    pub fn entry_cost(&self, entry: &LogEntry) -> Option<f64> {
+        let usage = TokenUsage::from_entry(entry)?;
+        Some(self.estimate_cost(entry.model().unwrap_or(UNKNOWN_MODEL), &usage))
+    }
+
+    /// Estimated cost of token usage broken down by model.
+    pub fn total_cost(&self, usage_by_model: &BTreeMap<String, TokenUsage>) -> f64 {
+        usage_by_model
+            .iter()
+            .map(|(model, usage)| self.estimate_cost(model, usage))
+            .sum()
+    }
+}
+
+/// Estimated cost of `usage` on `model` with the built-in prices.
+pub fn estimate_cost(model: &str, usage: &TokenUsage) -> f64 {
+    PricingTable::default().estimate_cost(model, usage)
+}
@@ -0,0 +1,55 @@
+// ABOUTME: User-defined tool handler specs loaded from a TOML or JSON config file
+// ABOUTME: Lets in-house MCP tools render with an icon and labelled fields instead of raw JSON
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolConfig {
+    #[serde(default)]
+    pub tools: HashMap<String, ToolSpec>,
+}
+
+/// How a configured tool should be rendered.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolSpec {
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub fields: Vec<FieldSpec>,
+    #[serde(default)]
+    pub output: OutputStyle,
+}
+
+/// An input field to display, looked up by key in the tool_use input.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldSpec {
+    pub key: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStyle {
+    #[default]
+    Code,
+    Blockquote,
+}
+
+impl ToolConfig {
+    /// Load a config file; `.json` files are parsed as JSON, anything else as TOML.
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let content = fs::read_to_string(path)?;
+        let config = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content)?
+        } else {
+            toml::from_str(&content)?
+        };
+        Ok(config)
+    }
+
+    pub fn get(&self, tool_name: &str) -> Option<&ToolSpec> {
+        self.tools.get(tool_name)
+    }
+}
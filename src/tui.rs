@@ -1,8 +1,11 @@
 // ABOUTME: Terminal User Interface for Claude Code log viewer
 // ABOUTME: Provides interactive terminal-based navigation, review, and export capabilities
 
-use crate::{AppState, LogEntry, ProjectSummary, SessionSummary};
-use chrono::Utc;
+use crate::{
+    export::{generate_export, render_message_body, tool_result_text, ExportFormat, ExportOptions},
+    get_tool_icon, is_human_turn, parse_log_entries, resolve_linked_summaries, session_files,
+    tool_error_count, AppState, LogEntry, ProjectSummary, SessionSummary, WatchEvent, ARCHIVE_DIR,
+};
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
@@ -13,50 +16,200 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState, Wrap,
+    },
     Frame, Terminal,
 };
-use std::{fs, io};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tokio::sync::broadcast::{self, error::TryRecvError};
 use tokio::time::{Duration, Instant};
-use walkdir::WalkDir;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum AppMode {
     ProjectList,
     SessionList,
     ConversationView,
+    MessageDetail,
     Export,
 }
 
+/// How often the project and session lists are reloaded by default.
+pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+/// How long to wait for a key press before redrawing by default.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Order of the session list; `s` cycles through these.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortKey {
+    /// Most recently active first.
+    #[default]
+    Activity,
+    /// Most messages first.
+    MessageCount,
+    /// Alphabetical by summary.
+    Name,
+}
+
+impl SortKey {
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Activity => SortKey::MessageCount,
+            SortKey::MessageCount => SortKey::Name,
+            SortKey::Name => SortKey::Activity,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortKey::Activity => "activity",
+            SortKey::MessageCount => "messages",
+            SortKey::Name => "name",
+        }
+    }
+
+    fn sort(self, sessions: &mut [SessionSummary]) {
+        match self {
+            SortKey::Activity => sessions.sort_by_key(|s| {
+                std::cmp::Reverse(s.last_activity.or(s.started_at).unwrap_or(s.timestamp))
+            }),
+            SortKey::MessageCount => sessions.sort_by_key(|s| std::cmp::Reverse(s.message_count)),
+            SortKey::Name => sessions.sort_by_cached_key(|s| s.summary.to_lowercase()),
+        }
+    }
+}
+
+/// Where the user last was, saved between runs so the TUI reopens there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LastViewed {
+    pub project: String,
+    pub session: Option<String>,
+}
+
+impl LastViewed {
+    /// `$XDG_CACHE_HOME/cc-log-viewer/tui-state.json`, falling back to
+    /// `~/.cache`.
+    pub fn default_path() -> Option<PathBuf> {
+        let cache_home = std::env::var_os("XDG_CACHE_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))?;
+        Some(cache_home.join("cc-log-viewer").join("tui-state.json"))
+    }
+
+    /// The saved location, or `None` when there is no readable state file.
+    pub fn load(path: &Path) -> Option<Self> {
+        let content = fs::read_to_string(path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
 #[derive(Debug)]
 pub struct TuiApp {
     app_state: AppState,
     pub mode: AppMode,
     pub projects: Vec<ProjectSummary>,
+    /// Sessions shown in the list, i.e. `all_sessions` narrowed by `session_filter`.
     pub sessions: Vec<SessionSummary>,
+    all_sessions: Vec<SessionSummary>,
     pub conversation: Vec<LogEntry>,
-    selected_project: Option<usize>,
+    pub selected_project: Option<usize>,
     selected_session: Option<usize>,
     selected_message: Option<usize>,
     project_list_state: ListState,
     session_list_state: ListState,
     message_list_state: ListState,
-    scroll_offset: usize,
+    pub scroll_offset: usize,
+    /// Keep the newest message in view as the conversation refreshes.
+    pub follow: bool,
+    detail_scroll: u16,
+    detail_line_count: u16,
+    detail_page_height: u16,
+    detail_return_offset: usize,
+    conversation_page_height: usize,
+    conversation_scrollbar: ScrollbarState,
+    detail_scrollbar: ScrollbarState,
+    search_input: Option<String>,
+    pub search_query: Option<String>,
+    /// Indices of the conversation entries matching `search_query`, kept up
+    /// to date as it changes so drawing doesn't re-test every visible entry.
+    search_matches: Vec<usize>,
+    /// Substring the session list is narrowed to, matched against summaries and ids.
+    pub session_filter: Option<String>,
+    editing_session_filter: bool,
+    pub session_sort: SortKey,
+    /// Flip `session_sort`, e.g. oldest or fewest messages first.
+    pub session_sort_reversed: bool,
+    pub export_format: ExportFormat,
+    pub export_path: Option<PathBuf>,
+    export_path_input: Option<String>,
+    pub show_help: bool,
+    /// Session waiting on a yes/no before being archived.
+    pub confirm_archive: Option<String>,
     pub status_message: String,
+    /// A conversation is being read; shown instead of the empty-session hint.
+    pub loading: bool,
+    /// File the last viewed project and session are kept in.
+    state_file: Option<PathBuf>,
+    /// Location to reopen once the projects have been loaded.
+    pending_restore: Option<LastViewed>,
     should_quit: bool,
+    /// Time between periodic list refreshes; `None` leaves it to `r`.
+    pub refresh_interval: Option<Duration>,
+    pub poll_interval: Duration,
     last_update: Instant,
+    watch_rx: broadcast::Receiver<WatchEvent>,
+    clipboard: Clipboard,
+}
+
+/// Lazily opened system clipboard. It is kept open for the app's lifetime
+/// because on X11 the copied text is only served while the handle lives.
+#[derive(Default)]
+struct Clipboard(Option<arboard::Clipboard>);
+
+impl std::fmt::Debug for Clipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Clipboard").field(&self.0.is_some()).finish()
+    }
+}
+
+impl Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), arboard::Error> {
+        if self.0.is_none() {
+            self.0 = Some(arboard::Clipboard::new()?);
+        }
+        match self.0.as_mut() {
+            Some(clipboard) => clipboard.set_text(text),
+            None => Err(arboard::Error::ClipboardNotSupported),
+        }
+    }
 }
 
 impl TuiApp {
     pub fn new(app_state: AppState) -> Self {
         let mut project_list_state = ListState::default();
         project_list_state.select(Some(0));
+        let watch_rx = app_state.watch_manager.subscribe();
 
         Self {
             app_state,
             mode: AppMode::ProjectList,
             projects: Vec::new(),
             sessions: Vec::new(),
+            all_sessions: Vec::new(),
             conversation: Vec::new(),
             selected_project: Some(0),
             selected_session: None,
@@ -65,12 +218,55 @@ impl TuiApp {
             session_list_state: ListState::default(),
             message_list_state: ListState::default(),
             scroll_offset: 0,
+            follow: false,
+            detail_scroll: 0,
+            detail_line_count: 0,
+            detail_page_height: 0,
+            detail_return_offset: 0,
+            conversation_page_height: 0,
+            conversation_scrollbar: ScrollbarState::default(),
+            detail_scrollbar: ScrollbarState::default(),
+            search_input: None,
+            search_query: None,
+            search_matches: Vec::new(),
+            session_filter: None,
+            editing_session_filter: false,
+            session_sort: SortKey::default(),
+            session_sort_reversed: false,
+            export_format: ExportFormat::Markdown,
+            export_path: None,
+            export_path_input: None,
+            show_help: false,
+            confirm_archive: None,
             status_message: "Welcome to Claude Code Log Viewer TUI".to_string(),
+            loading: false,
+            state_file: None,
+            pending_restore: None,
             should_quit: false,
+            refresh_interval: Some(DEFAULT_REFRESH_INTERVAL),
+            poll_interval: DEFAULT_POLL_INTERVAL,
             last_update: Instant::now(),
+            watch_rx,
+            clipboard: Clipboard::default(),
         }
     }
 
+    /// Reload the lists every `refresh` (never when `None`) and wait up to
+    /// `poll` for input between redraws.
+    pub fn with_intervals(mut self, refresh: Option<Duration>, poll: Duration) -> Self {
+        self.refresh_interval = refresh;
+        self.poll_interval = poll;
+        self
+    }
+
+    /// Remember the last opened project and session in `path`, and reopen
+    /// the one saved there on the first `refresh_projects`.
+    pub fn with_state_file(mut self, path: PathBuf) -> Self {
+        self.pending_restore = LastViewed::load(&path);
+        self.state_file = Some(path);
+        self
+    }
+
     pub async fn run(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         // Setup terminal
         enable_raw_mode()?;
@@ -108,67 +304,138 @@ impl TuiApp {
             }
 
             // Handle events with timeout for real-time updates
-            if event::poll(Duration::from_millis(100))? {
+            if event::poll(self.poll_interval)? {
                 if let Event::Key(key) = event::read()? {
                     if key.kind == KeyEventKind::Press {
+                        // Reading a large session takes a moment; draw the
+                        // loading indicator before starting.
+                        if self.reloads_conversation(key.code) {
+                            self.loading = true;
+                            terminal.draw(|f| self.ui(f))?;
+                        }
                         self.handle_key_event(key.code).await?;
+                        self.loading = false;
                     }
                 }
             }
 
-            // Periodic refresh for real-time monitoring
-            if self.last_update.elapsed() > Duration::from_secs(2) {
-                match self.mode {
-                    AppMode::ProjectList => {
-                        self.refresh_projects().await?;
-                    }
-                    AppMode::SessionList => {
-                        if let Some(project_idx) = self.selected_project {
-                            if let Some(project) = self.projects.get(project_idx) {
-                                let project_name = project.name.clone();
-                                self.refresh_sessions(&project_name).await?;
-                            }
-                        }
-                    }
-                    AppMode::ConversationView => {
-                        if let Some(project_idx) = self.selected_project {
-                            if let Some(session_idx) = self.selected_session {
-                                if let Some(project) = self.projects.get(project_idx) {
-                                    if let Some(session) = self.sessions.get(session_idx) {
-                                        let project_name = project.name.clone();
-                                        let session_id = session.id.clone();
-                                        self.refresh_conversation(&project_name, &session_id)
-                                            .await?;
-                                    }
-                                }
-                            }
-                        }
+            // New entries for the open conversation arrive from the watcher
+            self.process_watch_events().await?;
+
+            self.refresh_if_due().await?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `key` (re)reads a conversation from disk.
+    fn reloads_conversation(&self, key: KeyCode) -> bool {
+        let busy = self.search_input.is_some()
+            || self.editing_session_filter
+            || self.export_path_input.is_some()
+            || self.confirm_archive.is_some()
+            || self.show_help;
+        !busy
+            && matches!(
+                (&self.mode, key),
+                (AppMode::SessionList, KeyCode::Enter)
+                    | (AppMode::ConversationView, KeyCode::Char('r'))
+            )
+    }
+
+    /// Periodic refresh of the project and session lists, once
+    /// `refresh_interval` has passed since the last one.
+    pub async fn refresh_if_due(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(interval) = self.refresh_interval else {
+            return Ok(());
+        };
+        if self.last_update.elapsed() < interval {
+            return Ok(());
+        }
+
+        match self.mode {
+            AppMode::ProjectList => {
+                self.refresh_projects().await?;
+            }
+            AppMode::SessionList => {
+                if let Some(project_idx) = self.selected_project {
+                    if let Some(project) = self.projects.get(project_idx) {
+                        let project_name = project.name.clone();
+                        self.refresh_sessions(&project_name).await?;
                     }
-                    _ => {}
                 }
-                self.last_update = Instant::now();
             }
+            _ => {}
         }
-
+        self.last_update = Instant::now();
         Ok(())
     }
 
-    async fn handle_key_event(&mut self, key: KeyCode) -> Result<(), Box<dyn std::error::Error>> {
+    pub async fn handle_key_event(
+        &mut self,
+        key: KeyCode,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        if self.search_input.is_some() {
+            self.handle_search_input(key);
+            return Ok(());
+        }
+        if self.editing_session_filter {
+            self.handle_session_filter_input(key);
+            return Ok(());
+        }
+        if self.export_path_input.is_some() {
+            self.handle_export_path_input(key);
+            return Ok(());
+        }
+        if self.confirm_archive.is_some() {
+            match key {
+                KeyCode::Char('y') | KeyCode::Enter => self.archive_pending_session().await?,
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.confirm_archive = None;
+                    self.status_message = "Archive cancelled".to_string();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+        if self.show_help {
+            // Swallow everything else so the overlay never triggers navigation
+            if matches!(key, KeyCode::Char('?') | KeyCode::Esc) {
+                self.show_help = false;
+            }
+            return Ok(());
+        }
+
         match key {
             KeyCode::Char('q') => {
                 self.should_quit = true;
             }
+            KeyCode::Char('?') => {
+                self.show_help = true;
+            }
             KeyCode::Esc => match self.mode {
+                AppMode::SessionList if self.session_filter.is_some() => {
+                    self.session_filter = None;
+                    self.apply_session_filter();
+                }
                 AppMode::SessionList => {
                     self.mode = AppMode::ProjectList;
                     self.selected_session = None;
                     self.sessions.clear();
+                    self.all_sessions.clear();
                 }
                 AppMode::ConversationView => {
                     self.mode = AppMode::SessionList;
                     self.selected_message = None;
                     self.conversation.clear();
                     self.scroll_offset = 0;
+                    self.search_query = None;
+                    self.search_matches.clear();
+                }
+                AppMode::MessageDetail => {
+                    self.mode = AppMode::ConversationView;
+                    self.scroll_offset = self.detail_return_offset;
+                    self.detail_scroll = 0;
                 }
                 AppMode::Export => {
                     self.mode = AppMode::ConversationView;
@@ -184,6 +451,7 @@ impl TuiApp {
                             self.mode = AppMode::SessionList;
                             self.selected_session = Some(0);
                             self.session_list_state.select(Some(0));
+                            self.save_last_viewed();
                         }
                     }
                 }
@@ -194,19 +462,31 @@ impl TuiApp {
                                 if let Some(session) = self.sessions.get(session_idx) {
                                     let project_name = project.name.clone();
                                     let session_id = session.id.clone();
+                                    self.scroll_offset = 0;
                                     self.refresh_conversation(&project_name, &session_id)
                                         .await?;
                                     self.mode = AppMode::ConversationView;
                                     self.selected_message = Some(0);
                                     self.message_list_state.select(Some(0));
+                                    self.save_last_viewed();
                                 }
                             }
                         }
                     }
                 }
+                AppMode::ConversationView if self.scroll_offset < self.conversation.len() => {
+                    self.selected_message = Some(self.scroll_offset);
+                    self.detail_return_offset = self.scroll_offset;
+                    self.detail_scroll = 0;
+                    self.mode = AppMode::MessageDetail;
+                }
+                AppMode::Export => {
+                    self.export_conversation().await?;
+                    self.mode = AppMode::ConversationView;
+                }
                 _ => {}
             },
-            KeyCode::Up => match self.mode {
+            KeyCode::Up | KeyCode::Char('k') => match self.mode {
                 AppMode::ProjectList => {
                     if let Some(selected) = self.selected_project {
                         if selected > 0 {
@@ -223,14 +503,16 @@ impl TuiApp {
                         }
                     }
                 }
-                AppMode::ConversationView => {
-                    if self.scroll_offset > 0 {
-                        self.scroll_offset -= 1;
-                    }
+                AppMode::ConversationView if self.scroll_offset > 0 => {
+                    self.scroll_offset -= 1;
+                    self.follow = false;
+                }
+                AppMode::MessageDetail => {
+                    self.detail_scroll = self.detail_scroll.saturating_sub(1);
                 }
                 _ => {}
             },
-            KeyCode::Down => match self.mode {
+            KeyCode::Down | KeyCode::Char('j') => match self.mode {
                 AppMode::ProjectList => {
                     if let Some(selected) = self.selected_project {
                         if selected < self.projects.len().saturating_sub(1) {
@@ -247,18 +529,85 @@ impl TuiApp {
                         }
                     }
                 }
-                AppMode::ConversationView => {
-                    if self.scroll_offset < self.conversation.len().saturating_sub(1) {
-                        self.scroll_offset += 1;
-                    }
+                AppMode::ConversationView
+                    if self.scroll_offset < self.conversation.len().saturating_sub(1) =>
+                {
+                    self.scroll_offset += 1;
+                }
+                AppMode::MessageDetail
+                    if self.detail_scroll < self.detail_line_count.saturating_sub(1) =>
+                {
+                    self.detail_scroll += 1;
                 }
                 _ => {}
             },
-            KeyCode::Char('e') => {
-                if self.mode == AppMode::ConversationView {
-                    self.mode = AppMode::Export;
+            KeyCode::Char('g') | KeyCode::Home => self.jump_to_start(),
+            KeyCode::Char('G') | KeyCode::End => self.jump_to_end(),
+            KeyCode::PageUp if self.mode == AppMode::ConversationView => {
+                self.scroll_offset = self
+                    .scroll_offset
+                    .saturating_sub(self.conversation_page_height.max(1));
+                self.follow = false;
+            }
+            KeyCode::PageDown if self.mode == AppMode::ConversationView => {
+                self.scroll_offset = (self.scroll_offset + self.conversation_page_height.max(1))
+                    .min(self.conversation.len().saturating_sub(1));
+            }
+            KeyCode::PageUp if self.mode == AppMode::MessageDetail => {
+                self.detail_scroll = self
+                    .detail_scroll
+                    .saturating_sub(self.detail_page_height.max(1));
+            }
+            KeyCode::PageDown if self.mode == AppMode::MessageDetail => {
+                self.detail_scroll = (self.detail_scroll + self.detail_page_height.max(1))
+                    .min(self.detail_line_count.saturating_sub(1));
+            }
+            KeyCode::Char('e') if self.mode == AppMode::ConversationView => {
+                self.mode = AppMode::Export;
+            }
+            KeyCode::Char('s') if self.mode == AppMode::SessionList => {
+                self.session_sort = self.session_sort.next();
+                self.sort_sessions();
+            }
+            KeyCode::Char('S') if self.mode == AppMode::SessionList => {
+                self.session_sort_reversed = !self.session_sort_reversed;
+                self.sort_sessions();
+            }
+            KeyCode::Char('d') if self.mode == AppMode::SessionList => {
+                if let (_, Some(session)) = self.current_names() {
+                    self.confirm_archive = Some(session.to_string());
                 }
             }
+            KeyCode::Char('y')
+                if matches!(
+                    self.mode,
+                    AppMode::ConversationView | AppMode::MessageDetail
+                ) =>
+            {
+                self.copy_current_entry();
+            }
+            KeyCode::Char('f') if self.mode == AppMode::ConversationView => {
+                self.follow = !self.follow;
+                if self.follow {
+                    self.scroll_offset = self.conversation.len().saturating_sub(1);
+                    self.status_message = "Following newest messages".to_string();
+                } else {
+                    self.status_message = "Stopped following".to_string();
+                }
+            }
+            KeyCode::Char('/') if self.mode == AppMode::ConversationView => {
+                self.search_input = Some(String::new());
+            }
+            KeyCode::Char('/') if self.mode == AppMode::SessionList => {
+                self.editing_session_filter = true;
+                self.session_filter.get_or_insert_with(String::new);
+            }
+            KeyCode::Char('n') if self.mode == AppMode::ConversationView => {
+                self.jump_to_match(true);
+            }
+            KeyCode::Char('N') if self.mode == AppMode::ConversationView => {
+                self.jump_to_match(false);
+            }
             KeyCode::Char('r') => {
                 // Manual refresh
                 match self.mode {
@@ -293,11 +642,18 @@ impl TuiApp {
                     _ => {}
                 }
             }
-            KeyCode::Char('s') => {
-                if self.mode == AppMode::Export {
-                    self.export_conversation().await?;
-                    self.mode = AppMode::ConversationView;
-                }
+            KeyCode::Char('s') if self.mode == AppMode::Export => {
+                self.export_conversation().await?;
+                self.mode = AppMode::ConversationView;
+            }
+            KeyCode::Left if self.mode == AppMode::Export => {
+                self.cycle_export_format(false);
+            }
+            KeyCode::Right if self.mode == AppMode::Export => {
+                self.cycle_export_format(true);
+            }
+            KeyCode::Char('p') if self.mode == AppMode::Export => {
+                self.export_path_input = Some(self.export_target().display().to_string());
             }
             _ => {}
         }
@@ -305,31 +661,351 @@ impl TuiApp {
         Ok(())
     }
 
-    fn ui(&mut self, f: &mut Frame) {
+    fn jump_to_start(&mut self) {
+        match self.mode {
+            AppMode::ProjectList if !self.projects.is_empty() => {
+                self.selected_project = Some(0);
+                self.project_list_state.select(Some(0));
+            }
+            AppMode::SessionList if !self.sessions.is_empty() => {
+                self.selected_session = Some(0);
+                self.session_list_state.select(Some(0));
+            }
+            AppMode::ConversationView => {
+                self.scroll_offset = 0;
+                self.follow = false;
+            }
+            AppMode::MessageDetail => {
+                self.detail_scroll = 0;
+            }
+            _ => {}
+        }
+    }
+
+    fn jump_to_end(&mut self) {
+        match self.mode {
+            AppMode::ProjectList if !self.projects.is_empty() => {
+                let last = self.projects.len() - 1;
+                self.selected_project = Some(last);
+                self.project_list_state.select(Some(last));
+            }
+            AppMode::SessionList if !self.sessions.is_empty() => {
+                let last = self.sessions.len() - 1;
+                self.selected_session = Some(last);
+                self.session_list_state.select(Some(last));
+            }
+            AppMode::ConversationView => {
+                self.scroll_offset = self.conversation.len().saturating_sub(1);
+            }
+            AppMode::MessageDetail => {
+                self.detail_scroll = self.detail_line_count.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_search_input(&mut self, key: KeyCode) {
+        let Some(input) = self.search_input.as_mut() else {
+            return;
+        };
+
+        match key {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let query = input.trim().to_string();
+                self.search_input = None;
+                if query.is_empty() {
+                    self.search_query = None;
+                    self.search_matches.clear();
+                    self.status_message = "Search cleared".to_string();
+                } else {
+                    self.search_query = Some(query);
+                    self.update_search_matches();
+                    // Start from the current entry so a match at the top is found first
+                    self.jump_to_match_from(self.scroll_offset, true, true);
+                }
+            }
+            KeyCode::Esc => {
+                self.search_input = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// Edit the session filter, narrowing the list as each key is typed.
+    /// Enter keeps the filter; Esc drops it and restores the full list.
+    fn handle_session_filter_input(&mut self, key: KeyCode) {
+        let Some(filter) = self.session_filter.as_mut() else {
+            self.editing_session_filter = false;
+            return;
+        };
+
+        match key {
+            KeyCode::Char(c) => filter.push(c),
+            KeyCode::Backspace => {
+                filter.pop();
+            }
+            KeyCode::Enter => {
+                self.editing_session_filter = false;
+                if filter.trim().is_empty() {
+                    self.session_filter = None;
+                }
+            }
+            KeyCode::Esc => {
+                self.editing_session_filter = false;
+                self.session_filter = None;
+            }
+            _ => return,
+        }
+        self.apply_session_filter();
+    }
+
+    /// Reorder the session list by `session_sort`, keeping the selection.
+    fn sort_sessions(&mut self) {
+        self.order_all_sessions();
+        self.apply_session_filter();
+        self.status_message = format!("Sorted by {}", self.sort_label());
+    }
+
+    fn order_all_sessions(&mut self) {
+        self.session_sort.sort(&mut self.all_sessions);
+        if self.session_sort_reversed {
+            self.all_sessions.reverse();
+        }
+    }
+
+    fn sort_label(&self) -> String {
+        format!(
+            "{}{}",
+            self.session_sort.label(),
+            if self.session_sort_reversed {
+                ", reversed"
+            } else {
+                ""
+            }
+        )
+    }
+
+    /// Rebuild `sessions` from `all_sessions` and the current filter, keeping
+    /// the selected session highlighted when it is still visible.
+    fn apply_session_filter(&mut self) {
+        let selected_id = self
+            .selected_session
+            .and_then(|i| self.sessions.get(i))
+            .map(|s| s.id.clone());
+
+        let needle = self
+            .session_filter
+            .as_deref()
+            .map(|f| f.trim().to_lowercase())
+            .filter(|f| !f.is_empty());
+        self.sessions = match &needle {
+            Some(needle) => self
+                .all_sessions
+                .iter()
+                .filter(|s| {
+                    s.summary.to_lowercase().contains(needle)
+                        || s.id.to_lowercase().contains(needle)
+                })
+                .cloned()
+                .collect(),
+            None => self.all_sessions.clone(),
+        };
+
+        self.selected_session = if self.sessions.is_empty() {
+            None
+        } else {
+            let kept = selected_id.and_then(|id| self.sessions.iter().position(|s| s.id == id));
+            Some(kept.unwrap_or_else(|| {
+                self.selected_session
+                    .unwrap_or(0)
+                    .min(self.sessions.len() - 1)
+            }))
+        };
+        self.session_list_state.select(self.selected_session);
+
+        if needle.is_some() {
+            self.status_message = format!(
+                "{} of {} sessions match",
+                self.sessions.len(),
+                self.all_sessions.len()
+            );
+        }
+    }
+
+    /// Move `scroll_offset` to the next (or previous) entry matching the
+    /// current search query, wrapping around the conversation.
+    fn jump_to_match(&mut self, forward: bool) {
+        self.jump_to_match_from(self.scroll_offset, forward, false);
+    }
+
+    /// Scroll to the next match after `start`, or the previous one before
+    /// it, wrapping around. `inclusive` lets a match at `start` itself count.
+    fn jump_to_match_from(&mut self, start: usize, forward: bool, inclusive: bool) {
+        let Some(query) = self.search_query.as_ref() else {
+            self.status_message = "No search query (press / to search)".to_string();
+            return;
+        };
+
+        let matches = &self.search_matches;
+        if matches.is_empty() {
+            self.status_message = format!("No matches for '{}'", query);
+            return;
+        }
+
+        let position = if forward {
+            matches
+                .iter()
+                .position(|&i| i > start || (inclusive && i == start))
+                .unwrap_or(0)
+        } else {
+            matches
+                .iter()
+                .rposition(|&i| i < start || (inclusive && i == start))
+                .unwrap_or(matches.len() - 1)
+        };
+
+        self.scroll_offset = matches[position];
+        self.status_message = format!(
+            "Match {} of {} for '{}'",
+            position + 1,
+            matches.len(),
+            query
+        );
+    }
+
+    /// Recompute `search_matches` for the current query and conversation.
+    fn update_search_matches(&mut self) {
+        self.search_matches = match self.search_query.as_ref() {
+            Some(query) => self
+                .conversation
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| entry_matches(entry, query))
+                .map(|(i, _)| i)
+                .collect(),
+            None => Vec::new(),
+        };
+    }
+
+    /// Draw the current mode, status bar and any overlays into the frame.
+    pub fn ui(&mut self, f: &mut Frame) {
         let chunks = Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .constraints([
+                Constraint::Length(1),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
             .split(f.area());
 
+        self.conversation_scrollbar = self
+            .conversation_scrollbar
+            .content_length(self.conversation.len())
+            .position(self.scroll_offset);
+
+        let breadcrumb = Paragraph::new(self.breadcrumb())
+            .style(Style::default().fg(Color::White).bg(Color::DarkGray));
+        f.render_widget(breadcrumb, chunks[0]);
+
         match self.mode {
             AppMode::ProjectList => {
-                self.render_project_list(f, chunks[0]);
+                self.render_project_list(f, chunks[1]);
             }
             AppMode::SessionList => {
-                self.render_session_list(f, chunks[0]);
+                self.render_session_list(f, chunks[1]);
             }
             AppMode::ConversationView => {
-                self.render_conversation(f, chunks[0]);
+                self.render_conversation(f, chunks[1]);
+            }
+            AppMode::MessageDetail => {
+                self.render_message_detail(f, chunks[1]);
             }
             AppMode::Export => {
-                self.render_export_dialog(f, chunks[0]);
+                self.render_export_dialog(f, chunks[1]);
             }
         }
 
-        self.render_status_bar(f, chunks[1]);
+        self.render_status_bar(f, chunks[2]);
+
+        if self.show_help {
+            self.render_help(f, f.area());
+        }
+        if let Some(session) = &self.confirm_archive {
+            render_archive_confirmation(f, f.area(), session);
+        }
+    }
+
+    /// Where the user is, e.g. `my-project / Fix the parser / message 3 of 40`,
+    /// followed by the current mode.
+    pub fn breadcrumb(&self) -> String {
+        let position = |noun: &str, index: Option<usize>, len: usize| match index {
+            _ if len == 0 => format!("no {}s", noun),
+            Some(i) => format!("{} {} of {}", noun, i + 1, len),
+            None => format!("{} {}s", len, noun),
+        };
+        let project = self.current_names().0.map(str::to_string);
+        let session = self
+            .selected_session
+            .and_then(|i| self.sessions.get(i))
+            .map(|s| s.summary.clone());
+
+        let mut parts: Vec<String> = match self.mode {
+            AppMode::ProjectList => Vec::new(),
+            AppMode::SessionList => project.into_iter().collect(),
+            _ => project.into_iter().chain(session).collect(),
+        };
+        parts.push(match self.mode {
+            AppMode::ProjectList => position("project", self.selected_project, self.projects.len()),
+            AppMode::SessionList => position("session", self.selected_session, self.sessions.len()),
+            _ => position(
+                "message",
+                Some(self.current_message_index()),
+                self.conversation.len(),
+            ),
+        });
+        let mode = match self.mode {
+            AppMode::ProjectList => "Projects",
+            AppMode::SessionList => "Sessions",
+            AppMode::ConversationView => "Conversation",
+            AppMode::MessageDetail => "Message detail",
+            AppMode::Export => "Export",
+        };
+
+        format!(" {}  [{}]", parts.join(" / "), mode)
+    }
+
+    /// The conversation entry being looked at: the detailed one in
+    /// `MessageDetail`, otherwise the one at the top of the view.
+    fn current_message_index(&self) -> usize {
+        match self.mode {
+            AppMode::MessageDetail => self.selected_message.unwrap_or(self.scroll_offset),
+            _ => self.scroll_offset,
+        }
     }
 
     fn render_project_list(&mut self, f: &mut Frame, area: Rect) {
+        if self.projects.is_empty() {
+            // The status message says whether the directory is missing or
+            // just empty; repeat it where a first-time user is looking.
+            let text = Text::from(vec![
+                Line::from(""),
+                Line::from(self.status_message.clone()),
+                Line::from(""),
+                Line::from("Projects appear here once Claude Code has logged a session."),
+                Line::from("Press r to rescan."),
+            ]);
+            let placeholder = Paragraph::new(text)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL).title("Projects"))
+                .wrap(Wrap { trim: true });
+            f.render_widget(placeholder, area);
+            return;
+        }
+
         let items: Vec<ListItem> = self
             .projects
             .iter()
@@ -349,7 +1025,7 @@ impl TuiApp {
                     .unwrap_or_else(|| "No activity".to_string());
 
                 ListItem::new(vec![Line::from(vec![
-                    Span::styled(format!("📁 {}", project.name), style),
+                    Span::styled(format!("📁 {}", project.display_path), style),
                     Span::raw(format!(
                         " ({} sessions, last: {})",
                         project.session_count, activity
@@ -396,10 +1072,21 @@ impl TuiApp {
 
         let title = if let Some(project_idx) = self.selected_project {
             if let Some(project) = self.projects.get(project_idx) {
-                format!(
-                    "Sessions in {} (↑/↓ to navigate, Enter to select, Esc to go back)",
-                    project.name
-                )
+                match self.session_filter.as_deref() {
+                    Some(filter) if !filter.is_empty() => format!(
+                        "Sessions in {} matching \"{}\" ({} of {}, Esc to clear) [by {}]",
+                        project.name,
+                        filter,
+                        self.sessions.len(),
+                        self.all_sessions.len(),
+                        self.sort_label()
+                    ),
+                    _ => format!(
+                        "Sessions in {} (↑/↓ to navigate, Enter to select, Esc to go back) [by {}]",
+                        project.name,
+                        self.sort_label()
+                    ),
+                }
             } else {
                 "Sessions".to_string()
             }
@@ -420,7 +1107,7 @@ impl TuiApp {
                 if let Some(project) = self.projects.get(project_idx) {
                     if let Some(session) = self.sessions.get(session_idx) {
                         format!(
-                            "Conversation: {} / {} (↑/↓ to scroll, e to export, Esc to go back)",
+                            "Conversation: {} / {} (↑/↓ to scroll, Enter for details, e to export, Esc to go back)",
                             project.name, session.summary
                         )
                     } else {
@@ -436,12 +1123,36 @@ impl TuiApp {
             "Conversation".to_string()
         };
 
+        self.conversation_page_height = area.height.saturating_sub(2) as usize;
+
+        if self.conversation.is_empty() {
+            let hint = if self.loading {
+                "⏳ Loading…"
+            } else {
+                "No messages yet — press r to refresh"
+            };
+            let padding = area.height.saturating_sub(3) / 2;
+            let mut lines = vec![Line::from(""); padding as usize];
+            lines.push(Line::from(Span::styled(
+                hint,
+                Style::default().fg(Color::DarkGray),
+            )));
+            let placeholder = Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL).title(title));
+            f.render_widget(placeholder, area);
+            return;
+        }
+
         let visible_messages = self
             .conversation
             .iter()
+            .enumerate()
             .skip(self.scroll_offset)
-            .take(area.height.saturating_sub(2) as usize)
-            .map(|entry| {
+            .take(self.conversation_page_height)
+            .map(|(i, entry)| {
+                let is_match = self.search_matches.binary_search(&i).is_ok();
+
                 let role = entry
                     .message
                     .as_ref()
@@ -453,25 +1164,21 @@ impl TuiApp {
                     .message
                     .as_ref()
                     .and_then(|m| m.get("content"))
-                    .and_then(|c| {
-                        if c.is_string() {
-                            c.as_str().map(|s| s.to_string())
-                        } else {
-                            Some(format!("{}", c))
-                        }
-                    })
+                    .map(content_summary)
                     .unwrap_or_else(|| "No content".to_string());
 
                 let icon = match role {
-                    "user" => "👤",
+                    "user" if is_human_turn(entry) => "👤",
+                    "user" => "📋",
                     "assistant" => "🤖",
                     _ => "ℹ️",
                 };
 
-                let style = match role {
-                    "user" => Style::default().fg(Color::Cyan),
-                    "assistant" => Style::default().fg(Color::Green),
-                    _ => Style::default().fg(Color::Gray),
+                let style = Style::default().fg(entry_color(entry, role, &content));
+                let style = if is_match {
+                    style.bg(Color::Yellow).fg(Color::Black)
+                } else {
+                    style
                 };
 
                 let timestamp = entry
@@ -482,7 +1189,7 @@ impl TuiApp {
                 Line::from(vec![
                     Span::styled(format!("{} [{}] ", icon, timestamp), style),
                     Span::styled(content.chars().take(120).collect::<String>(), style),
-                    if content.len() > 120 {
+                    if content.chars().count() > 120 {
                         Span::raw("...")
                     } else {
                         Span::raw("")
@@ -496,11 +1203,79 @@ impl TuiApp {
             .block(Block::default().borders(Borders::ALL).title(title))
             .wrap(Wrap { trim: true });
 
-        f.render_widget(paragraph, area);
+        let (content_area, scrollbar_area) = split_scrollbar(area);
+        f.render_widget(paragraph, content_area);
+        f.render_stateful_widget(
+            vertical_scrollbar(),
+            scrollbar_area,
+            &mut self.conversation_scrollbar,
+        );
+    }
+
+    fn render_message_detail(&mut self, f: &mut Frame, area: Rect) {
+        let entry = self
+            .selected_message
+            .and_then(|idx| self.conversation.get(idx));
+
+        let (title, text) = match entry {
+            Some(entry) => {
+                let idx = self.selected_message.unwrap_or(0);
+                let role = match entry.model() {
+                    Some(model) => format!("{} ({})", entry_role(entry), model),
+                    None => entry_role(entry).to_string(),
+                };
+                let timestamp = entry
+                    .timestamp
+                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+                    .unwrap_or_else(|| "Unknown".to_string());
+                let cost = self
+                    .app_state
+                    .pricing
+                    .entry_cost(entry)
+                    .map(|cost| format!(" ~${:.4}", cost))
+                    .unwrap_or_default();
+                (
+                    format!(
+                        "Message {} of {}: {} [{}]{} (↑/↓/PgUp/PgDn to scroll, Esc to go back)",
+                        idx + 1,
+                        self.conversation.len(),
+                        role,
+                        timestamp,
+                        cost
+                    ),
+                    Text::from(entry_detail_lines(entry)),
+                )
+            }
+            None => (
+                "Message".to_string(),
+                Text::from("No message selected".to_string()),
+            ),
+        };
+
+        let (content_area, scrollbar_area) = split_scrollbar(area);
+        let block = Block::default().borders(Borders::ALL).title(title);
+        let paragraph = Paragraph::new(text).wrap(Wrap { trim: false });
+
+        // Count rows after wrapping, so scrolling reaches the end of long lines
+        let wrapped_lines = paragraph.line_count(block.inner(content_area).width);
+        self.detail_line_count = wrapped_lines.min(u16::MAX as usize) as u16;
+        self.detail_page_height = area.height.saturating_sub(2);
+        self.detail_scrollbar = self
+            .detail_scrollbar
+            .content_length(self.detail_line_count as usize)
+            .position(self.detail_scroll as usize);
+
+        let paragraph = paragraph.block(block).scroll((self.detail_scroll, 0));
+        f.render_widget(paragraph, content_area);
+        f.render_stateful_widget(
+            vertical_scrollbar(),
+            scrollbar_area,
+            &mut self.detail_scrollbar,
+        );
     }
 
     fn render_export_dialog(&mut self, f: &mut Frame, area: Rect) {
-        let popup_area = centered_rect(60, 20, area);
+        let popup_area = centered_rect(60, 30, area);
 
         f.render_widget(Clear, popup_area);
 
@@ -509,11 +1284,46 @@ impl TuiApp {
             .borders(Borders::ALL)
             .style(Style::default().bg(Color::DarkGray));
 
+        let formats: Vec<Span> = ExportFormat::ALL
+            .iter()
+            .flat_map(|format| {
+                let style = if *format == self.export_format {
+                    Style::default()
+                        .fg(Color::Black)
+                        .bg(Color::Yellow)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                [
+                    Span::styled(format!(" {} ", format.label()), style),
+                    Span::raw(" "),
+                ]
+            })
+            .collect();
+
+        let path_line = match &self.export_path_input {
+            Some(input) => Line::from(vec![
+                Span::raw("Save to: "),
+                Span::styled(
+                    format!("{}_", input),
+                    Style::default().add_modifier(Modifier::UNDERLINED),
+                ),
+            ]),
+            None => Line::from(format!("Save to: {}", self.export_target().display())),
+        };
+
         let text = Text::from(vec![
-            Line::from("Press 's' to save conversation to file"),
-            Line::from("Press Esc to cancel"),
+            Line::from("Format (←/→ to change):"),
+            Line::from(formats),
+            Line::from(""),
+            path_line,
             Line::from(""),
-            Line::from("File will be saved as: conversation_export.txt"),
+            if self.export_path_input.is_some() {
+                Line::from("Enter to confirm path, Esc to keep the previous one")
+            } else {
+                Line::from("Press 's' or Enter to save, 'p' to edit path, Esc to cancel")
+            },
         ]);
 
         let paragraph = Paragraph::new(text)
@@ -524,14 +1334,101 @@ impl TuiApp {
         f.render_widget(paragraph, popup_area);
     }
 
+    fn render_help(&self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 60, area);
+        f.render_widget(Clear, popup_area);
+
+        let key_style = Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD);
+        let section_style = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+
+        let mut lines = Vec::new();
+        for (section, bindings) in [
+            (mode_label(&self.mode), mode_keybindings(&self.mode)),
+            ("Global", GLOBAL_KEYBINDINGS),
+        ] {
+            lines.push(Line::from(Span::styled(section, section_style)));
+            for (keys, description) in bindings {
+                lines.push(Line::from(vec![
+                    Span::styled(format!("  {:<14}", keys), key_style),
+                    Span::raw(*description),
+                ]));
+            }
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from("Press ? or Esc to close"));
+
+        let paragraph = Paragraph::new(Text::from(lines))
+            .block(
+                Block::default()
+                    .title("Keybindings")
+                    .borders(Borders::ALL)
+                    .style(Style::default().bg(Color::DarkGray)),
+            )
+            .wrap(Wrap { trim: false });
+
+        f.render_widget(paragraph, popup_area);
+    }
+
     fn render_status_bar(&mut self, f: &mut Frame, area: Rect) {
+        if let Some(input) = &self.search_input {
+            let search = Paragraph::new(format!("/{}", input))
+                .style(Style::default().fg(Color::White).bg(Color::Blue))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .title("Search (Enter to find, Esc to cancel)"),
+                );
+            f.render_widget(search, area);
+            return;
+        }
+        if self.editing_session_filter {
+            let filter =
+                Paragraph::new(format!("/{}", self.session_filter.as_deref().unwrap_or("")))
+                    .style(Style::default().fg(Color::White).bg(Color::Blue))
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title("Filter sessions (Enter to keep, Esc to clear)"),
+                    );
+            f.render_widget(filter, area);
+            return;
+        }
+
+        if self.loading {
+            let loading = Paragraph::new("⏳ Loading conversation…")
+                .style(Style::default().fg(Color::White).bg(Color::Blue))
+                .alignment(Alignment::Center)
+                .block(Block::default().borders(Borders::ALL));
+            f.render_widget(loading, area);
+            return;
+        }
+
         let status_text = match self.mode {
-            AppMode::ProjectList => format!("{} | q: Quit, r: Refresh", self.status_message),
-            AppMode::SessionList => format!("{} | Esc: Back, r: Refresh", self.status_message),
+            AppMode::ProjectList => {
+                format!("{} | q: Quit, r: Refresh, ?: Help", self.status_message)
+            }
+            AppMode::SessionList => format!(
+                "{} | Esc: Back, /: Filter, s/S: Sort, d: Archive, r: Refresh",
+                self.status_message
+            ),
             AppMode::ConversationView => {
-                format!("{} | Esc: Back, e: Export, r: Refresh", self.status_message)
+                format!(
+                    "{}{} | Esc: Back, Enter: Details, /: Search, n/N: Next/Prev, f: Follow, e: Export, r: Refresh",
+                    if self.follow { "[FOLLOW] " } else { "" },
+                    self.status_message
+                )
+            }
+            AppMode::MessageDetail => {
+                format!("{} | Esc: Back, PgUp/PgDn: Page", self.status_message)
             }
-            AppMode::Export => format!("{} | s: Save, Esc: Cancel", self.status_message),
+            AppMode::Export => format!(
+                "{} | ←/→: Format, p: Path, s: Save, Esc: Cancel",
+                self.status_message
+            ),
         };
 
         let status = Paragraph::new(status_text)
@@ -543,6 +1440,17 @@ impl TuiApp {
     }
 
     pub async fn refresh_projects(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let projects_dir = &self.app_state.projects_dir;
+        if !projects_dir.is_dir() {
+            self.status_message = format!(
+                "Projects directory {} does not exist",
+                projects_dir.display()
+            );
+            self.projects.clear();
+            self.selected_project = None;
+            self.project_list_state.select(None);
+            return Ok(());
+        }
         if let Err(e) = self.app_state.refresh_cache().await {
             self.status_message = format!("Failed to refresh projects: {}", e);
             return Ok(());
@@ -552,17 +1460,77 @@ impl TuiApp {
         self.projects = projects.clone();
 
         if self.projects.is_empty() {
-            self.status_message = "No projects found".to_string();
+            self.status_message = format!(
+                "No Claude Code projects found in {}",
+                self.app_state.projects_dir.display()
+            );
             self.selected_project = None;
             self.project_list_state.select(None);
         } else if self.selected_project.is_none() {
             self.selected_project = Some(0);
             self.project_list_state.select(Some(0));
         }
+        drop(projects);
+
+        if let Some(last) = self.pending_restore.take() {
+            self.restore_last_viewed(last).await?;
+        }
 
         Ok(())
     }
 
+    /// Reopen a saved project and session, going only as far as still
+    /// exists: a missing session leaves the session list open, a missing
+    /// project the project list.
+    async fn restore_last_viewed(
+        &mut self,
+        last: LastViewed,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(project_idx) = self.projects.iter().position(|p| p.name == last.project) else {
+            return Ok(());
+        };
+        self.selected_project = Some(project_idx);
+        self.project_list_state.select(Some(project_idx));
+        self.refresh_sessions(&last.project).await?;
+        self.mode = AppMode::SessionList;
+
+        let Some(session_idx) = last
+            .session
+            .and_then(|id| self.sessions.iter().position(|s| s.id == id))
+        else {
+            return Ok(());
+        };
+        self.selected_session = Some(session_idx);
+        self.session_list_state.select(Some(session_idx));
+        let session_id = self.sessions[session_idx].id.clone();
+        self.refresh_conversation(&last.project, &session_id)
+            .await?;
+        self.mode = AppMode::ConversationView;
+        self.selected_message = Some(0);
+        self.message_list_state.select(Some(0));
+        self.scroll_offset = 0;
+        self.status_message = format!("Reopened {}/{}", last.project, session_id);
+        Ok(())
+    }
+
+    fn save_last_viewed(&mut self) {
+        let Some(path) = &self.state_file else {
+            return;
+        };
+        let (Some(project), session) = self.current_names() else {
+            return;
+        };
+        let last = LastViewed {
+            project: project.to_string(),
+            session: (self.mode != AppMode::SessionList)
+                .then(|| session.map(str::to_string))
+                .flatten(),
+        };
+        if let Err(e) = last.save(path) {
+            self.status_message = format!("Could not save {}: {}", path.display(), e);
+        }
+    }
+
     pub async fn refresh_sessions(
         &mut self,
         project_name: &str,
@@ -576,58 +1544,23 @@ impl TuiApp {
 
         let mut sessions = Vec::new();
 
-        for entry in WalkDir::new(&project_path).min_depth(1).max_depth(1) {
-            let entry = entry?;
-            if entry.file_type().is_file()
-                && entry.path().extension().is_some_and(|ext| ext == "jsonl")
+        for file in session_files(&project_path, self.app_state.scan_depth) {
+            let file = file?;
+            if let Some(session) = self
+                .app_state
+                .cached_session_summary(project_name, &file.path)
             {
-                let session_id = entry
-                    .path()
-                    .file_stem()
-                    .unwrap_or_default()
-                    .to_string_lossy()
-                    .to_string();
-
-                if let Ok(content) = fs::read_to_string(entry.path()) {
-                    let mut summary = "Untitled Session".to_string();
-                    let mut timestamp = Utc::now();
-                    let message_count = content.lines().count();
-
-                    for line in content.lines().take(10) {
-                        if let Ok(entry) = serde_json::from_str::<LogEntry>(line) {
-                            if entry.entry_type.as_deref() == Some("summary") {
-                                if let Some(s) = entry.summary {
-                                    summary = s;
-                                }
-                            }
-                            if let Some(ts) = entry.timestamp {
-                                timestamp = ts;
-                                break;
-                            }
-                        }
-                    }
-
-                    sessions.push(SessionSummary {
-                        id: session_id,
-                        summary,
-                        timestamp,
-                        message_count,
-                        project_name: project_name.to_string(),
-                    });
-                }
+                sessions.push(session);
             }
         }
 
-        sessions.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
-        self.sessions = sessions;
+        resolve_linked_summaries(&mut sessions);
+        self.all_sessions = sessions;
+        self.order_all_sessions();
+        self.apply_session_filter();
 
-        if self.sessions.is_empty() {
+        if self.all_sessions.is_empty() {
             self.status_message = "No sessions found in project".to_string();
-            self.selected_session = None;
-            self.session_list_state.select(None);
-        } else if self.selected_session.is_none() {
-            self.selected_session = Some(0);
-            self.session_list_state.select(Some(0));
         }
 
         Ok(())
@@ -650,70 +1583,680 @@ impl TuiApp {
         }
 
         let content = fs::read_to_string(&log_path)?;
-        let mut entries = Vec::new();
+        self.conversation = parse_log_entries(&content);
+        self.update_search_matches();
+
+        let last = self.conversation.len().saturating_sub(1);
+        self.scroll_offset = if self.follow {
+            last
+        } else {
+            self.scroll_offset.min(last)
+        };
+
+        Ok(())
+    }
+
+    /// Move the session awaiting confirmation into the project's
+    /// `ARCHIVE_DIR`, which is never scanned, then reload the list.
+    async fn archive_pending_session(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let Some(session_id) = self.confirm_archive.take() else {
+            return Ok(());
+        };
+        let Some(project_name) = self.current_names().0.map(str::to_string) else {
+            return Ok(());
+        };
+
+        let project_dir = self.app_state.projects_dir.join(&project_name);
+        let file_name = format!("{}.jsonl", session_id);
+        let archived = project_dir.join(ARCHIVE_DIR).join(&file_name);
+        let result = archived
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::rename(project_dir.join(&file_name), &archived));
+        if let Err(e) = result {
+            self.status_message = format!("Failed to archive {}: {}", session_id, e);
+            return Ok(());
+        }
+
+        self.refresh_sessions(&project_name).await?;
+        if let Some(selected) = self.selected_session {
+            let clamped = selected.min(self.sessions.len().saturating_sub(1));
+            self.selected_session = (!self.sessions.is_empty()).then_some(clamped);
+            self.session_list_state.select(self.selected_session);
+        }
+        self.status_message = format!("Archived {} to {}/", session_id, ARCHIVE_DIR);
+        Ok(())
+    }
+
+    /// Copy the highlighted (or detailed) entry's text to the clipboard. When
+    /// there is no clipboard, e.g. over SSH, the text goes to a temp file and
+    /// the status bar names it instead.
+    fn copy_current_entry(&mut self) {
+        let index = self.current_message_index();
+        let Some(entry) = self.conversation.get(index) else {
+            self.status_message = "Nothing to copy".to_string();
+            return;
+        };
+        let text = entry_plain_text(entry);
+
+        self.status_message = match self.clipboard.set_text(&text) {
+            Ok(()) => "Copied".to_string(),
+            Err(_) => {
+                // The uuid comes from the log, so only a plain one names the file
+                let name = entry
+                    .uuid
+                    .as_deref()
+                    .filter(|uuid| {
+                        !uuid.is_empty()
+                            && uuid.len() <= 64
+                            && uuid.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+                    })
+                    .map_or_else(|| format!("entry-{}", index + 1), str::to_string);
+                let path = std::env::temp_dir().join(format!("cc-log-viewer-{}.txt", name));
+                match fs::write(&path, &text) {
+                    Ok(()) => format!("Clipboard unavailable; saved to {}", path.display()),
+                    Err(e) => format!("Copy failed: {}", e),
+                }
+            }
+        };
+    }
 
-        for line in content.lines() {
-            if let Ok(entry) = serde_json::from_str::<LogEntry>(line) {
-                entries.push(entry);
+    /// Apply any pending watcher events: entries for the open conversation
+    /// are appended in place. If events were missed (the receiver lagged)
+    /// or an entry can't be deduplicated, the conversation is reloaded.
+    pub async fn process_watch_events(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let mut needs_reload = false;
+        let mut projects_changed = false;
+
+        loop {
+            let event = match self.watch_rx.try_recv() {
+                Ok(event) => event,
+                Err(TryRecvError::Lagged(_)) => {
+                    needs_reload = true;
+                    continue;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Closed) => break,
+            };
+
+            if event.event_type == "project_created" {
+                projects_changed = true;
+                continue;
+            }
+            if !matches!(
+                self.mode,
+                AppMode::ConversationView | AppMode::MessageDetail
+            ) || event.event_type != "log_entry"
+            {
+                continue;
+            }
+            let (project, session) = self.current_names();
+            if project != Some(event.project.as_str()) || session != event.session.as_deref() {
+                continue;
+            }
+            let Some(entry) = event.entry else {
+                continue;
+            };
+
+            // The watcher may replay entries we already loaded from disk.
+            match entry.uuid.as_deref() {
+                Some(uuid) => {
+                    if !self
+                        .conversation
+                        .iter()
+                        .any(|e| e.uuid.as_deref() == Some(uuid))
+                    {
+                        if self
+                            .search_query
+                            .as_ref()
+                            .is_some_and(|query| entry_matches(&entry, query))
+                        {
+                            self.search_matches.push(self.conversation.len());
+                        }
+                        self.conversation.push(entry);
+                    }
+                }
+                None => needs_reload = true,
             }
         }
 
-        self.conversation = entries;
-        self.scroll_offset = 0;
+        if projects_changed {
+            // Keep the same project selected even if the new one sorts before it.
+            let selected = self
+                .selected_project
+                .and_then(|i| self.projects.get(i))
+                .map(|p| p.name.clone());
+            self.refresh_projects().await?;
+            if let Some(i) =
+                selected.and_then(|name| self.projects.iter().position(|p| p.name == name))
+            {
+                self.selected_project = Some(i);
+                self.project_list_state.select(Some(i));
+            }
+        }
+        if needs_reload {
+            if let (Some(project), Some(session)) = self.current_names() {
+                let (project, session) = (project.to_string(), session.to_string());
+                return self.refresh_conversation(&project, &session).await;
+            }
+        }
+        if self.follow {
+            self.scroll_offset = self.conversation.len().saturating_sub(1);
+        }
 
         Ok(())
     }
 
+    /// Default export file name for the current session:
+    /// `<project>-<session>.<ext>` in the working directory.
+    pub fn default_export_path(&self) -> PathBuf {
+        let (project, session) = self.current_names();
+        PathBuf::from(format!(
+            "{}-{}.{}",
+            project.unwrap_or("conversation"),
+            session.unwrap_or("export"),
+            self.export_format.extension()
+        ))
+    }
+
+    fn export_target(&self) -> PathBuf {
+        self.export_path
+            .clone()
+            .unwrap_or_else(|| self.default_export_path())
+    }
+
+    fn current_names(&self) -> (Option<&str>, Option<&str>) {
+        let project = self
+            .selected_project
+            .and_then(|idx| self.projects.get(idx))
+            .map(|p| p.name.as_str());
+        let session = self
+            .selected_session
+            .and_then(|idx| self.sessions.get(idx))
+            .map(|s| s.id.as_str());
+        (project, session)
+    }
+
+    fn cycle_export_format(&mut self, forward: bool) {
+        self.export_format = if forward {
+            self.export_format.next()
+        } else {
+            self.export_format.previous()
+        };
+        // Keep a user-chosen path but follow the selected format's extension
+        if let Some(path) = self.export_path.as_mut() {
+            path.set_extension(self.export_format.extension());
+        }
+    }
+
+    fn handle_export_path_input(&mut self, key: KeyCode) {
+        let Some(input) = self.export_path_input.as_mut() else {
+            return;
+        };
+
+        match key {
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Enter => {
+                let path = input.trim().to_string();
+                self.export_path_input = None;
+                self.export_path = if path.is_empty() {
+                    None
+                } else {
+                    Some(PathBuf::from(path))
+                };
+            }
+            KeyCode::Esc => {
+                self.export_path_input = None;
+            }
+            _ => {}
+        }
+    }
+
     pub async fn export_conversation(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         if self.conversation.is_empty() {
             self.status_message = "No conversation to export".to_string();
             return Ok(());
         }
 
-        let mut export_content = String::new();
-        export_content.push_str("Claude Code Conversation Export\n");
-        export_content.push_str("================================\n\n");
+        let (project, session) = self.current_names();
+        let export_content = generate_export(
+            self.export_format,
+            project.unwrap_or("unknown"),
+            session.unwrap_or("unknown"),
+            &self.conversation,
+        );
+
+        let path = self.export_target();
+        fs::write(&path, export_content)?;
+        let display_path = fs::canonicalize(&path).unwrap_or(path);
+        self.status_message = format!(
+            "Conversation exported as {} to {}",
+            self.export_format.label(),
+            display_path.display()
+        );
 
-        for entry in &self.conversation {
-            if let Some(message) = &entry.message {
-                let role = message
-                    .get("role")
-                    .and_then(|r| r.as_str())
-                    .unwrap_or("system");
+        Ok(())
+    }
+}
 
-                let content = message
-                    .get("content")
-                    .and_then(|c| {
-                        if c.is_string() {
-                            c.as_str().map(|s| s.to_string())
-                        } else {
-                            Some(format!("{}", c))
-                        }
-                    })
-                    .unwrap_or_else(|| "No content".to_string());
+const GLOBAL_KEYBINDINGS: &[(&str, &str)] =
+    &[("?", "Toggle this help"), ("r", "Refresh"), ("q", "Quit")];
 
-                let timestamp = entry
-                    .timestamp
-                    .map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
-                    .unwrap_or_else(|| "Unknown".to_string());
+fn mode_label(mode: &AppMode) -> &'static str {
+    match mode {
+        AppMode::ProjectList => "Projects",
+        AppMode::SessionList => "Sessions",
+        AppMode::ConversationView => "Conversation",
+        AppMode::MessageDetail => "Message detail",
+        AppMode::Export => "Export",
+    }
+}
+
+fn mode_keybindings(mode: &AppMode) -> &'static [(&'static str, &'static str)] {
+    match mode {
+        AppMode::ProjectList => &[
+            ("↑/↓ j/k", "Select project"),
+            ("g/G Home/End", "First / last project"),
+            ("Enter", "Open project"),
+        ],
+        AppMode::SessionList => &[
+            ("↑/↓ j/k", "Select session"),
+            ("g/G Home/End", "First / last session"),
+            ("Enter", "Open session"),
+            ("/", "Filter sessions"),
+            ("s / S", "Cycle sort / reverse order"),
+            ("d", "Archive session"),
+            ("Esc", "Back to projects"),
+        ],
+        AppMode::ConversationView => &[
+            ("↑/↓ j/k", "Scroll messages"),
+            ("PgUp/PgDn", "Scroll by page"),
+            ("g/G Home/End", "First / last message"),
+            ("Enter", "Show message detail"),
+            ("/", "Search conversation"),
+            ("n / N", "Next / previous match"),
+            ("f", "Follow newest messages"),
+            ("y", "Copy message"),
+            ("e", "Export conversation"),
+            ("Esc", "Back to sessions"),
+        ],
+        AppMode::MessageDetail => &[
+            ("↑/↓ j/k", "Scroll message"),
+            ("g/G Home/End", "Top / bottom"),
+            ("PgUp/PgDn", "Scroll by page"),
+            ("y", "Copy message"),
+            ("Esc", "Back to conversation"),
+        ],
+        AppMode::Export => &[
+            ("←/→", "Change format"),
+            ("p", "Edit output path"),
+            ("s / Enter", "Save export"),
+            ("Esc", "Cancel"),
+        ],
+    }
+}
 
-                export_content.push_str(&format!(
-                    "[{}] {}: {}\n\n",
-                    timestamp,
-                    role.to_uppercase(),
-                    content
-                ));
+fn render_archive_confirmation(f: &mut Frame, area: Rect, session: &str) {
+    let popup_area = centered_rect(50, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = Text::from(vec![
+        Line::from(format!("Archive session {}?", session)),
+        Line::from(""),
+        Line::from("It will be moved to the project's archived/ folder."),
+        Line::from(""),
+        Line::from(Span::styled(
+            "y: Archive   n/Esc: Cancel",
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        )),
+    ]);
+    let paragraph = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .title("Confirm archive")
+                .borders(Borders::ALL)
+                .style(Style::default().bg(Color::DarkGray)),
+        )
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(paragraph, popup_area);
+}
+
+fn entry_role(entry: &LogEntry) -> &str {
+    entry
+        .message
+        .as_ref()
+        .and_then(|m| m.get("role"))
+        .and_then(|r| r.as_str())
+        .unwrap_or("system")
+}
+
+/// Conversation list color for an entry. Errors win over everything so a
+/// failing step stands out, then tool calls and results, then the role.
+fn entry_color(entry: &LogEntry, role: &str, summary: &str) -> Color {
+    let blocks = entry
+        .message
+        .as_ref()
+        .and_then(|m| m.get("content"))
+        .and_then(|c| c.as_array())
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+    let has_block = |kind: &str| {
+        blocks
+            .iter()
+            .any(|b| b.get("type").and_then(|t| t.as_str()) == Some(kind))
+    };
+    let tool_failed = tool_error_count(entry) > 0;
+
+    if tool_failed || summary.to_lowercase().contains("error") {
+        Color::Red
+    } else if has_block("tool_use") {
+        Color::Magenta
+    } else if has_block("tool_result") {
+        Color::Blue
+    } else {
+        match role {
+            "user" => Color::Cyan,
+            "assistant" => Color::Green,
+            _ => Color::Gray,
+        }
+    }
+}
+
+/// Build the full, untruncated lines for a single log entry, including
+/// tool_use inputs and tool_result outputs.
+fn entry_detail_lines(entry: &LogEntry) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let heading = Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD);
+
+    if let Some(summary) = &entry.summary {
+        lines.push(Line::from(Span::styled("📋 Summary", heading)));
+        push_text_lines(&mut lines, summary);
+    }
+
+    // Reuse the exporter's plain-text rendering so tool calls and results
+    // read the same here as in a .txt export, then restyle its underlined
+    // headings for the terminal.
+    let body = render_message_body(
+        ExportFormat::PlainText,
+        &ExportOptions::default(),
+        entry,
+        &HashMap::new(),
+    );
+    let body_lines: Vec<&str> = body.trim_end().lines().collect();
+    if !body_lines.is_empty() && !lines.is_empty() {
+        lines.push(Line::from(""));
+    }
+
+    // Claude writes its replies in markdown, so style those; anything the
+    // line styler doesn't recognise is shown as written.
+    let markdown = entry_role(entry) == "assistant";
+    let mut in_code_block = false;
+    let mut i = 0;
+    while i < body_lines.len() {
+        let line = body_lines[i];
+        let is_heading = !in_code_block
+            && body_lines.get(i + 1).is_some_and(|next| {
+                !line.is_empty()
+                    && next.chars().count() == line.chars().count()
+                    && next.chars().all(|c| c == '-' || c == '=')
+            });
+        if is_heading {
+            lines.push(Line::from(Span::styled(line.to_string(), heading)));
+            i += 2;
+        } else if markdown {
+            lines.push(markdown_line(line, &mut in_code_block));
+            i += 1;
+        } else {
+            lines.push(Line::from(line.to_string()));
+            i += 1;
+        }
+    }
+
+    if lines.is_empty() {
+        lines.push(Line::from("No content"));
+    }
+
+    lines
+}
+
+/// Style one line of markdown: fenced code, `#` headings, list bullets and
+/// block quotes, with inline code and emphasis inside them. Lines indented
+/// like the exporter's tool blocks are left alone.
+fn markdown_line(line: &str, in_code_block: &mut bool) -> Line<'static> {
+    let code = Style::default().fg(Color::Cyan);
+    let trimmed = line.trim_start();
+
+    if trimmed.starts_with("```") {
+        *in_code_block = !*in_code_block;
+        return Line::from(Span::styled(
+            line.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    if *in_code_block {
+        return Line::from(Span::styled(line.to_string(), code));
+    }
+    if line.starts_with("    ") || line.starts_with('\t') {
+        return Line::from(line.to_string());
+    }
+
+    let hashes = trimmed.chars().take_while(|&c| c == '#').count();
+    if (1..=6).contains(&hashes) && trimmed[hashes..].starts_with(' ') {
+        let mut style = Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD);
+        if hashes == 1 {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        return Line::from(Span::styled(trimmed[hashes + 1..].to_string(), style));
+    }
+
+    let indent = &line[..line.len() - trimmed.len()];
+    let mut spans = vec![Span::raw(indent.to_string())];
+    let rest = if let Some(item) = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|marker| trimmed.strip_prefix(marker))
+    {
+        spans.push(Span::styled("• ", Style::default().fg(Color::Yellow)));
+        item
+    } else if let Some(quote) = trimmed.strip_prefix("> ") {
+        spans.push(Span::styled("│ ", Style::default().fg(Color::DarkGray)));
+        spans.extend(
+            markdown_inline(quote)
+                .into_iter()
+                .map(|span| span.patch_style(Style::default().add_modifier(Modifier::ITALIC))),
+        );
+        return Line::from(spans);
+    } else {
+        trimmed
+    };
+    spans.extend(markdown_inline(rest));
+    Line::from(spans)
+}
+
+/// Split text into spans for `code`, **bold** and *italic* runs. Markers
+/// without a closing partner are kept as literal text.
+fn markdown_inline(text: &str) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let styled = match c {
+            '`' => rest[1..]
+                .find('`')
+                .filter(|&end| end > 0)
+                .map(|end| (&rest[1..end + 1], end + 2, Style::default().fg(Color::Cyan))),
+            '*' if rest.starts_with("**") => {
+                rest[2..].find("**").filter(|&end| end > 0).map(|end| {
+                    (
+                        &rest[2..end + 2],
+                        end + 4,
+                        Style::default().add_modifier(Modifier::BOLD),
+                    )
+                })
+            }
+            '*' => rest[1..]
+                .find('*')
+                .map(|end| &rest[1..end + 1])
+                .filter(|inner| {
+                    !inner.is_empty() && !inner.starts_with(' ') && !inner.ends_with(' ')
+                })
+                .map(|inner| {
+                    (
+                        inner,
+                        inner.len() + 2,
+                        Style::default().add_modifier(Modifier::ITALIC),
+                    )
+                }),
+            _ => None,
+        };
+
+        match styled {
+            Some((inner, consumed, style)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::raw(std::mem::take(&mut plain)));
+                }
+                spans.push(Span::styled(inner.to_string(), style));
+                rest = &rest[consumed..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
             }
         }
+    }
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+    spans
+}
+
+/// One-line, plain-text summary of message content for the conversation
+/// list: text is shown as-is, tool calls as icon, name and key input, and
+/// tool results as their first line of output.
+fn content_summary(content: &serde_json::Value) -> String {
+    let blocks = match content {
+        serde_json::Value::String(text) => return text.clone(),
+        serde_json::Value::Array(blocks) => blocks,
+        other => return other.to_string(),
+    };
+
+    blocks
+        .iter()
+        .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+            Some("text") => block
+                .get("text")
+                .and_then(|t| t.as_str())
+                .map(|t| t.trim().to_string()),
+            Some("thinking") => Some("💭 Thinking…".to_string()),
+            Some("tool_use") => {
+                let name = block.get("name").and_then(|n| n.as_str()).unwrap_or("?");
+                let input = block.get("input").cloned().unwrap_or_default();
+                match tool_input_summary(name, &input) {
+                    Some(summary) => Some(format!("{} {}: {}", get_tool_icon(name), name, summary)),
+                    None => Some(format!("{} {}", get_tool_icon(name), name)),
+                }
+            }
+            Some("tool_result") => {
+                let output = tool_result_text(block);
+                let line_count = output.lines().count();
+                let first_line = output.lines().find(|l| !l.trim().is_empty()).unwrap_or("");
+                if line_count > 1 {
+                    Some(format!(
+                        "📋 Result: {} (+{} lines)",
+                        first_line.trim(),
+                        line_count - 1
+                    ))
+                } else if first_line.is_empty() {
+                    Some("📋 Result: (empty)".to_string())
+                } else {
+                    Some(format!("📋 Result: {}", first_line.trim()))
+                }
+            }
+            Some("image") => Some("🖼️ Image".to_string()),
+            _ => None,
+        })
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
 
-        let filename = "conversation_export.txt";
-        fs::write(filename, export_content)?;
-        self.status_message = format!("Conversation exported to {}", filename);
+/// The most relevant input of a tool call, e.g. the command for Bash or the
+/// file path for Read/Edit.
+fn tool_input_summary(name: &str, input: &serde_json::Value) -> Option<String> {
+    let field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(str::to_string);
+
+    match name {
+        "Bash" => field("command").map(|c| format!("$ {}", c)),
+        "Read" | "Write" | "Edit" | "MultiEdit" => field("file_path"),
+        "Grep" => field("pattern").map(|p| match field("path") {
+            Some(path) => format!("\"{}\" in {}", p, path),
+            None => format!("\"{}\"", p),
+        }),
+        "Glob" => field("pattern"),
+        "LS" => field("path"),
+        "WebFetch" => field("url"),
+        "WebSearch" => field("query"),
+        "Task" => field("description"),
+        "TodoWrite" => input
+            .get("todos")
+            .and_then(|t| t.as_array())
+            .map(|todos| format!("{} todos", todos.len())),
+        _ => None,
+    }
+}
 
-        Ok(())
+/// Flatten an entry's rendered lines into plain text for searching.
+fn entry_plain_text(entry: &LogEntry) -> String {
+    entry_detail_lines(entry)
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn entry_matches(entry: &LogEntry, query: &str) -> bool {
+    entry_plain_text(entry)
+        .to_lowercase()
+        .contains(&query.to_lowercase())
+}
+
+fn push_text_lines(lines: &mut Vec<Line<'static>>, text: &str) {
+    for line in text.lines() {
+        lines.push(Line::from(line.to_string()));
     }
 }
 
+/// Split off a one-column strip on the right for a vertical scrollbar so the
+/// content never renders underneath it.
+fn split_scrollbar(area: Rect) -> (Rect, Rect) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(area);
+    (chunks[0], chunks[1])
+}
+
+fn vertical_scrollbar() -> Scrollbar<'static> {
+    Scrollbar::new(ScrollbarOrientation::VerticalRight)
+        .begin_symbol(Some("↑"))
+        .end_symbol(Some("↓"))
+}
+
 fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
     let popup_layout = Layout::default()
         .direction(Direction::Vertical)
@@ -0,0 +1,1431 @@
+// ABOUTME: Integration tests for the JSON API endpoints beyond the basic listings
+// ABOUTME: Exercises session-derived views such as flattened content blocks and tool config
+
+use axum_test::TestServer;
+use cc_log_viewer::{
+    encode_auth_token, export_session_markdown, flatten_message_blocks, get_project_stats,
+    get_projects, get_session_blocks, get_session_entries_since, get_session_entry,
+    get_session_logs, get_session_meta, get_session_tree, get_sessions, get_tool_config, get_tools,
+    healthz, require_auth_token, safe_component, search, static_asset,
+    tool_config::{OutputStyle, ToolConfig},
+    AppState, ContentBlock, LogEntry,
+};
+use serde_json::{json, Value};
+use std::fs;
+use tempfile::TempDir;
+
+fn create_test_server(projects_dir: std::path::PathBuf) -> TestServer {
+    let state = AppState::new(projects_dir).expect("Failed to create app state");
+    create_test_server_with_state(state)
+}
+
+fn create_test_server_with_state(state: AppState) -> TestServer {
+    let app = axum::Router::new()
+        .route("/api/projects", axum::routing::get(get_projects))
+        .route(
+            "/api/projects/:project/sessions",
+            axum::routing::get(get_sessions),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session",
+            axum::routing::get(get_session_logs),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/blocks",
+            axum::routing::get(get_session_blocks),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/entry/:uuid",
+            axum::routing::get(get_session_entry),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/tree",
+            axum::routing::get(get_session_tree),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/export.md",
+            axum::routing::get(export_session_markdown),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/meta",
+            axum::routing::get(get_session_meta),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/since",
+            axum::routing::get(get_session_entries_since),
+        )
+        .route("/api/tool-config", axum::routing::get(get_tool_config))
+        .route("/api/search", axum::routing::get(search))
+        .route("/api/tools", axum::routing::get(get_tools))
+        .route(
+            "/api/projects/:project/stats",
+            axum::routing::get(get_project_stats),
+        )
+        .with_state(state);
+
+    TestServer::new(app).expect("Failed to create test server")
+}
+
+fn write_session(projects_dir: &std::path::Path, project: &str, session: &str, lines: &[Value]) {
+    let project_dir = projects_dir.join(project);
+    fs::create_dir_all(&project_dir).unwrap();
+    let content = lines
+        .iter()
+        .map(|l| l.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(project_dir.join(format!("{}.jsonl", session)), content).unwrap();
+}
+
+fn interleaved_assistant_entry() -> Value {
+    json!({
+        "type": "assistant",
+        "message": {
+            "role": "assistant",
+            "content": [
+                {"type": "thinking", "thinking": "Let me check the files"},
+                {"type": "text", "text": "First I'll list files."},
+                {"type": "tool_use", "id": "toolu_1", "name": "Bash", "input": {"command": "ls"}},
+                {"type": "text", "text": "Then read one."},
+                {"type": "tool_use", "id": "toolu_2", "name": "Read", "input": {"file_path": "/a.rs"}}
+            ]
+        },
+        "timestamp": "2024-01-15T10:00:00Z",
+        "uuid": "assistant-1"
+    })
+}
+
+#[test]
+fn test_flatten_message_blocks_preserves_order() {
+    let entry: LogEntry = serde_json::from_value(interleaved_assistant_entry()).unwrap();
+    let blocks = flatten_message_blocks(&entry);
+
+    assert_eq!(blocks.len(), 5);
+    assert!(
+        matches!(&blocks[0], ContentBlock::Thinking { thinking } if thinking == "Let me check the files")
+    );
+    assert!(matches!(&blocks[1], ContentBlock::Text { text } if text == "First I'll list files."));
+    assert!(matches!(&blocks[2], ContentBlock::ToolUse { name, .. } if name == "Bash"));
+    assert!(matches!(&blocks[3], ContentBlock::Text { text } if text == "Then read one."));
+    assert!(matches!(&blocks[4], ContentBlock::ToolUse { name, .. } if name == "Read"));
+
+    let string_entry: LogEntry = serde_json::from_value(json!({
+        "type": "user",
+        "message": {"role": "user", "content": "plain prompt"}
+    }))
+    .unwrap();
+    assert_eq!(
+        flatten_message_blocks(&string_entry),
+        vec![ContentBlock::Text {
+            text: "plain prompt".to_string()
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_session_blocks_endpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "blocks-project",
+        "session-1",
+        &[
+            interleaved_assistant_entry(),
+            json!({
+                "type": "user",
+                "message": {
+                    "role": "user",
+                    "content": [
+                        {"type": "tool_result", "tool_use_id": "toolu_1", "content": "Cargo.toml"}
+                    ]
+                },
+                "timestamp": "2024-01-15T10:00:01Z",
+                "uuid": "user-1"
+            }),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let response = server
+        .get("/api/projects/blocks-project/sessions/session-1/blocks")
+        .await;
+    response.assert_status_ok();
+
+    let entries: Vec<Value> = response.json();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["uuid"], "assistant-1");
+    assert_eq!(entries[0]["role"], "assistant");
+
+    let types: Vec<&str> = entries[0]["blocks"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|b| b["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        types,
+        vec!["thinking", "text", "tool_use", "text", "tool_use"]
+    );
+    assert_eq!(entries[0]["blocks"][2]["input"]["command"], "ls");
+
+    let result = &entries[1]["blocks"][0];
+    assert_eq!(result["type"], "tool_result");
+    assert_eq!(result["id"], "toolu_1");
+    assert_eq!(result["content"], "Cargo.toml");
+
+    let missing = server
+        .get("/api/projects/blocks-project/sessions/missing/blocks")
+        .await;
+    missing.assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_tool_config_loads_and_is_served() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("tools.toml");
+    fs::write(
+        &config_path,
+        r#"
+[tools."mcp__mycompany__deploy"]
+icon = "🚀"
+output = "blockquote"
+fields = [
+    { key = "service", label = "Service" },
+    { key = "environment" },
+]
+"#,
+    )
+    .unwrap();
+
+    let config = ToolConfig::load(&config_path).unwrap();
+    let spec = config.get("mcp__mycompany__deploy").unwrap();
+    assert_eq!(spec.icon.as_deref(), Some("🚀"));
+    assert_eq!(spec.output, OutputStyle::Blockquote);
+    assert_eq!(spec.fields.len(), 2);
+    assert_eq!(spec.fields[0].label.as_deref(), Some("Service"));
+    assert!(spec.fields[1].label.is_none());
+
+    let json_path = temp_dir.path().join("tools.json");
+    fs::write(
+        &json_path,
+        r#"{"tools": {"mcp__mycompany__status": {"fields": [{"key": "id"}]}}}"#,
+    )
+    .unwrap();
+    let json_config = ToolConfig::load(&json_path).unwrap();
+    assert_eq!(
+        json_config.get("mcp__mycompany__status").unwrap().output,
+        OutputStyle::Code
+    );
+
+    let state = AppState::new(temp_dir.path().to_path_buf())
+        .unwrap()
+        .with_tool_config(config);
+    let server = create_test_server_with_state(state);
+    let response = server.get("/api/tool-config").await;
+    response.assert_status_ok();
+
+    let body: Value = response.json();
+    assert_eq!(body["tools"]["mcp__mycompany__deploy"]["icon"], "🚀");
+    assert_eq!(
+        body["tools"]["mcp__mycompany__deploy"]["output"],
+        "blockquote"
+    );
+}
+
+#[tokio::test]
+async fn test_auth_token_gate() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route("/api/projects", axum::routing::get(get_projects))
+        .route("/healthz", axum::routing::get(healthz))
+        .with_state(state)
+        .layer(axum::middleware::from_fn_with_state(
+            std::sync::Arc::new("s3cret".to_string()),
+            require_auth_token,
+        ));
+    let server = TestServer::new(app).unwrap();
+
+    server
+        .get("/api/projects")
+        .await
+        .assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    server
+        .get("/api/projects")
+        .authorization_bearer("wrong")
+        .await
+        .assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    server
+        .get("/api/projects")
+        .authorization_bearer("s3cret")
+        .await
+        .assert_status_ok();
+
+    // Health probes don't carry the token
+    server.get("/healthz").await.assert_status_ok();
+
+    let via_query = server.get("/api/projects?token=s3cret").await;
+    via_query.assert_status_ok();
+    let cookie = via_query.header("set-cookie");
+    assert!(cookie
+        .to_str()
+        .unwrap()
+        .starts_with("cc_log_viewer_token=s3cret"));
+
+    server
+        .get("/api/projects")
+        .add_header(
+            axum::http::header::COOKIE,
+            axum::http::HeaderValue::from_static("cc_log_viewer_token=s3cret"),
+        )
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_auth_token_with_reserved_characters() {
+    let token = "a+b&c=d%2F; é";
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route("/api/projects", axum::routing::get(get_projects))
+        .with_state(state)
+        .layer(axum::middleware::from_fn_with_state(
+            std::sync::Arc::new(token.to_string()),
+            require_auth_token,
+        ));
+    let server = TestServer::new(app).unwrap();
+
+    // The raw token isn't a valid query value; its encoding is
+    server
+        .get("/api/projects?token=a+b&c=d%2F;%20%C3%A9")
+        .await
+        .assert_status(axum::http::StatusCode::UNAUTHORIZED);
+    let via_query = server
+        .get(&format!(
+            "/api/projects?limit=5&token={}",
+            encode_auth_token(token)
+        ))
+        .await;
+    via_query.assert_status_ok();
+
+    // The cookie holds the encoded token and is accepted on the next request
+    let set_cookie = via_query.header("set-cookie").to_str().unwrap().to_string();
+    let cookie = set_cookie.split(';').next().unwrap().to_string();
+    assert_eq!(
+        cookie,
+        format!("cc_log_viewer_token={}", encode_auth_token(token))
+    );
+    server
+        .get("/api/projects")
+        .add_header(
+            axum::http::header::COOKIE,
+            axum::http::HeaderValue::from_str(&format!("theme=dark; {}", cookie)).unwrap(),
+        )
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_sessions_date_range_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    for (session, timestamp) in [
+        ("old", "2024-01-01T09:00:00Z"),
+        ("mid", "2024-01-10T09:00:00Z"),
+        ("new", "2024-01-20T09:00:00Z"),
+    ] {
+        write_session(
+            temp_dir.path(),
+            "range-project",
+            session,
+            &[json!({
+                "type": "user",
+                "message": {"role": "user", "content": "hi"},
+                "timestamp": timestamp
+            })],
+        );
+    }
+    write_session(
+        temp_dir.path(),
+        "range-project",
+        "undated",
+        &[json!({"type": "user", "message": {"role": "user", "content": "hi"}})],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let ids = |response: axum_test::TestResponse| {
+        let mut ids: Vec<String> = response
+            .json::<Vec<Value>>()
+            .iter()
+            .map(|s| s["id"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    };
+
+    let all = server.get("/api/projects/range-project/sessions").await;
+    assert_eq!(ids(all), vec!["mid", "new", "old", "undated"]);
+
+    let since = server
+        .get("/api/projects/range-project/sessions?since=2024-01-05T00:00:00Z")
+        .await;
+    assert_eq!(ids(since), vec!["mid", "new"]);
+
+    let between = server
+        .get("/api/projects/range-project/sessions?since=2024-01-05T00:00:00Z&until=2024-01-15T00:00:00%2B00:00")
+        .await;
+    assert_eq!(ids(between), vec!["mid"]);
+
+    server
+        .get("/api/projects/range-project/sessions?since=last-week")
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_session_timestamps_use_earliest_and_latest_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "order-project",
+        "shuffled",
+        &[
+            json!({"type": "summary", "summary": "Shuffled session", "leafUuid": "x"}),
+            json!({"type": "user", "message": {"role": "user", "content": "second"}, "timestamp": "2024-01-15T10:05:00Z"}),
+            json!({"type": "user", "message": {"role": "user", "content": "last"}, "timestamp": "2024-01-15T11:30:00Z"}),
+            json!({"type": "user", "message": {"role": "user", "content": "first"}, "timestamp": "2024-01-15T09:00:00Z"}),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let sessions: Vec<Value> = server
+        .get("/api/projects/order-project/sessions")
+        .await
+        .json();
+
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["summary"], "Shuffled session");
+    assert_eq!(sessions[0]["started_at"], "2024-01-15T09:00:00Z");
+    assert_eq!(sessions[0]["timestamp"], "2024-01-15T09:00:00Z");
+    assert_eq!(sessions[0]["last_activity"], "2024-01-15T11:30:00Z");
+}
+
+#[tokio::test]
+async fn test_session_counts_skip_blank_and_malformed_lines() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("count-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let content = [
+        json!({"type": "summary", "summary": "Counting", "leafUuid": "x"}).to_string(),
+        json!({"type": "user", "message": {"role": "user", "content": "hi"}, "timestamp": "2024-01-15T10:00:00Z"}).to_string(),
+        "{not valid json".to_string(),
+        json!({"type": "assistant", "message": {"role": "assistant", "content": "hello"}, "timestamp": "2024-01-15T10:00:01Z"}).to_string(),
+        json!({"type": "system", "content": "meta"}).to_string(),
+        json!({"message": {"role": "user", "content": "untyped"}}).to_string(),
+        String::new(),
+        String::new(),
+    ]
+    .join("\n");
+    fs::write(project_dir.join("session.jsonl"), content).unwrap();
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let sessions: Vec<Value> = server
+        .get("/api/projects/count-project/sessions")
+        .await
+        .json();
+
+    assert_eq!(sessions[0]["message_count"], 2);
+    assert_eq!(sessions[0]["entry_count"], 4);
+}
+
+#[tokio::test]
+async fn test_session_summaries_cached_until_mtime_changes() {
+    let temp_dir = TempDir::new().unwrap();
+    let entry = |text: &str| json!({"type": "user", "message": {"role": "user", "content": text}, "timestamp": "2024-01-15T10:00:00Z"});
+    write_session(temp_dir.path(), "cache-project", "s1", &[entry("one")]);
+    let path = temp_dir.path().join("cache-project").join("s1.jsonl");
+    let original_mtime = fs::metadata(&path).unwrap().modified().unwrap();
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let count = |sessions: Vec<Value>| sessions[0]["message_count"].as_u64().unwrap();
+
+    let first: Vec<Value> = server
+        .get("/api/projects/cache-project/sessions")
+        .await
+        .json();
+    assert_eq!(count(first), 1);
+
+    // Same mtime: the cached summary is served without re-parsing.
+    write_session(
+        temp_dir.path(),
+        "cache-project",
+        "s1",
+        &[entry("one"), entry("two")],
+    );
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(original_mtime)
+        .unwrap();
+    let cached: Vec<Value> = server
+        .get("/api/projects/cache-project/sessions")
+        .await
+        .json();
+    assert_eq!(count(cached), 1);
+
+    // A new mtime invalidates the entry.
+    fs::File::options()
+        .write(true)
+        .open(&path)
+        .unwrap()
+        .set_modified(original_mtime + std::time::Duration::from_secs(5))
+        .unwrap();
+    let refreshed: Vec<Value> = server
+        .get("/api/projects/cache-project/sessions")
+        .await
+        .json();
+    assert_eq!(count(refreshed), 2);
+}
+
+#[tokio::test]
+async fn test_projects_scan_order_is_deterministic() {
+    let temp_dir = TempDir::new().unwrap();
+    for i in 0..24 {
+        // Pairs of projects share a timestamp to exercise the name tiebreak.
+        let timestamp = format!("2024-01-{:02}T10:00:00Z", 1 + i / 2);
+        write_session(
+            temp_dir.path(),
+            &format!("project-{:02}", i),
+            "session",
+            &[
+                json!({"type": "user", "message": {"role": "user", "content": "hi"}, "timestamp": timestamp}),
+            ],
+        );
+    }
+    fs::create_dir_all(temp_dir.path().join("empty-project")).unwrap();
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let names = |projects: Vec<Value>| -> Vec<String> {
+        projects
+            .iter()
+            .map(|p| p["name"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let first = names(server.get("/api/projects").await.json());
+    let second = names(server.get("/api/projects").await.json());
+    assert_eq!(first, second);
+    assert_eq!(first.len(), 25);
+    assert_eq!(&first[..3], &["project-22", "project-23", "project-20"]);
+    assert_eq!(first.last().unwrap(), "empty-project");
+}
+
+#[tokio::test]
+async fn test_project_stats_endpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "stats-project",
+        "s1",
+        &[
+            json!({"type": "summary", "summary": "First", "leafUuid": "x"}),
+            json!({"type": "user", "message": {"role": "user", "content": "go"}, "timestamp": "2024-01-15T10:00:00Z"}),
+            interleaved_assistant_entry(),
+            json!({"type": "user", "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "No such file", "is_error": true}
+            ]}}),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "stats-project",
+        "s2",
+        &[json!({
+            "type": "assistant",
+            "message": {
+                "role": "assistant",
+                "content": [{"type": "tool_use", "id": "t3", "name": "Bash", "input": {"command": "pwd"}}],
+                "usage": {"input_tokens": 100, "output_tokens": 20, "cache_read_input_tokens": 500}
+            },
+            "timestamp": "2024-01-20T08:00:00Z"
+        })],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let response = server.get("/api/projects/stats-project/stats").await;
+    response.assert_status_ok();
+
+    let stats: Value = response.json();
+    assert_eq!(stats["project_name"], "stats-project");
+    assert_eq!(stats["total_sessions"], 2);
+    assert_eq!(stats["total_messages"], 4);
+    assert_eq!(stats["error_count"], 1);
+    assert_eq!(stats["total_tool_calls"], 3);
+    assert_eq!(stats["tool_calls"]["Bash"], 2);
+    assert_eq!(stats["tool_calls"]["Read"], 1);
+    assert_eq!(stats["token_usage"]["input_tokens"], 100);
+    assert_eq!(stats["token_usage"]["cache_read_input_tokens"], 500);
+    assert_eq!(stats["first_activity"], "2024-01-15T10:00:00Z");
+    assert_eq!(stats["last_activity"], "2024-01-20T08:00:00Z");
+
+    server
+        .get("/api/projects/missing/stats")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_global_search_across_projects() {
+    let temp_dir = TempDir::new().unwrap();
+    let user = |text: &str, ts: &str| json!({"type": "user", "message": {"role": "user", "content": text}, "timestamp": ts});
+    write_session(
+        temp_dir.path(),
+        "alpha",
+        "s1",
+        &[
+            user("Let's look at the build", "2024-01-15T10:00:00Z"),
+            user(
+                "I think there is a Deadlock in the worker pool",
+                "2024-01-15T10:01:00Z",
+            ),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "beta",
+        "s2",
+        &[json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "thread 2 waiting: deadlock detected"}
+            ]},
+            "timestamp": "2024-01-16T09:00:00Z"
+        })],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+
+    let hits: Vec<Value> = server.get("/api/search?q=deadlock").await.json();
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0]["project"], "alpha");
+    assert_eq!(hits[0]["session"], "s1");
+    assert_eq!(hits[0]["line"], 2);
+    assert!(hits[0]["snippet"].as_str().unwrap().contains("Deadlock"));
+    assert_eq!(hits[0]["timestamp"], "2024-01-15T10:01:00Z");
+    assert_eq!(hits[1]["project"], "beta");
+
+    let filtered: Vec<Value> = server
+        .get("/api/search?q=deadlock&project=beta")
+        .await
+        .json();
+    assert_eq!(filtered.len(), 1);
+    assert_eq!(filtered[0]["project"], "beta");
+
+    let limited: Vec<Value> = server.get("/api/search?q=deadlock&limit=1").await.json();
+    assert_eq!(limited.len(), 1);
+
+    server
+        .get("/api/search?q=")
+        .await
+        .assert_status_bad_request();
+    server
+        .get("/api/search?q=x&project=missing")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_get_single_entry_by_uuid() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "proj",
+        "s1",
+        &[
+            json!({"type": "user", "uuid": "u-1", "message": {"role": "user", "content": "first"}}),
+            json!({"type": "assistant", "uuid": "a-2", "parentUuid": "u-1",
+                   "message": {"role": "assistant", "content": [{"type": "text", "text": "second"}]}}),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+
+    let entry: Value = server
+        .get("/api/projects/proj/sessions/s1/entry/a-2")
+        .await
+        .json();
+    assert_eq!(entry["uuid"], "a-2");
+    assert_eq!(entry["type"], "assistant");
+    assert_eq!(entry["parentUuid"], "u-1");
+
+    server
+        .get("/api/projects/proj/sessions/s1/entry/missing")
+        .await
+        .assert_status_not_found();
+    server
+        .get("/api/projects/proj/sessions/nope/entry/a-2")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_entries_since_uuid() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "proj",
+        "s1",
+        &[
+            json!({"type": "user", "uuid": "u-1", "message": {"role": "user", "content": "first"}}),
+            json!({"type": "summary", "summary": "no uuid here"}),
+            json!({"type": "assistant", "uuid": "a-2", "message": {"role": "assistant", "content": "second"}}),
+            json!({"type": "user", "uuid": "u-3", "message": {"role": "user", "content": "third"}}),
+        ],
+    );
+    let server = create_test_server(temp_dir.path().to_path_buf());
+
+    let newer: Value = server
+        .get("/api/projects/proj/sessions/s1/since?after=u-1")
+        .await
+        .json();
+    let newer = newer.as_array().unwrap();
+    assert_eq!(newer.len(), 3);
+    assert_eq!(newer[0]["type"], "summary");
+    assert_eq!(newer[2]["uuid"], "u-3");
+
+    let none: Value = server
+        .get("/api/projects/proj/sessions/s1/since?after=u-3")
+        .await
+        .json();
+    assert_eq!(none, json!([]));
+
+    let missing = server
+        .get("/api/projects/proj/sessions/s1/since?after=gone")
+        .await;
+    missing.assert_status_not_found();
+    assert_eq!(missing.json::<Value>()["code"], "entry_not_found");
+    server
+        .get("/api/projects/proj/sessions/s1/since")
+        .await
+        .assert_status_bad_request();
+    server
+        .get("/api/projects/proj/sessions/nope/since?after=u-1")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_session_logs_type_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "proj",
+        "s1",
+        &[
+            json!({"type": "summary", "summary": "Filtering"}),
+            json!({"type": "user", "uuid": "u1", "message": {"role": "user", "content": "hi"}}),
+            json!({"type": "assistant", "uuid": "a1",
+                   "message": {"role": "assistant", "content": [{"type": "text", "text": "hello"}]}}),
+            json!({"type": "assistant", "uuid": "a2",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}}
+            ]}}),
+            json!({"type": "user", "uuid": "u2",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "file.txt"}
+            ]}}),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let uuids = |entries: Vec<Value>| -> Vec<String> {
+        entries
+            .iter()
+            .map(|e| e["uuid"].as_str().unwrap_or("-").to_string())
+            .collect()
+    };
+
+    let all: Vec<Value> = server.get("/api/projects/proj/sessions/s1").await.json();
+    assert_eq!(all.len(), 5);
+
+    let assistant: Vec<Value> = server
+        .get("/api/projects/proj/sessions/s1?type=assistant")
+        .await
+        .json();
+    assert_eq!(uuids(assistant), vec!["a1", "a2"]);
+
+    let tools: Vec<Value> = server
+        .get("/api/projects/proj/sessions/s1?type=tool_use,tool_result")
+        .await
+        .json();
+    assert_eq!(uuids(tools), vec!["a2", "u2"]);
+
+    let mixed: Vec<Value> = server
+        .get("/api/projects/proj/sessions/s1?type=summary,%20toolUse")
+        .await
+        .json();
+    assert_eq!(uuids(mixed), vec!["-", "a2"]);
+}
+
+#[tokio::test]
+async fn test_session_tree_nests_sidechains() {
+    let temp_dir = TempDir::new().unwrap();
+    let entry = |uuid: &str, parent: Option<&str>, sidechain: bool| {
+        json!({
+            "type": "assistant",
+            "uuid": uuid,
+            "parentUuid": parent,
+            "isSidechain": sidechain,
+            "message": {"role": "assistant", "content": [{"type": "text", "text": uuid}]}
+        })
+    };
+    write_session(
+        temp_dir.path(),
+        "proj",
+        "s1",
+        &[
+            entry("main-1", None, false),
+            entry("task-1", Some("main-1"), true),
+            entry("task-2", Some("task-1"), true),
+            entry("main-2", Some("main-1"), false),
+            entry("lost-1", Some("not-in-file"), true),
+            entry("lost-2", Some("lost-1"), true),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let tree: Vec<Value> = server
+        .get("/api/projects/proj/sessions/s1/tree")
+        .await
+        .json();
+
+    let roots: Vec<&str> = tree
+        .iter()
+        .map(|n| n["entry"]["uuid"].as_str().unwrap())
+        .collect();
+    assert_eq!(roots, vec!["main-1", "main-2", "lost-1"]);
+
+    let children: Vec<&str> = tree[0]["children"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|n| n["entry"]["uuid"].as_str().unwrap())
+        .collect();
+    assert_eq!(children, vec!["task-1", "task-2"]);
+    assert_eq!(tree[0]["orphaned"], false);
+    assert!(tree[1]["children"].as_array().unwrap().is_empty());
+
+    assert_eq!(tree[2]["orphaned"], true);
+    assert_eq!(tree[2]["children"][0]["entry"]["uuid"], "lost-2");
+}
+
+#[tokio::test]
+async fn test_project_display_path() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "-Users-harper-src-my-app",
+        "s1",
+        &[
+            json!({"type": "summary", "summary": "No cwd here"}),
+            json!({"type": "user", "cwd": "/Users/harper/src/my-app",
+                   "message": {"role": "user", "content": "hi"},
+                   "timestamp": "2024-01-15T10:00:00Z"}),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "-tmp-scratch",
+        "s1",
+        &[
+            json!({"type": "user", "message": {"role": "user", "content": "hi"},
+                 "timestamp": "2024-01-14T10:00:00Z"}),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let projects: Vec<Value> = server.get("/api/projects").await.json();
+
+    assert_eq!(projects[0]["name"], "-Users-harper-src-my-app");
+    assert_eq!(projects[0]["display_path"], "/Users/harper/src/my-app");
+    assert_eq!(projects[1]["name"], "-tmp-scratch");
+    assert_eq!(projects[1]["display_path"], "/tmp/scratch");
+}
+
+#[tokio::test]
+async fn test_errors_are_structured_json() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "proj",
+        "s1",
+        &[json!({"type": "user", "uuid": "u1", "message": {"role": "user", "content": "hi"}})],
+    );
+    let server = create_test_server(temp_dir.path().to_path_buf());
+
+    let response = server.get("/api/projects/ghost/sessions").await;
+    response.assert_status_not_found();
+    let body: Value = response.json();
+    assert_eq!(body["code"], "project_not_found");
+    assert!(body["error"].as_str().unwrap().contains("ghost"));
+
+    let response = server.get("/api/projects/proj/sessions/missing").await;
+    response.assert_status_not_found();
+    let body: Value = response.json();
+    assert_eq!(body["code"], "session_not_found");
+    assert!(body["error"].as_str().unwrap().contains("missing"));
+    assert!(body["error"].as_str().unwrap().contains("proj"));
+
+    let response = server
+        .get("/api/projects/proj/sessions?since=yesterday")
+        .await;
+    response.assert_status_bad_request();
+    let body: Value = response.json();
+    assert_eq!(body["code"], "bad_request");
+}
+
+#[tokio::test]
+async fn test_empty_and_missing_projects_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    fs::create_dir_all(&projects_dir).unwrap();
+    let server = create_test_server(projects_dir.clone());
+
+    // Existing but empty is not an error
+    let response = server.get("/api/projects").await;
+    response.assert_status_ok();
+    let projects: Vec<Value> = response.json();
+    assert!(projects.is_empty());
+
+    fs::remove_dir_all(&projects_dir).unwrap();
+    let response = server.get("/api/projects").await;
+    response.assert_status_not_found();
+    let body: Value = response.json();
+    assert_eq!(body["code"], "projects_dir_missing");
+    assert!(body["error"].as_str().unwrap().contains("does not exist"));
+}
+
+#[test]
+fn test_safe_component() {
+    assert_eq!(
+        safe_component("-Users-harper-src-foo"),
+        Ok("-Users-harper-src-foo")
+    );
+    assert_eq!(safe_component("session-1.backup"), Ok("session-1.backup"));
+    for bad in ["", ".", "..", "../etc", "a/b", "a\\b", "..\\..\\windows"] {
+        assert!(safe_component(bad).is_err(), "{:?} should be rejected", bad);
+    }
+}
+
+#[tokio::test]
+async fn test_path_traversal_is_rejected() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    write_session(
+        &projects_dir,
+        "proj",
+        "s1",
+        &[json!({"type": "user", "uuid": "u1", "message": {"role": "user", "content": "hi"}})],
+    );
+    // A session file outside the projects directory that traversal would reach.
+    write_session(
+        temp_dir.path(),
+        "outside",
+        "secret",
+        &[json!({"type": "user", "message": {"role": "user", "content": "secret"}})],
+    );
+    // Sessions that exist but are never listed: archived, and nested deeper
+    // than the default scan depth.
+    for hidden in ["archived", "2024-06-01"] {
+        fs::create_dir_all(projects_dir.join("proj").join(hidden)).unwrap();
+        fs::write(
+            projects_dir.join("proj").join(hidden).join("old.jsonl"),
+            json!({"type": "user", "uuid": "u2", "message": {"role": "user", "content": "old"}})
+                .to_string(),
+        )
+        .unwrap();
+    }
+
+    let server = create_test_server(projects_dir);
+
+    for path in [
+        "/api/projects/..%2Foutside/sessions",
+        "/api/projects/..%2Foutside/stats",
+        "/api/projects/..%2Foutside/sessions/secret",
+        "/api/projects/proj/sessions/..%2F..%2Foutside%2Fsecret",
+        "/api/projects/proj/sessions/..%5C..%5Coutside%5Csecret/blocks",
+        "/api/projects/proj/sessions/..%2Fproj%2Fs1/entry/u1",
+        "/api/search?q=secret&project=..%2Foutside",
+    ] {
+        let response = server.get(path).await;
+        response.assert_status_bad_request();
+        let body: Value = response.json();
+        assert_eq!(body["code"], "invalid_path", "{}", path);
+    }
+
+    for path in [
+        "/api/projects/proj/sessions/archived%2Fold",
+        "/api/projects/proj/sessions/archived%2Fold/blocks",
+        "/api/projects/proj/sessions/2024-06-01%2Fold",
+        "/api/projects/proj/sessions/2024-06-01%2Fold/entry/u2",
+    ] {
+        server.get(path).await.assert_status_not_found();
+    }
+}
+
+#[tokio::test]
+async fn test_tools_endpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("tools.toml");
+    fs::write(
+        &config_path,
+        r#"
+[tools.Bash]
+icon = "🐚"
+
+[tools.mcp__deploy__ship]
+icon = "🚀"
+"#,
+    )
+    .unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf())
+        .unwrap()
+        .with_tool_config(ToolConfig::load(&config_path).unwrap());
+    let server = create_test_server_with_state(state);
+
+    let tools: Vec<Value> = server.get("/api/tools").await.json();
+    let find = |name: &str| tools.iter().find(|t| t["name"] == name).cloned().unwrap();
+
+    assert_eq!(find("Read")["icon"], "📖");
+    assert_eq!(find("Read")["kind"], "core");
+    assert_eq!(find("Bash")["icon"], "🐚");
+    assert_eq!(find("mcp__socialmedia__login")["kind"], "mcp");
+    assert_eq!(find("mcp__deploy__ship")["icon"], "🚀");
+    assert_eq!(find("mcp__deploy__ship")["kind"], "mcp");
+    assert_eq!(tools[0]["name"], "Bash");
+}
+
+#[tokio::test]
+async fn test_session_meta_endpoint() {
+    let temp_dir = TempDir::new().unwrap();
+    write_session(
+        temp_dir.path(),
+        "meta-project",
+        "s1",
+        &[
+            json!({"type": "summary", "summary": "Meta", "leafUuid": "x"}),
+            json!({"type": "user", "message": {"role": "user", "content": "go"}, "timestamp": "2024-01-15T10:00:00Z"}),
+            json!({
+                "type": "assistant",
+                "message": {
+                    "role": "assistant",
+                    "model": "claude-sonnet-4",
+                    "content": [{"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}}],
+                    "usage": {"input_tokens": 100, "output_tokens": 20}
+                },
+                "timestamp": "2024-01-15T10:00:05Z"
+            }),
+            json!({
+                "type": "assistant",
+                "isSidechain": true,
+                "message": {
+                    "role": "assistant",
+                    "model": "claude-haiku",
+                    "content": [{"type": "tool_use", "id": "t2", "name": "Read", "input": {"file_path": "a"}}],
+                    "usage": {"input_tokens": 5, "output_tokens": 1}
+                },
+                "timestamp": "2024-01-15T10:01:00Z"
+            }),
+            interleaved_assistant_entry(),
+            json!({"type": "user", "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "<tool_use_error>File does not exist.</tool_use_error>"},
+                {"type": "tool_result", "tool_use_id": "t2", "content": "ok"}
+            ]}}),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "meta-project",
+        "plain",
+        &[json!({"type": "user", "message": {"role": "user", "content": "hi"}})],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let response = server
+        .get("/api/projects/meta-project/sessions/s1/meta")
+        .await;
+    response.assert_status_ok();
+    let meta: Value = response.json();
+    assert_eq!(meta["session_id"], "s1");
+    assert_eq!(meta["message_count"], 5);
+    assert_eq!(meta["error_count"], 1);
+    assert_eq!(meta["token_usage"]["input_tokens"], 105);
+    assert_eq!(meta["token_usage"]["output_tokens"], 21);
+    assert_eq!(meta["tools_used"]["Bash"], 2);
+    assert_eq!(meta["tools_used"]["Read"], 2);
+    assert_eq!(meta["models"], json!(["claude-sonnet-4", "claude-haiku"]));
+    assert_eq!(meta["started_at"], "2024-01-15T10:00:00Z");
+    assert_eq!(meta["last_activity"], "2024-01-15T10:01:00Z");
+    assert_eq!(meta["has_sidechains"], true);
+
+    let plain: Value = server
+        .get("/api/projects/meta-project/sessions/plain/meta")
+        .await
+        .json();
+    assert_eq!(plain["has_sidechains"], false);
+    assert_eq!(plain["models"], json!([]));
+    assert_eq!(plain["started_at"], Value::Null);
+    assert_eq!(plain["error_count"], 0);
+
+    server
+        .get("/api/projects/meta-project/sessions/missing/meta")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_static_assets_are_embedded() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route("/static/*path", axum::routing::get(static_asset))
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let response = server.get("/static/live.html").await;
+    response.assert_status_ok();
+    assert_eq!(response.header("content-type"), "text/html; charset=utf-8");
+    let on_disk =
+        fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/static/live.html")).unwrap();
+    assert_eq!(response.text(), on_disk);
+
+    server
+        .get("/static/missing.js")
+        .await
+        .assert_status_not_found();
+    server
+        .get("/static/%2E%2E/Cargo.toml")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_healthz() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route("/healthz", axum::routing::get(healthz))
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let health: Value = server.get("/healthz").await.json();
+    assert_eq!(
+        health,
+        json!({"status": "ok", "projects_dir_exists": true, "watcher_alive": true})
+    );
+
+    // Still answers, but reports the directory going away
+    let projects_dir = temp_dir.path().to_path_buf();
+    drop(temp_dir);
+    assert!(!projects_dir.exists());
+    let health: Value = server.get("/healthz").await.json();
+    assert_eq!(health["status"], "ok");
+    assert_eq!(health["projects_dir_exists"], false);
+}
+
+#[tokio::test]
+async fn test_healthz_reports_stopped_session_reader() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let watch_manager = state.watch_manager.clone();
+    let app = axum::Router::new()
+        .route("/healthz", axum::routing::get(healthz))
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let health: Value = server.get("/healthz").await.json();
+    assert_eq!(health["watcher_alive"], true);
+
+    // The watcher still runs, but nothing reads what it reports
+    watch_manager.stop_session_reader();
+    let mut alive = true;
+    for _ in 0..100 {
+        let health: Value = server.get("/healthz").await.json();
+        alive = health["watcher_alive"].as_bool().unwrap();
+        if !alive {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert!(!alive);
+}
+
+#[tokio::test]
+async fn test_session_titles_from_linked_summaries_and_prompts() {
+    let temp_dir = TempDir::new().unwrap();
+    let user = |uuid: &str, text: &str, ts: &str| json!({"type": "user", "uuid": uuid, "message": {"role": "user", "content": text}, "timestamp": ts});
+    // The original conversation, summarised only inside its continuation
+    write_session(
+        temp_dir.path(),
+        "linked",
+        "original",
+        &[
+            user("a1", "Fix the parser", "2024-01-10T10:00:00Z"),
+            user("a2", "Now the lexer", "2024-01-10T10:05:00Z"),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "linked",
+        "continued",
+        &[
+            json!({"type": "summary", "summary": "Parser and lexer fixes", "leafUuid": "a2"}),
+            json!({"type": "summary", "summary": "Release preparation", "leafUuid": "b1"}),
+            user("b1", "Let's cut a release", "2024-01-11T10:00:00Z"),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "linked",
+        "untitled",
+        &[
+            json!({"type": "user", "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t", "content": "noise"}]}, "timestamp": "2024-01-12T10:00:00Z"}),
+            user("c1", "  Please refactor the\n\n authentication   module so that tokens are refreshed automatically", "2024-01-12T10:01:00Z"),
+        ],
+    );
+    write_session(
+        temp_dir.path(),
+        "linked",
+        "empty",
+        &[
+            json!({"type": "assistant", "message": {"role": "assistant", "content": "hello"}, "timestamp": "2024-01-13T10:00:00Z"}),
+        ],
+    );
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let sessions: Vec<Value> = server.get("/api/projects/linked/sessions").await.json();
+    let title = |id: &str| {
+        sessions.iter().find(|s| s["id"] == id).unwrap()["summary"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    assert_eq!(title("original"), "Parser and lexer fixes");
+    assert_eq!(title("continued"), "Release preparation");
+    let prompt = title("untitled");
+    assert!(
+        prompt.starts_with("Please refactor the authentication module so that"),
+        "{}",
+        prompt
+    );
+    assert!(prompt.ends_with('…'), "{}", prompt);
+    assert_eq!(prompt.chars().count(), 60);
+    assert_eq!(title("empty"), "empty");
+
+    // The linked summary still applies when its session is filtered out
+    let sessions: Vec<Value> = server
+        .get("/api/projects/linked/sessions?until=2024-01-10T23:59:59Z")
+        .await
+        .json();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0]["summary"], "Parser and lexer fixes");
+}
+
+#[tokio::test]
+async fn test_export_session_markdown_streams_large_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let padding = "lorem ipsum ".repeat(50);
+    let entries: Vec<Value> = (0..5_000)
+        .map(|i| {
+            let role = if i % 2 == 0 { "user" } else { "assistant" };
+            json!({
+                "type": role,
+                "uuid": format!("u{}", i),
+                "message": {"role": role, "content": format!("Message {} {}", i, padding)},
+                "timestamp": "2024-01-15T10:00:00Z"
+            })
+        })
+        .collect();
+    write_session(temp_dir.path(), "big", "huge-session", &entries);
+
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let response = server
+        .get("/api/projects/big/sessions/huge-session/export.md")
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.header("content-disposition"),
+        "attachment; filename=\"huge-session.md\""
+    );
+    assert_eq!(
+        response.header("content-type"),
+        "text/markdown; charset=utf-8"
+    );
+
+    let markdown = response.text();
+    assert!(markdown.starts_with("# Claude Code Conversation Export"));
+    assert!(markdown.contains("**Project:** big"));
+    assert!(markdown.contains("**Session:** huge-session"));
+    // Larger than a single chunk, and nothing lost at the seams
+    assert!(markdown.len() > 1_000_000);
+    assert!(markdown.contains("Message 0 lorem"));
+    assert!(markdown.contains("Message 4999 lorem"));
+    assert_eq!(markdown.matches("Message ").count(), 5_000);
+
+    let transcript = server
+        .get("/api/projects/big/sessions/huge-session/export.md")
+        .add_query_param("mode", "conversation")
+        .await;
+    transcript.assert_status_ok();
+    assert_eq!(transcript.text().matches("Message ").count(), 5_000);
+    server
+        .get("/api/projects/big/sessions/huge-session/export.md")
+        .add_query_param("mode", "everything")
+        .await
+        .assert_status_bad_request();
+
+    server
+        .get("/api/projects/big/sessions/missing/export.md")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_export_session_markdown_is_sent_in_bounded_chunks() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let temp_dir = TempDir::new().unwrap();
+    let padding = "lorem ipsum ".repeat(50);
+    let entries: Vec<Value> = (0..2_000)
+        .map(|i| {
+            json!({
+                "type": "user",
+                "message": {"role": "user", "content": format!("Message {} {}", i, padding)},
+                "timestamp": "2024-01-15T10:00:00Z"
+            })
+        })
+        .collect();
+    write_session(temp_dir.path(), "big", "huge-session", &entries);
+
+    // Chunk boundaries only survive over a real connection
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route(
+            "/api/projects/:project/sessions/:session/export.md",
+            axum::routing::get(export_session_markdown),
+        )
+        .with_state(state);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            b"GET /api/projects/big/sessions/huge-session/export.md HTTP/1.1\r\n\
+              Host: localhost\r\nConnection: close\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response).await.unwrap();
+
+    let split = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+    let head = String::from_utf8_lossy(&response[..split]).to_lowercase();
+    assert!(head.starts_with("http/1.1 200"));
+    assert!(head.contains("transfer-encoding: chunked"));
+
+    let mut body = &response[split + 4..];
+    let mut chunks = Vec::new();
+    loop {
+        let line_end = body.windows(2).position(|w| w == b"\r\n").unwrap();
+        let size =
+            usize::from_str_radix(std::str::from_utf8(&body[..line_end]).unwrap(), 16).unwrap();
+        if size == 0 {
+            break;
+        }
+        chunks.push(size);
+        body = &body[line_end + 2 + size + 2..];
+    }
+
+    // Sent as it is written, 64 KiB at a time, rather than all at once
+    let total: usize = chunks.iter().sum();
+    assert!(total > 1_000_000);
+    assert!(chunks.len() > 10, "only {} chunks", chunks.len());
+    assert!(chunks.iter().all(|&size| size < 64 * 1024 + 4096));
+}
+
+#[tokio::test]
+async fn test_scan_depth_finds_nested_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let user = |text: &str| json!({"type": "user", "message": {"role": "user", "content": text}, "timestamp": "2024-06-01T10:00:00Z"});
+    write_session(temp_dir.path(), "nested", "flat", &[user("At the top")]);
+    write_session(
+        &temp_dir.path().join("nested"),
+        "2024-06-01",
+        "deep",
+        &[user("In a dated folder")],
+    );
+
+    // The default only looks directly inside the project
+    let server = create_test_server(temp_dir.path().to_path_buf());
+    let sessions: Vec<Value> = server.get("/api/projects/nested/sessions").await.json();
+    let ids: Vec<&str> = sessions.iter().map(|s| s["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, ["flat"]);
+
+    let state = AppState::new(temp_dir.path().to_path_buf())
+        .unwrap()
+        .with_scan_depth(2);
+    let server = create_test_server_with_state(state);
+    let sessions: Vec<Value> = server.get("/api/projects/nested/sessions").await.json();
+    let mut ids: Vec<&str> = sessions.iter().map(|s| s["id"].as_str().unwrap()).collect();
+    ids.sort();
+    assert_eq!(ids, ["2024-06-01/deep", "flat"]);
+
+    let entries: Vec<Value> = server
+        .get("/api/projects/nested/sessions/2024-06-01%2Fdeep")
+        .await
+        .json();
+    assert_eq!(entries[0]["message"]["content"], "In a dated folder");
+    server
+        .get("/api/projects/nested/sessions/2024-06-01%2F..%2F..%2Fsecret")
+        .await
+        .assert_status_bad_request();
+
+    let projects: Vec<Value> = server.get("/api/projects").await.json();
+    assert_eq!(projects[0]["session_count"], 2);
+}
+
+#[tokio::test]
+async fn test_scan_depth_covers_stats_and_search() {
+    let temp_dir = TempDir::new().unwrap();
+    let user = |text: &str| json!({"type": "user", "message": {"role": "user", "content": text}, "timestamp": "2024-06-01T10:00:00Z"});
+    write_session(temp_dir.path(), "nested", "flat", &[user("At the top")]);
+    write_session(
+        &temp_dir.path().join("nested"),
+        "2024-06-01",
+        "deep",
+        &[user("A needle in a dated folder"), user("Another message")],
+    );
+
+    let state = AppState::new(temp_dir.path().to_path_buf())
+        .unwrap()
+        .with_scan_depth(2);
+    let server = create_test_server_with_state(state);
+
+    let stats: Value = server.get("/api/projects/nested/stats").await.json();
+    assert_eq!(stats["total_sessions"], 2);
+    assert_eq!(stats["total_messages"], 3);
+
+    let hits: Vec<Value> = server
+        .get("/api/search")
+        .add_query_param("q", "needle")
+        .await
+        .json();
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0]["project"], "nested");
+    assert_eq!(hits[0]["session"], "2024-06-01/deep");
+}
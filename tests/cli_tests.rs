@@ -1,14 +1,37 @@
 // ABOUTME: CLI argument parsing unit tests
 // ABOUTME: Tests command-line interface structure and parsing logic
 
-use clap::{CommandFactory, Parser};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::net::IpAddr;
 use std::path::PathBuf;
 
-// Replicate the CLI struct from main.rs for testing
+// Replicate the CLI structs from main.rs for testing
 #[derive(Parser, Debug)]
 #[clap(name = "cc-log-viewer")]
 #[clap(about = "Claude Code log viewer - Web interface for viewing conversation logs")]
+#[clap(args_conflicts_with_subcommands = true)]
 struct TestCli {
+    #[clap(subcommand)]
+    command: Option<TestCommand>,
+
+    #[clap(flatten)]
+    serve: TestServeArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum TestCommand {
+    /// Serve the web interface (the default)
+    Serve(TestServeArgs),
+    /// Browse logs in the terminal UI
+    Tui(TestTuiArgs),
+    /// Write a single session to stdout
+    Export(TestExportArgs),
+    /// Write every session of every project into a directory
+    ExportAll(TestExportAllArgs),
+}
+
+#[derive(Args, Debug)]
+struct TestServeArgs {
     #[clap(
         help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
     )]
@@ -17,61 +40,341 @@ struct TestCli {
     #[clap(short, long, default_value = "2006", help = "Port to serve on")]
     port: u16,
 
-    #[clap(long, help = "Use terminal UI instead of web interface")]
-    tui: bool,
+    #[clap(
+        long,
+        default_value = "127.0.0.1",
+        help = "Address to bind to (use 0.0.0.0 to allow LAN access)"
+    )]
+    host: IpAddr,
+
+    #[clap(long, help = "Open the viewer in a browser once the server starts")]
+    open: bool,
+
+    #[clap(
+        long,
+        value_name = "TOKEN",
+        help = "Require this token on every request (or set CC_LOG_VIEWER_TOKEN)"
+    )]
+    auth_token: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "TOML or JSON file defining custom tool handlers"
+    )]
+    tool_config: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct TestTuiArgs {
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "TOML or JSON file defining custom tool handlers"
+    )]
+    tool_config: Option<PathBuf>,
+}
+
+#[derive(Args, Debug)]
+struct TestExportArgs {
+    #[clap(value_name = "PROJECT/SESSION", help = "Session to export")]
+    session: String,
+
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(long, value_enum, default_value = "markdown", help = "Output format")]
+    format: TestOutputFormat,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+
+    #[clap(
+        long,
+        help = "Export every session of PROJECT as one Markdown document with a table of contents"
+    )]
+    combine: bool,
+
+    #[clap(
+        long,
+        conflicts_with = "combine",
+        help = "Start Markdown exports with YAML frontmatter (project, session, tools, tokens)"
+    )]
+    frontmatter: bool,
+
+    #[clap(long, help = "Include extra detail such as todo ids")]
+    verbose: bool,
+
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value = "100000",
+        help = "Cut tool output longer than this many bytes"
+    )]
+    max_output_bytes: usize,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or after this time (e.g. 2024-01-15T00:00:00Z)"
+    )]
+    since: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or before this time"
+    )]
+    until: Option<String>,
+}
+
+#[derive(Args, Debug)]
+struct TestExportAllArgs {
+    #[clap(
+        help = "Path to projects directory containing log files (defaults to ~/.claude/projects/)"
+    )]
+    projects_dir: Option<PathBuf>,
+
+    #[clap(
+        short,
+        long,
+        value_name = "DIR",
+        help = "Directory to write <project>/<session> files and index.json into"
+    )]
+    output: PathBuf,
+
+    #[clap(long, value_enum, default_value = "markdown", help = "Output format")]
+    format: TestOutputFormat,
+
+    #[clap(long, help = "Mask API keys, tokens and other secrets")]
+    redact: bool,
+
+    #[clap(
+        long,
+        help = "Start Markdown exports with YAML frontmatter (project, session, tools, tokens)"
+    )]
+    frontmatter: bool,
+
+    #[clap(long, help = "Include extra detail such as todo ids")]
+    verbose: bool,
+
+    #[clap(
+        long,
+        value_name = "BYTES",
+        default_value = "100000",
+        help = "Cut tool output longer than this many bytes"
+    )]
+    max_output_bytes: usize,
+
+    #[clap(long, help = "Skip sessions whose exported file is newer than the log")]
+    update: bool,
+
+    #[clap(
+        long,
+        help = "List what would be exported or skipped without writing anything"
+    )]
+    dry_run: bool,
+
+    #[clap(
+        long,
+        value_enum,
+        default_value = "archive",
+        help = "What to do with exports whose session log no longer exists"
+    )]
+    orphans: TestOrphanPolicy,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or after this time (e.g. 2024-01-15T00:00:00Z)"
+    )]
+    since: Option<String>,
+
+    #[clap(
+        long,
+        value_name = "RFC3339",
+        help = "Only export sessions started at or before this time"
+    )]
+    until: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum TestOrphanPolicy {
+    Archive,
+    Delete,
+    Keep,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, ValueEnum)]
+enum TestOutputFormat {
+    Markdown,
+    Html,
+    Json,
 }
 
 #[test]
-fn test_cli_tui_flag_parsing() {
-    // Test --tui flag
-    let cli = TestCli::try_parse_from(["cc-log-viewer", "--tui"]).unwrap();
-    assert!(cli.tui);
-    assert_eq!(cli.port, 2006); // Default port
-    assert!(cli.projects_dir.is_none()); // No projects dir specified
+fn test_cli_subcommand_parsing() {
+    // No subcommand means serve with the top-level options
+    let cli = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
+    assert!(cli.command.is_none());
+    assert_eq!(cli.serve.port, 2006); // Default port
+    assert!(cli.serve.projects_dir.is_none()); // No projects dir specified
+
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "serve", "-p", "3000"]).unwrap();
+    assert!(matches!(cli.command, Some(TestCommand::Serve(ref args)) if args.port == 3000));
 
-    // Test without --tui flag (default is false)
-    let cli_no_tui = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
-    assert!(!cli_no_tui.tui);
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "tui", "/my/projects"]).unwrap();
+    match cli.command {
+        Some(TestCommand::Tui(args)) => {
+            assert_eq!(args.projects_dir, Some(PathBuf::from("/my/projects")))
+        }
+        _ => panic!("expected tui subcommand"),
+    }
+
+    // Serve-only options don't belong to other modes
+    assert!(TestCli::try_parse_from(["cc-log-viewer", "tui", "--port", "9000"]).is_err());
 }
 
 #[test]
 fn test_cli_port_flag_parsing() {
     // Test default port
     let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
-    assert_eq!(cli_default.port, 2006);
+    assert_eq!(cli_default.serve.port, 2006);
 
     // Test custom port with short flag
     let cli_short = TestCli::try_parse_from(["cc-log-viewer", "-p", "8080"]).unwrap();
-    assert_eq!(cli_short.port, 8080);
+    assert_eq!(cli_short.serve.port, 8080);
 
     // Test custom port with long flag
     let cli_long = TestCli::try_parse_from(["cc-log-viewer", "--port", "3000"]).unwrap();
-    assert_eq!(cli_long.port, 3000);
+    assert_eq!(cli_long.serve.port, 3000);
 }
 
 #[test]
 fn test_cli_projects_dir_parsing() {
     // Test without projects dir (should be None)
     let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
-    assert!(cli_default.projects_dir.is_none());
+    assert!(cli_default.serve.projects_dir.is_none());
 
     // Test with projects dir
     let cli_with_dir = TestCli::try_parse_from(["cc-log-viewer", "/custom/path"]).unwrap();
     assert_eq!(
-        cli_with_dir.projects_dir,
+        cli_with_dir.serve.projects_dir,
         Some(PathBuf::from("/custom/path"))
     );
 }
 
+#[test]
+fn test_cli_host_parsing() {
+    let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
+    assert_eq!(
+        cli_default.serve.host,
+        "127.0.0.1".parse::<IpAddr>().unwrap()
+    );
+
+    let cli_lan = TestCli::try_parse_from(["cc-log-viewer", "--host", "0.0.0.0"]).unwrap();
+    assert!(cli_lan.serve.host.is_unspecified());
+
+    let cli_v6 = TestCli::try_parse_from(["cc-log-viewer", "--host", "::1"]).unwrap();
+    assert!(cli_v6.serve.host.is_loopback());
+
+    let invalid = TestCli::try_parse_from(["cc-log-viewer", "--host", "not-an-address"]);
+    assert!(invalid.is_err());
+}
+
+#[test]
+fn test_cli_auth_token_parsing() {
+    let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
+    assert!(cli_default.serve.auth_token.is_none());
+
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "--auth-token", "s3cret"]).unwrap();
+    assert_eq!(cli.serve.auth_token.as_deref(), Some("s3cret"));
+}
+
+#[test]
+fn test_cli_open_flag_parsing() {
+    let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
+    assert!(!cli_default.serve.open);
+
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "--open", "-p", "3000"]).unwrap();
+    assert!(cli.serve.open);
+    assert_eq!(cli.serve.port, 3000);
+}
+
+#[test]
+fn test_cli_tool_config_parsing() {
+    let cli_default = TestCli::try_parse_from(["cc-log-viewer"]).unwrap();
+    assert!(cli_default.serve.tool_config.is_none());
+
+    let cli =
+        TestCli::try_parse_from(["cc-log-viewer", "--tool-config", "/etc/tools.toml"]).unwrap();
+    assert_eq!(
+        cli.serve.tool_config,
+        Some(PathBuf::from("/etc/tools.toml"))
+    );
+}
+
+#[test]
+fn test_cli_export_parsing() {
+    let cli = TestCli::try_parse_from([
+        "cc-log-viewer",
+        "export",
+        "my-project/session-1",
+        "/my/projects",
+        "--format",
+        "json",
+    ])
+    .unwrap();
+    let Some(TestCommand::Export(args)) = cli.command else {
+        panic!("expected export subcommand");
+    };
+    assert_eq!(args.session, "my-project/session-1");
+    assert_eq!(args.projects_dir, Some(PathBuf::from("/my/projects")));
+    assert_eq!(args.format, TestOutputFormat::Json);
+
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "export", "p/s"]).unwrap();
+    let Some(TestCommand::Export(args)) = cli.command else {
+        panic!("expected export subcommand");
+    };
+    assert_eq!(args.format, TestOutputFormat::Markdown);
+    assert!(!args.combine && !args.frontmatter);
+
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "export", "p/s", "--frontmatter"]).unwrap();
+    assert!(matches!(cli.command, Some(TestCommand::Export(ref args)) if args.frontmatter));
+    assert!(TestCli::try_parse_from([
+        "cc-log-viewer",
+        "export",
+        "p",
+        "--combine",
+        "--frontmatter"
+    ])
+    .is_err());
+
+    // The session is required, and export flags only exist under export
+    assert!(TestCli::try_parse_from(["cc-log-viewer", "export"]).is_err());
+    assert!(
+        TestCli::try_parse_from(["cc-log-viewer", "export", "p/s", "--format", "pdf"]).is_err()
+    );
+    assert!(TestCli::try_parse_from(["cc-log-viewer", "--redact"]).is_err());
+}
+
 #[test]
 fn test_cli_combined_flags() {
     // Test all flags together
-    let cli = TestCli::try_parse_from(["cc-log-viewer", "--tui", "--port", "9000", "/my/projects"])
-        .unwrap();
+    let cli =
+        TestCli::try_parse_from(["cc-log-viewer", "--open", "--port", "9000", "/my/projects"])
+            .unwrap();
 
-    assert!(cli.tui);
-    assert_eq!(cli.port, 9000);
-    assert_eq!(cli.projects_dir, Some(PathBuf::from("/my/projects")));
+    assert!(cli.serve.open);
+    assert_eq!(cli.serve.port, 9000);
+    assert_eq!(cli.serve.projects_dir, Some(PathBuf::from("/my/projects")));
 }
 
 #[test]
@@ -82,7 +385,7 @@ fn test_cli_invalid_port_handling() {
 
     // Test port out of range (0 is technically valid for u16 but may not be useful)
     let cli_zero = TestCli::try_parse_from(["cc-log-viewer", "--port", "0"]).unwrap();
-    assert_eq!(cli_zero.port, 0);
+    assert_eq!(cli_zero.serve.port, 0);
 }
 
 #[test]
@@ -92,8 +395,9 @@ fn test_cli_help_generation() {
     let help_text = app.render_help().to_string();
 
     // Verify key elements are in help text
-    assert!(help_text.contains("--tui"));
-    assert!(help_text.contains("Use terminal UI instead of web interface"));
+    assert!(help_text.contains("tui"));
+    assert!(help_text.contains("Browse logs in the terminal UI"));
+    assert!(help_text.contains("export"));
     assert!(help_text.contains("--port"));
     assert!(help_text.contains("Port to serve on"));
     assert!(help_text.contains("[default: 2006]"));
@@ -104,11 +408,11 @@ fn test_cli_help_generation() {
 #[test]
 fn test_cli_flag_order_independence() {
     // Test that flag order doesn't matter
-    let cli1 = TestCli::try_parse_from(["cc-log-viewer", "--tui", "--port", "8080"]).unwrap();
-    let cli2 = TestCli::try_parse_from(["cc-log-viewer", "--port", "8080", "--tui"]).unwrap();
+    let cli1 = TestCli::try_parse_from(["cc-log-viewer", "--open", "--port", "8080"]).unwrap();
+    let cli2 = TestCli::try_parse_from(["cc-log-viewer", "--port", "8080", "--open"]).unwrap();
 
-    assert_eq!(cli1.tui, cli2.tui);
-    assert_eq!(cli1.port, cli2.port);
+    assert_eq!(cli1.serve.open, cli2.serve.open);
+    assert_eq!(cli1.serve.port, cli2.serve.port);
 }
 
 #[test]
@@ -117,7 +421,7 @@ fn test_cli_short_vs_long_flags() {
     let cli_short = TestCli::try_parse_from(["cc-log-viewer", "-p", "5000"]).unwrap();
     let cli_long = TestCli::try_parse_from(["cc-log-viewer", "--port", "5000"]).unwrap();
 
-    assert_eq!(cli_short.port, cli_long.port);
+    assert_eq!(cli_short.serve.port, cli_long.serve.port);
 }
 
 #[test]
@@ -138,7 +442,7 @@ fn test_cli_unknown_flag_handling() {
 #[test]
 fn test_cli_version_info_structure() {
     // Test that version information can be accessed
-    let mut app = TestCli::command();
+    let app = TestCli::command();
 
     // Should have name and about text
     assert_eq!(app.get_name(), "cc-log-viewer");
@@ -147,3 +451,814 @@ fn test_cli_version_info_structure() {
     assert!(about.contains("Claude Code log viewer"));
     assert!(about.contains("Web interface for viewing conversation logs"));
 }
+
+#[test]
+fn test_export_writes_to_stdout() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("my-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("session-1.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":"Hello there"},"uuid":"u1"}"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .arg("export")
+            .args(args)
+            .arg(temp_dir.path())
+            .output()
+            .unwrap()
+    };
+
+    let markdown = run(&["my-project/session-1"]);
+    assert!(markdown.status.success());
+    let stdout = String::from_utf8_lossy(&markdown.stdout);
+    assert!(stdout.starts_with("# Claude Code Conversation Export"));
+    assert!(stdout.contains("Hello there"));
+
+    let json = run(&["my-project/session-1", "--format", "json"]);
+    assert!(json.status.success());
+    let entries: serde_json::Value = serde_json::from_slice(&json.stdout).unwrap();
+    assert_eq!(entries[0]["uuid"], "u1");
+
+    let missing = run(&["my-project/nope"]);
+    assert!(!missing.status.success());
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("Session file not found"));
+}
+
+#[test]
+fn test_export_combine_writes_one_document() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("my-project");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    std::fs::write(
+        project_dir.join("later.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":"Second"},"uuid":"u2","timestamp":"2024-01-16T10:00:00Z"}"#,
+    )
+    .unwrap();
+    std::fs::write(
+        project_dir.join("earlier.jsonl"),
+        r#"{"type":"user","message":{"role":"user","content":"First"},"uuid":"u1","timestamp":"2024-01-15T10:00:00Z"}"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .arg("export")
+            .args(args)
+            .arg(temp_dir.path())
+            .output()
+            .unwrap()
+    };
+
+    let output = run(&["my-project", "--combine"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.starts_with("# Project my-project"));
+    let first = stdout.find("## Session earlier").unwrap();
+    let second = stdout.find("## Session later").unwrap();
+    assert!(first < second);
+    assert!(stdout.contains("[Session earlier](#session-earlier)"));
+
+    let html = run(&["my-project", "--combine", "--format", "html"]);
+    assert!(!html.status.success());
+}
+
+#[test]
+fn test_export_all_writes_manifest() {
+    let projects = tempfile::TempDir::new().unwrap();
+    let output = tempfile::TempDir::new().unwrap();
+    for (project, session, ts) in [
+        ("alpha", "s1", "2024-01-15T10:00:00Z"),
+        ("alpha", "s2", "2024-01-16T10:00:00Z"),
+        ("beta", "s3", "2024-01-17T10:00:00Z"),
+    ] {
+        let dir = projects.path().join(project);
+        std::fs::create_dir_all(&dir).unwrap();
+        let lines = [
+            serde_json::json!({"type": "user", "uuid": "u1", "timestamp": ts,
+                "message": {"role": "user", "content": "Hi"}}),
+            serde_json::json!({"type": "assistant", "uuid": "a1", "timestamp": ts,
+                "message": {"role": "assistant", "content": "Hello"}}),
+        ];
+        let content: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        std::fs::write(dir.join(format!("{}.jsonl", session)), content.join("\n")).unwrap();
+    }
+
+    // export-all requires somewhere to write
+    let cli = TestCli::try_parse_from(["cc-log-viewer", "export-all"]);
+    assert!(cli.is_err());
+
+    let result = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["export-all", "--output"])
+        .arg(output.path())
+        .arg(projects.path())
+        .output()
+        .unwrap();
+    assert!(result.status.success());
+    assert!(String::from_utf8_lossy(&result.stderr).contains("Exported 3 sessions"));
+    assert!(output.path().join("alpha").join("s1.md").is_file());
+    assert!(output.path().join("beta").join("s3.md").is_file());
+
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.path().join("index.json")).unwrap()).unwrap();
+    assert_eq!(manifest["version"], env!("CARGO_PKG_VERSION"));
+    assert!(manifest["generated_at"].is_string());
+    let files = manifest["files"].as_array().unwrap();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0]["project"], "alpha");
+    assert_eq!(files[0]["session_id"], "s1");
+    assert_eq!(files[0]["path"], "alpha/s1.md");
+    assert_eq!(files[0]["message_count"], 2);
+    assert_eq!(files[0]["timestamp"], "2024-01-15T10:00:00Z");
+    assert_eq!(files[2]["path"], "beta/s3.md");
+
+    // Only the session whose log changed since the last export is stale
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    let changed = projects.path().join("beta").join("s3.jsonl");
+    let content = std::fs::read_to_string(&changed).unwrap();
+    std::fs::write(&changed, content).unwrap();
+    let s3_before = std::fs::metadata(output.path().join("beta").join("s3.md"))
+        .unwrap()
+        .modified()
+        .unwrap();
+
+    let run_update = |extra: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .args(["export-all", "--update", "--output"])
+            .arg(output.path())
+            .args(extra)
+            .arg(projects.path())
+            .output()
+            .unwrap()
+    };
+
+    let dry = run_update(&["--dry-run"]);
+    assert!(dry.status.success());
+    let stdout = String::from_utf8_lossy(&dry.stdout);
+    assert!(stdout.contains("would export beta/s3.md"));
+    assert!(stdout.contains("would skip alpha/s1.md"));
+    assert!(stdout.contains("would skip alpha/s2.md"));
+    assert!(String::from_utf8_lossy(&dry.stderr).contains("would export 1 sessions, skip 2"));
+    let s3_after_dry = std::fs::metadata(output.path().join("beta").join("s3.md"))
+        .unwrap()
+        .modified()
+        .unwrap();
+    assert_eq!(s3_before, s3_after_dry);
+
+    // Rows for unchanged sessions come from the last manifest, not their logs
+    let manifest_path = output.path().join("index.json");
+    let mut manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+    manifest["files"][0]["message_count"] = 99.into();
+    manifest["files"][2]["message_count"] = 99.into();
+    std::fs::write(&manifest_path, manifest.to_string()).unwrap();
+
+    let real = run_update(&[]);
+    assert!(real.status.success());
+    assert!(String::from_utf8_lossy(&real.stderr).contains("Exported 1 sessions"));
+    assert!(String::from_utf8_lossy(&real.stderr).contains("(2 unchanged"));
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(&manifest_path).unwrap()).unwrap();
+    let files = manifest["files"].as_array().unwrap();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[0]["path"], "alpha/s1.md");
+    assert_eq!(files[0]["message_count"], 99);
+    assert_eq!(files[0]["timestamp"], "2024-01-15T10:00:00Z");
+    assert_eq!(files[2]["path"], "beta/s3.md");
+    assert_eq!(files[2]["message_count"], 2);
+
+    // Exports of deleted sessions are archived by default, or deleted on request
+    std::fs::remove_file(projects.path().join("alpha").join("s2.jsonl")).unwrap();
+    let archived = run_update(&[]);
+    assert!(String::from_utf8_lossy(&archived.stderr).contains("1 orphaned archived"));
+    assert!(!output.path().join("alpha").join("s2.md").exists());
+    assert!(output
+        .path()
+        .join("archived")
+        .join("alpha")
+        .join("s2.md")
+        .is_file());
+
+    std::fs::remove_file(projects.path().join("alpha").join("s1.jsonl")).unwrap();
+    let dry = run_update(&["--orphans", "delete", "--dry-run"]);
+    assert!(String::from_utf8_lossy(&dry.stdout).contains("would delete alpha/s1.md"));
+    assert!(output.path().join("alpha").join("s1.md").is_file());
+    let deleted = run_update(&["--orphans", "delete"]);
+    assert!(String::from_utf8_lossy(&deleted.stderr).contains("1 orphaned deleted"));
+    assert!(!output.path().join("alpha").join("s1.md").exists());
+    assert!(!output
+        .path()
+        .join("archived")
+        .join("alpha")
+        .join("s1.md")
+        .exists());
+}
+
+#[test]
+fn test_projects_dir_resolution_order() {
+    let env_dir = tempfile::TempDir::new().unwrap();
+    let home_dir = tempfile::TempDir::new().unwrap();
+    let config_home = tempfile::TempDir::new().unwrap();
+
+    let session = r#"{"type":"user","message":{"role":"user","content":"Hi"},"uuid":"u1"}"#;
+    std::fs::create_dir_all(env_dir.path().join("p")).unwrap();
+    std::fs::write(env_dir.path().join("p").join("s.jsonl"), session).unwrap();
+    let home_projects = home_dir.path().join(".claude").join("projects");
+    std::fs::create_dir_all(home_projects.join("p")).unwrap();
+    std::fs::write(home_projects.join("p").join("s.jsonl"), session).unwrap();
+
+    let run = |env_override: Option<&std::path::Path>| {
+        let mut cmd = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"));
+        cmd.args(["export", "p/s"])
+            .env("HOME", home_dir.path())
+            .env("XDG_CONFIG_HOME", config_home.path())
+            .env_remove("CC_LOG_VIEWER_DIR");
+        if let Some(dir) = env_override {
+            cmd.env("CC_LOG_VIEWER_DIR", dir);
+        }
+        cmd.output().unwrap()
+    };
+
+    let from_env = run(Some(env_dir.path()));
+    assert!(from_env.status.success());
+    assert!(String::from_utf8_lossy(&from_env.stderr).contains("from CC_LOG_VIEWER_DIR"));
+
+    let from_home = run(None);
+    assert!(from_home.status.success());
+    let stderr = String::from_utf8_lossy(&from_home.stderr);
+    assert!(stderr.contains("from default location"));
+    assert!(stderr.contains(&home_projects.display().to_string()));
+}
+
+#[test]
+fn test_export_large_project_streams_to_completion() {
+    let projects = tempfile::TempDir::new().unwrap();
+    let output = tempfile::TempDir::new().unwrap();
+    let dir = projects.path().join("big-project");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    // One very large session alongside a small one
+    let mut big = String::new();
+    for i in 0..20_000 {
+        let entry = serde_json::json!({
+            "type": if i % 2 == 0 { "user" } else { "assistant" },
+            "uuid": format!("u{}", i),
+            "timestamp": "2024-01-15T10:00:00Z",
+            "message": {"role": "user", "content": format!("message {} {}", i, "x".repeat(200))}
+        });
+        big.push_str(&entry.to_string());
+        big.push('\n');
+    }
+    std::fs::write(dir.join("big.jsonl"), big).unwrap();
+    std::fs::write(
+        dir.join("small.jsonl"),
+        serde_json::json!({"type": "user", "timestamp": "2024-01-16T10:00:00Z",
+            "message": {"role": "user", "content": "last session"}})
+        .to_string(),
+    )
+    .unwrap();
+
+    let result = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["export-all", "--output"])
+        .arg(output.path())
+        .arg(projects.path())
+        .output()
+        .unwrap();
+    assert!(result.status.success(), "{:?}", result);
+    let exported = std::fs::read_to_string(output.path().join("big-project/big.md")).unwrap();
+    assert!(exported.contains("message 0 "));
+    assert!(exported.contains("message 19999 "));
+
+    let combined = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["export", "big-project", "--combine"])
+        .arg(projects.path())
+        .output()
+        .unwrap();
+    assert!(combined.status.success(), "{:?}", combined.status);
+    let combined = String::from_utf8(combined.stdout).unwrap();
+    assert!(combined.contains("message 19999 "));
+    let big_at = combined.find("## Session big").unwrap();
+    let small_at = combined.find("## Session small").unwrap();
+    assert!(big_at < small_at);
+    assert!(combined.trim_end().ends_with("last session"));
+}
+
+#[test]
+fn test_tail_prints_recent_then_new_entries() {
+    use std::io::{BufRead, Write};
+
+    let projects = tempfile::TempDir::new().unwrap();
+    let dir = projects.path().join("proj");
+    std::fs::create_dir_all(&dir).unwrap();
+    let user = |text: &str| {
+        serde_json::json!({"type": "user", "timestamp": "2024-01-15T10:00:00Z",
+            "message": {"role": "user", "content": text}})
+        .to_string()
+            + "\n"
+    };
+    std::fs::write(
+        dir.join("s.jsonl"),
+        user("first") + &user("second") + &user("third"),
+    )
+    .unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["tail", "proj/s", "-n", "2", "--format", "json"])
+        .arg(projects.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+    let next_text = || {
+        let line = rx
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("tail printed nothing");
+        let entry: serde_json::Value = serde_json::from_str(&line).unwrap();
+        entry["message"]["content"].as_str().unwrap().to_string()
+    };
+
+    assert_eq!(next_text(), "second");
+    assert_eq!(next_text(), "third");
+
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    let mut file = std::fs::OpenOptions::new()
+        .append(true)
+        .open(dir.join("s.jsonl"))
+        .unwrap();
+    file.write_all(user("fourth").as_bytes()).unwrap();
+    std::fs::write(dir.join("other.jsonl"), user("elsewhere")).unwrap();
+
+    assert_eq!(next_text(), "fourth");
+    assert!(rx
+        .recv_timeout(std::time::Duration::from_millis(500))
+        .is_err());
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_watch_follows_most_recent_session() {
+    use std::io::{BufRead, Write};
+
+    let projects = tempfile::TempDir::new().unwrap();
+    let user = |text: &str| {
+        serde_json::json!({"type": "user", "timestamp": "2024-01-15T10:00:00Z",
+            "message": {"role": "user", "content": text}})
+        .to_string()
+            + "\n"
+    };
+    for project in ["alpha", "beta"] {
+        std::fs::create_dir_all(projects.path().join(project)).unwrap();
+    }
+    let older = projects.path().join("alpha").join("old.jsonl");
+    let newer = projects.path().join("beta").join("new.jsonl");
+    std::fs::write(&older, user("old one") + &user("old two")).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(50));
+    std::fs::write(&newer, user("new one") + &user("new two")).unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["watch", "-n", "1", "--format", "json"])
+        .arg(projects.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+    let next_text = || {
+        let line = rx
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("watch printed nothing");
+        let entry: serde_json::Value = serde_json::from_str(&line).unwrap();
+        entry["message"]["content"].as_str().unwrap().to_string()
+    };
+    let append = |path: &std::path::Path, text: &str| {
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(user(text).as_bytes()).unwrap();
+    };
+
+    assert_eq!(next_text(), "new two");
+
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    append(&newer, "new three");
+    assert_eq!(next_text(), "new three");
+
+    // Writing to the other session makes it the most recent one
+    append(&older, "old three");
+    assert_eq!(next_text(), "old three");
+    append(&older, "old four");
+    assert_eq!(next_text(), "old four");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
+
+#[test]
+fn test_export_rejects_unknown_timezone() {
+    let projects = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(projects.path().join("proj")).unwrap();
+    std::fs::write(
+        projects.path().join("proj/s.jsonl"),
+        serde_json::json!({"type": "user", "timestamp": "2024-01-15T10:00:00Z",
+            "message": {"role": "user", "content": "hi"}})
+        .to_string(),
+    )
+    .unwrap();
+
+    let run = |timezone: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .args(["export", "proj/s", "--timezone", timezone])
+            .arg(projects.path())
+            .output()
+            .unwrap()
+    };
+
+    let bad = run("Mars/Olympus_Mons");
+    assert!(!bad.status.success());
+    assert!(bad.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("--timezone"));
+
+    let tokyo = run("Asia/Tokyo");
+    assert!(tokyo.status.success(), "{:?}", tokyo);
+    assert!(String::from_utf8_lossy(&tokyo.stdout).contains("2024-01-15 19:00:00 JST"));
+}
+
+#[test]
+fn test_log_level_flag() {
+    let projects = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(projects.path().join("proj")).unwrap();
+    std::fs::write(
+        projects.path().join("proj/s.jsonl"),
+        serde_json::json!({"type": "user", "message": {"role": "user", "content": "hi"}})
+            .to_string(),
+    )
+    .unwrap();
+
+    let run = |level: &str| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .args(["export", "proj/s", "--format", "json", "--log-level", level])
+            .arg(projects.path())
+            .output()
+            .unwrap()
+    };
+
+    // Diagnostics never end up in the exported output
+    let ok = run("cc_log_viewer=trace");
+    assert!(ok.status.success(), "{:?}", ok);
+    let exported: serde_json::Value = serde_json::from_slice(&ok.stdout).unwrap();
+    assert!(exported.is_object() || exported.is_array());
+
+    let bad = run("[[not a filter");
+    assert!(!bad.status.success());
+    assert!(bad.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("--log-level"));
+}
+
+#[test]
+fn test_serve_logs_requests_at_debug_level() {
+    use std::io::{BufRead, Read, Write};
+
+    let projects = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(projects.path().join("proj")).unwrap();
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["serve", "--port", "0", "--log-level", "tower_http=debug"])
+        .arg(projects.path())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stderr = child.stderr.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stderr).lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+
+    let mut banner = String::new();
+    std::io::BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut banner)
+        .unwrap();
+    let address = banner
+        .rsplit("bound to ")
+        .next()
+        .unwrap()
+        .trim_end()
+        .trim_end_matches(')')
+        .to_string();
+
+    let mut stream = std::net::TcpStream::connect(&address).unwrap();
+    write!(
+        stream,
+        "GET /api/projects/missing/sessions?token=hunter2 HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        address
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 404"), "{}", response);
+
+    let line = loop {
+        let line = rx
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("no request was logged");
+        if line.contains("finished processing request") {
+            break line;
+        }
+    };
+    let _ = child.kill();
+    let _ = child.wait();
+
+    assert!(line.contains("method=GET"), "{}", line);
+    assert!(
+        line.contains("path=/api/projects/missing/sessions"),
+        "{}",
+        line
+    );
+    assert!(line.contains("status=404"), "{}", line);
+    assert!(line.contains("latency="), "{}", line);
+    assert!(!line.contains("hunter2"), "{}", line);
+}
+
+/// Start `serve` on a free port and return it with its address.
+fn spawn_server(args: &[&str], projects_dir: &std::path::Path) -> (std::process::Child, String) {
+    use std::io::BufRead;
+
+    let mut child = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+        .args(["serve", "--port", "0"])
+        .args(args)
+        .arg(projects_dir)
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .unwrap();
+    // Keep reading stdout so later prints don't hit a closed pipe
+    let (tx, rx) = std::sync::mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in std::io::BufReader::new(stdout).lines() {
+            let _ = tx.send(line.unwrap());
+        }
+    });
+    let banner = rx
+        .recv_timeout(std::time::Duration::from_secs(10))
+        .expect("server printed no address");
+    let address = banner
+        .rsplit("bound to ")
+        .next()
+        .unwrap()
+        .trim_end_matches(')')
+        .to_string();
+    (child, address)
+}
+
+/// Send a raw HTTP/1.1 request and return the response head, lowercased.
+fn http_head(address: &str, method: &str, path: &str, headers: &[&str]) -> String {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, path, address);
+    for header in headers {
+        request.push_str(header);
+        request.push_str("\r\n");
+    }
+    request.push_str("Connection: close\r\n\r\n");
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+        .split("\r\n\r\n")
+        .next()
+        .unwrap()
+        .to_ascii_lowercase()
+}
+
+#[test]
+fn test_allow_origin_adds_cors_to_api_routes() {
+    let projects = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(projects.path().join("proj")).unwrap();
+    let origin = "Origin: https://dash.example";
+
+    // Off by default
+    let (mut child, address) = spawn_server(&[], projects.path());
+    let head = http_head(&address, "GET", "/api/projects", &[origin]);
+    let _ = child.kill();
+    let _ = child.wait();
+    assert!(head.starts_with("http/1.1 200"), "{}", head);
+    assert!(!head.contains("access-control-allow-origin"), "{}", head);
+
+    let (mut child, address) = spawn_server(
+        &[
+            "--allow-origin",
+            "https://dash.example",
+            "--allow-origin",
+            "http://localhost:5173",
+            "--auth-token",
+            "s3cret",
+        ],
+        projects.path(),
+    );
+    // Preflights get through the token check and are answered
+    let preflight = http_head(
+        &address,
+        "OPTIONS",
+        "/api/projects",
+        &[
+            origin,
+            "Access-Control-Request-Method: GET",
+            "Access-Control-Request-Headers: authorization",
+        ],
+    );
+    let allowed = http_head(
+        &address,
+        "GET",
+        "/api/projects",
+        &[origin, "Authorization: Bearer s3cret"],
+    );
+    let other_origin = http_head(
+        &address,
+        "GET",
+        "/api/projects",
+        &[
+            "Origin: https://evil.example",
+            "Authorization: Bearer s3cret",
+        ],
+    );
+    // A rejected request still carries CORS headers, so the page can read the 401
+    let unauthorized = http_head(
+        &address,
+        "GET",
+        "/api/projects",
+        &[origin, "Authorization: Bearer wrong"],
+    );
+    let not_api = http_head(&address, "GET", "/healthz", &[origin]);
+    let page_unauthorized = http_head(&address, "GET", "/", &[origin]);
+    let _ = child.kill();
+    let _ = child.wait();
+
+    assert!(preflight.starts_with("http/1.1 200"), "{}", preflight);
+    assert!(
+        preflight.contains("access-control-allow-origin: https://dash.example"),
+        "{}",
+        preflight
+    );
+    assert!(preflight.contains("authorization"), "{}", preflight);
+    assert!(allowed.starts_with("http/1.1 200"), "{}", allowed);
+    assert!(
+        allowed.contains("access-control-allow-origin: https://dash.example"),
+        "{}",
+        allowed
+    );
+    assert!(
+        !other_origin.contains("access-control-allow-origin"),
+        "{}",
+        other_origin
+    );
+    assert!(unauthorized.starts_with("http/1.1 401"), "{}", unauthorized);
+    assert!(
+        unauthorized.contains("access-control-allow-origin: https://dash.example"),
+        "{}",
+        unauthorized
+    );
+    assert!(
+        !not_api.contains("access-control-allow-origin"),
+        "{}",
+        not_api
+    );
+    assert!(
+        page_unauthorized.starts_with("http/1.1 401"),
+        "{}",
+        page_unauthorized
+    );
+    assert!(
+        !page_unauthorized.contains("access-control-allow-origin"),
+        "{}",
+        page_unauthorized
+    );
+}
+
+#[test]
+fn test_export_conversation_only() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("proj");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let lines = [
+        r#"{"type":"user","message":{"role":"user","content":"What is here?"}}"#,
+        r#"{"type":"assistant","message":{"role":"assistant","content":[{"type":"text","text":"Let me look."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#,
+        r#"{"type":"user","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"secret-listing"}]}}"#,
+    ];
+    std::fs::write(project_dir.join("s.jsonl"), lines.join("\n")).unwrap();
+
+    let run = |extra: &[&str]| {
+        let output = std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .args(["export", "proj/s"])
+            .arg(temp_dir.path())
+            .args(extra)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+
+    let full = run(&[]);
+    assert!(full.contains("secret-listing"));
+    let transcript = run(&["--conversation-only"]);
+    assert!(transcript.contains("What is here?"));
+    assert!(transcript.contains("Let me look."));
+    assert!(!transcript.contains("secret-listing"));
+    assert!(!transcript.contains("Tool:"));
+}
+
+#[test]
+fn test_export_since_selects_whole_sessions() {
+    let projects = tempfile::TempDir::new().unwrap();
+    let output = tempfile::TempDir::new().unwrap();
+    let project_dir = projects.path().join("proj");
+    std::fs::create_dir_all(&project_dir).unwrap();
+    let entry = |text: &str, ts: &str| {
+        serde_json::json!({"type": "user", "timestamp": ts,
+            "message": {"role": "user", "content": text}})
+        .to_string()
+    };
+    // Started before the range but still going inside it
+    std::fs::write(
+        project_dir.join("early.jsonl"),
+        [
+            entry("Early start", "2024-01-10T10:00:00Z"),
+            entry("Early finish", "2024-01-20T10:00:00Z"),
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+    std::fs::write(
+        project_dir.join("late.jsonl"),
+        [
+            entry("Late start", "2024-01-16T10:00:00Z"),
+            entry("Late finish", "2024-01-25T10:00:00Z"),
+        ]
+        .join("\n"),
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        std::process::Command::new(env!("CARGO_BIN_EXE_cc-log-viewer"))
+            .args(args)
+            .arg(projects.path())
+            .output()
+            .unwrap()
+    };
+    let since = [
+        "--since",
+        "2024-01-15T00:00:00Z",
+        "--until",
+        "2024-01-18T00:00:00Z",
+    ];
+
+    // A session in range is exported whole, even past --until
+    let late = run(&[&["export", "proj/late"][..], &since].concat());
+    assert!(late.status.success());
+    let stdout = String::from_utf8_lossy(&late.stdout);
+    assert!(stdout.contains("Late start"));
+    assert!(stdout.contains("Late finish"));
+
+    let early = run(&[&["export", "proj/early"][..], &since].concat());
+    assert!(!early.status.success());
+    assert!(String::from_utf8_lossy(&early.stderr).contains("did not start within"));
+
+    let combined = run(&[&["export", "proj", "--combine"][..], &since].concat());
+    assert!(combined.status.success());
+    let stdout = String::from_utf8_lossy(&combined.stdout);
+    assert!(stdout.contains("Late finish"));
+    assert!(!stdout.contains("Early"));
+
+    // export-all leaves out the early session but keeps its earlier export
+    let output_dir = output.path().to_str().unwrap();
+    assert!(run(&["export-all", "--output", output_dir])
+        .status
+        .success());
+    let ranged = run(&[&["export-all", "--output", output_dir][..], &since].concat());
+    assert!(ranged.status.success());
+    assert!(String::from_utf8_lossy(&ranged.stderr).contains("Exported 1 sessions"));
+    assert!(output.path().join("proj").join("early.md").is_file());
+    let manifest: serde_json::Value =
+        serde_json::from_slice(&std::fs::read(output.path().join("index.json")).unwrap()).unwrap();
+    let files = manifest["files"].as_array().unwrap();
+    assert_eq!(files.len(), 1);
+    assert_eq!(files[0]["session_id"], "late");
+}
@@ -0,0 +1,94 @@
+// ABOUTME: Tests for the optional config.toml defaults file
+// ABOUTME: Covers parsing, home expansion and the live watch project filter
+
+use cc_log_viewer::{config::Config, AppState};
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+#[test]
+fn test_config_file_parsing() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    fs::write(
+        &path,
+        r#"
+port = 3100
+projects_dir = "/data/claude/projects"
+watch_projects = ["cc-log-viewer", "website"]
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.port, Some(3100));
+    assert_eq!(
+        config.projects_dir,
+        Some(PathBuf::from("/data/claude/projects"))
+    );
+    assert_eq!(config.watch_projects, vec!["cc-log-viewer", "website"]);
+}
+
+#[test]
+fn test_config_fields_are_optional() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    fs::write(&path, "port = 4000\n").unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.port, Some(4000));
+    assert!(config.projects_dir.is_none());
+    assert!(config.watch_projects.is_empty());
+
+    fs::write(&path, "port = \"not a number\"\n").unwrap();
+    assert!(Config::load(&path).is_err());
+}
+
+#[test]
+fn test_config_expands_home_in_projects_dir() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    fs::write(&path, "projects_dir = \"~/claude/projects\"\n").unwrap();
+
+    let config = Config::load(&path).unwrap();
+    let home = std::env::var("HOME").unwrap();
+    assert_eq!(
+        config.projects_dir,
+        Some(PathBuf::from(home).join("claude/projects"))
+    );
+}
+
+#[test]
+fn test_watch_projects_filter() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let unfiltered = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    assert!(unfiltered.watches_project("-Users-me-anything"));
+
+    let filtered = AppState::new(temp_dir.path().to_path_buf())
+        .unwrap()
+        .with_watch_projects(vec!["cc-log-viewer".to_string()]);
+    assert!(filtered.watches_project("-Users-me-src-cc-log-viewer"));
+    assert!(!filtered.watches_project("-Users-me-src-website"));
+}
+
+#[test]
+fn test_config_tui_intervals() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    fs::write(
+        &path,
+        "[tui]\nrefresh_interval_ms = 10000\npoll_interval_ms = 250\nauto_refresh = false\n",
+    )
+    .unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.tui.refresh_interval_ms, Some(10000));
+    assert_eq!(config.tui.poll_interval_ms, Some(250));
+    assert_eq!(config.tui.auto_refresh, Some(false));
+
+    fs::write(&path, "port = 4000\n").unwrap();
+    let config = Config::load(&path).unwrap();
+    assert!(config.tui.refresh_interval_ms.is_none());
+    assert!(config.tui.auto_refresh.is_none());
+}
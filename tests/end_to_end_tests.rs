@@ -3,7 +3,6 @@
 
 use axum_test::TestServer;
 use futures_util::stream::StreamExt as FuturesStreamExt;
-use futures_util::{SinkExt, StreamExt};
 use serde_json::{json, Value};
 use std::fs;
 use std::time::Duration;
@@ -428,7 +427,7 @@ async fn test_api_endpoints_with_tools() {
 
     // Create session with tool events
     let session_file = project_dir.join("tool-session.jsonl");
-    let content = vec![
+    let content = [
         json!({
             "type": "summary",
             "summary": "Session with tool usage",
@@ -524,7 +523,7 @@ async fn test_websocket_connection_management() {
     let (ws_stream, _) = connect_async(&ws_url)
         .await
         .expect("WebSocket connection failed");
-    let (_ws_sender, mut ws_receiver) = ws_stream.split();
+    let (_ws_sender, _ws_receiver) = ws_stream.split();
 
     // Connection should stay alive
     sleep(Duration::from_secs(1)).await;
@@ -540,7 +539,7 @@ async fn test_path_formatting_in_events() {
 
     // Create project with path that would be encoded
     let encoded_project_name = "-Users-harper-Public-src-2389-cc-log-viewer";
-    let project_dir = projects_dir.join(&encoded_project_name);
+    let project_dir = projects_dir.join(encoded_project_name);
     fs::create_dir_all(&project_dir).unwrap();
 
     let server = create_test_server(projects_dir).await;
@@ -648,7 +647,7 @@ async fn test_error_resilience() {
     let session_file = project_dir.join("error-test.jsonl");
 
     // Write mix of valid and invalid entries
-    let mixed_content = vec![
+    let mixed_content = [
         create_rich_bash_tool_event(),
         "invalid json line that should be skipped".to_string(),
         create_rich_tool_result_event(),
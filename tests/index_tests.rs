@@ -0,0 +1,296 @@
+// ABOUTME: Tests for the optional SQLite session index
+// ABOUTME: Checks it agrees with filesystem scanning and follows appended entries
+
+use axum_test::TestServer;
+use cc_log_viewer::{
+    get_project_stats, index::SessionIndex, project_stats, search, search_project, AppState,
+    DEFAULT_SCAN_DEPTH,
+};
+use serde_json::{json, Value};
+use std::{fs, io::Write, path::Path};
+use tempfile::TempDir;
+
+fn write_session(projects_dir: &Path, project: &str, session: &str, lines: &[Value]) {
+    let project_dir = projects_dir.join(project);
+    fs::create_dir_all(&project_dir).unwrap();
+    let content: String = lines.iter().map(|l| format!("{}\n", l)).collect();
+    fs::write(project_dir.join(format!("{}.jsonl", session)), content).unwrap();
+}
+
+fn append_line(projects_dir: &Path, project: &str, session: &str, line: &str) {
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(
+            projects_dir
+                .join(project)
+                .join(format!("{}.jsonl", session)),
+        )
+        .unwrap();
+    file.write_all(line.as_bytes()).unwrap();
+}
+
+fn user(text: &str, ts: &str) -> Value {
+    json!({"type": "user", "message": {"role": "user", "content": text}, "timestamp": ts})
+}
+
+fn sample_projects(dir: &Path) {
+    write_session(
+        dir,
+        "alpha",
+        "s1",
+        &[
+            json!({"type": "summary", "summary": "Fixing the parser", "leafUuid": "x"}),
+            user("Please fix the Parser bug", "2024-01-15T10:00:00Z"),
+            json!({
+                "type": "assistant",
+                "message": {
+                    "role": "assistant",
+                    "content": [
+                        {"type": "text", "text": "Looking at the parser now."},
+                        {"type": "tool_use", "id": "t1", "name": "Read", "input": {"file_path": "src/parser.rs"}},
+                        {"type": "tool_use", "id": "t2", "name": "Bash", "input": {"command": "cargo test"}}
+                    ],
+                    "usage": {"input_tokens": 10, "output_tokens": 5, "cache_creation_input_tokens": 7}
+                },
+                "timestamp": "2024-01-15T10:00:05.250Z"
+            }),
+            json!({
+                "type": "user",
+                "message": {"role": "user", "content": [
+                    {"type": "tool_result", "tool_use_id": "t1", "content": "fn parse() {}"},
+                    {"type": "tool_result", "tool_use_id": "t2", "content": "1 test failed", "is_error": true}
+                ]},
+                "timestamp": "2024-01-15T10:00:06Z"
+            }),
+            json!("not an entry"),
+        ],
+    );
+    write_session(
+        dir,
+        "alpha",
+        "s2",
+        &[json!({
+            "type": "assistant",
+            "message": {
+                "role": "assistant",
+                "content": [{"type": "tool_use", "id": "t3", "name": "Bash", "input": {"command": "ls parser"}}],
+                "usage": {"input_tokens": 100, "output_tokens": 20, "cache_read_input_tokens": 500}
+            },
+            "timestamp": "2024-01-20T08:00:00Z"
+        })],
+    );
+    write_session(
+        dir,
+        "beta",
+        "s1",
+        &[user("unrelated PARSER question", "2024-02-01T00:00:00Z")],
+    );
+}
+
+fn open_index(db_dir: &TempDir, projects_dir: &Path) -> SessionIndex {
+    SessionIndex::open(
+        &db_dir.path().join("index.sqlite3"),
+        projects_dir.to_path_buf(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_index_matches_filesystem_stats_and_search() {
+    let projects = TempDir::new().unwrap();
+    let db = TempDir::new().unwrap();
+    sample_projects(projects.path());
+
+    let index = open_index(&db, projects.path());
+    assert_eq!(index.sync().unwrap(), 3);
+
+    let from_index = index.project_stats("alpha").unwrap();
+    let from_files = project_stats(&projects.path().join("alpha"), DEFAULT_SCAN_DEPTH);
+    assert_eq!(
+        serde_json::to_value(&from_index).unwrap(),
+        serde_json::to_value(&from_files).unwrap()
+    );
+    assert_eq!(from_index.total_tool_calls, 3);
+    assert_eq!(from_index.tool_calls["Bash"], 2);
+    assert_eq!(from_index.error_count, 1);
+
+    let hits = |hits: Vec<cc_log_viewer::SearchHit>| -> Vec<Value> {
+        hits.iter()
+            .map(|h| serde_json::to_value(h).unwrap())
+            .collect()
+    };
+    let mut expected = search_project(
+        &projects.path().join("alpha"),
+        "parser",
+        100,
+        DEFAULT_SCAN_DEPTH,
+    );
+    expected.extend(search_project(
+        &projects.path().join("beta"),
+        "parser",
+        100,
+        DEFAULT_SCAN_DEPTH,
+    ));
+    assert_eq!(
+        hits(index.search("parser", None, 100).unwrap()),
+        hits(expected)
+    );
+
+    let alpha_only = index.search("parser", Some("alpha"), 2).unwrap();
+    assert_eq!(alpha_only.len(), 2);
+    assert!(alpha_only.iter().all(|h| h.project == "alpha"));
+    assert!(index.search("100%", None, 10).unwrap().is_empty());
+}
+
+#[test]
+fn test_index_follows_appends_and_rewrites() {
+    let projects = TempDir::new().unwrap();
+    let db = TempDir::new().unwrap();
+    sample_projects(projects.path());
+    let index = open_index(&db, projects.path());
+    index.sync().unwrap();
+
+    // Nothing new to read
+    assert_eq!(index.update_session("beta", "s1").unwrap(), 0);
+
+    // A line still being written is indexed, then picked up again whole
+    append_line(
+        projects.path(),
+        "beta",
+        "s1",
+        &user("first zebra", "2024-02-02T00:00:00Z").to_string(),
+    );
+    assert_eq!(index.update_session("beta", "s1").unwrap(), 1);
+    assert_eq!(index.search("zebra", None, 10).unwrap().len(), 1);
+    append_line(projects.path(), "beta", "s1", "\n");
+    append_line(
+        projects.path(),
+        "beta",
+        "s1",
+        &format!("{}\n", user("second zebra", "2024-02-03T00:00:00Z")),
+    );
+    assert_eq!(index.update_session("beta", "s1").unwrap(), 2);
+    let zebras = index.search("zebra", None, 10).unwrap();
+    assert_eq!(
+        zebras.iter().map(|h| h.line).collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert_eq!(index.project_stats("beta").unwrap().total_messages, 3);
+
+    // A rewritten (shorter) file is reindexed from scratch
+    write_session(
+        projects.path(),
+        "beta",
+        "s1",
+        &[user("x", "2024-03-01T00:00:00Z")],
+    );
+    index.update_session("beta", "s1").unwrap();
+    assert!(index.search("zebra", None, 10).unwrap().is_empty());
+    assert_eq!(index.project_stats("beta").unwrap().total_messages, 1);
+
+    // Deleted sessions drop out on the next sync
+    fs::remove_file(projects.path().join("alpha").join("s2.jsonl")).unwrap();
+    assert_eq!(index.sync().unwrap(), 2);
+    let stats = index.project_stats("alpha").unwrap();
+    assert_eq!(stats.total_sessions, 1);
+    assert_eq!(stats.tool_calls["Bash"], 1);
+}
+
+#[test]
+fn test_index_reopened_for_another_directory_is_cleared() {
+    let projects = TempDir::new().unwrap();
+    let other = TempDir::new().unwrap();
+    let db = TempDir::new().unwrap();
+    sample_projects(projects.path());
+
+    open_index(&db, projects.path()).sync().unwrap();
+    let reopened = open_index(&db, projects.path());
+    assert_eq!(reopened.project_stats("alpha").unwrap().total_sessions, 2);
+
+    let elsewhere = open_index(&db, other.path());
+    assert_eq!(elsewhere.project_stats("alpha").unwrap().total_sessions, 0);
+    assert!(elsewhere.search("parser", None, 10).unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_endpoints_use_index_when_attached() {
+    let projects = TempDir::new().unwrap();
+    let db = TempDir::new().unwrap();
+    sample_projects(projects.path());
+    let index = open_index(&db, projects.path());
+    index.sync().unwrap();
+
+    // Sessions added after indexing are only visible to a filesystem scan,
+    // which shows the endpoints answer from the index.
+    write_session(
+        projects.path(),
+        "alpha",
+        "s3",
+        &[user("parser again", "2024-04-01T00:00:00Z")],
+    );
+
+    let state = AppState::new(projects.path().to_path_buf())
+        .unwrap()
+        .with_index(index);
+    let app = axum::Router::new()
+        .route("/api/search", axum::routing::get(search))
+        .route(
+            "/api/projects/:project/stats",
+            axum::routing::get(get_project_stats),
+        )
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let stats: Value = server.get("/api/projects/alpha/stats").await.json();
+    assert_eq!(stats["total_sessions"], 2);
+    assert_eq!(stats["token_usage"]["cache_read_input_tokens"], 500);
+    assert_eq!(stats["last_activity"], "2024-01-20T08:00:00Z");
+
+    let hits: Value = server
+        .get("/api/search")
+        .add_query_param("q", "parser")
+        .add_query_param("project", "alpha")
+        .await
+        .json();
+    let sessions: Vec<&str> = hits
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|h| h["session"].as_str().unwrap())
+        .collect();
+    assert!(!sessions.is_empty());
+    assert!(!sessions.contains(&"s3"));
+
+    server
+        .get("/api/projects/missing/stats")
+        .await
+        .assert_status_not_found();
+}
+
+#[test]
+fn test_index_reads_crlf_and_bom_sessions() {
+    let projects = TempDir::new().unwrap();
+    let db = TempDir::new().unwrap();
+    let project_dir = projects.path().join("windows");
+    fs::create_dir_all(&project_dir).unwrap();
+    fs::write(
+        project_dir.join("s1.jsonl"),
+        format!(
+            "\u{feff}{}\r\n{}\r\n",
+            user("first windows line", "2024-01-01T00:00:00Z"),
+            user("second windows line", "2024-01-01T00:01:00Z")
+        ),
+    )
+    .unwrap();
+
+    let index = open_index(&db, projects.path());
+    index.sync().unwrap();
+    assert_eq!(index.project_stats("windows").unwrap().total_messages, 2);
+    let lines: Vec<usize> = index
+        .search("windows line", None, 10)
+        .unwrap()
+        .iter()
+        .map(|h| h.line)
+        .collect();
+    assert_eq!(lines, vec![1, 2]);
+}
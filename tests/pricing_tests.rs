@@ -0,0 +1,221 @@
+// ABOUTME: Tests for token cost estimation and the pricing table
+// ABOUTME: Covers built-in prices, config overrides and costs in the API
+
+use axum_test::TestServer;
+use cc_log_viewer::{
+    config::Config,
+    get_project_stats, get_session_blocks, get_session_meta, get_sessions,
+    pricing::{estimate_cost, ModelPricing, PricingTable},
+    AppState, LogEntry, TokenUsage,
+};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fs};
+use tempfile::TempDir;
+
+fn usage(input: u64, output: u64, cache_write: u64, cache_read: u64) -> TokenUsage {
+    TokenUsage {
+        input_tokens: input,
+        output_tokens: output,
+        cache_creation_input_tokens: cache_write,
+        cache_read_input_tokens: cache_read,
+    }
+}
+
+fn assert_close(actual: f64, expected: f64) {
+    assert!(
+        (actual - expected).abs() < 1e-9,
+        "expected {}, got {}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_builtin_prices() {
+    let million = usage(1_000_000, 1_000_000, 1_000_000, 1_000_000);
+    assert_close(
+        estimate_cost("claude-3-5-sonnet-20241022", &million),
+        3.0 + 15.0 + 3.75 + 0.30,
+    );
+    assert_close(
+        estimate_cost("claude-opus-4-1-20250805", &million),
+        15.0 + 75.0 + 18.75 + 1.50,
+    );
+    assert_close(
+        estimate_cost("claude-3-5-haiku-20241022", &million),
+        0.80 + 4.0 + 1.0 + 0.08,
+    );
+
+    // Cache reads are a fraction of the price of fresh input
+    let fresh = estimate_cost("claude-sonnet-4", &usage(10_000, 0, 0, 0));
+    let cached = estimate_cost("claude-sonnet-4", &usage(0, 0, 0, 10_000));
+    assert!(cached < fresh / 5.0);
+
+    assert_eq!(estimate_cost("gpt-4o", &million), 0.0);
+    assert_eq!(estimate_cost("unknown", &million), 0.0);
+}
+
+#[test]
+fn test_pricing_overrides() {
+    let price = |input: f64| ModelPricing {
+        input,
+        output: 0.0,
+        cache_write: 0.0,
+        cache_read: 0.0,
+    };
+    let table = PricingTable::with_overrides(BTreeMap::from([
+        ("sonnet".to_string(), price(1.0)),
+        ("sonnet-4".to_string(), price(2.0)),
+    ]));
+    let tokens = usage(1_000_000, 0, 0, 0);
+
+    assert_close(
+        table.estimate_cost("claude-sonnet-4-20250514", &tokens),
+        2.0,
+    );
+    assert_close(table.estimate_cost("claude-3-7-sonnet", &tokens), 1.0);
+    // Models not overridden keep the built-in price
+    assert_close(table.estimate_cost("claude-3-haiku", &tokens), 0.25);
+
+    let by_model = BTreeMap::from([
+        ("claude-sonnet-4".to_string(), tokens),
+        ("claude-3-7-sonnet".to_string(), tokens),
+    ]);
+    assert_close(table.total_cost(&by_model), 3.0);
+}
+
+#[test]
+fn test_pricing_from_config() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("config.toml");
+    fs::write(
+        &path,
+        r#"
+[pricing.sonnet-4]
+input = 2.5
+output = 10.0
+cache_write = 3.0
+cache_read = 0.25
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(
+        config.pricing["sonnet-4"],
+        ModelPricing {
+            input: 2.5,
+            output: 10.0,
+            cache_write: 3.0,
+            cache_read: 0.25
+        }
+    );
+}
+
+#[tokio::test]
+async fn test_costs_in_sessions_stats_and_meta() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("cost-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let assistant = |model: Option<&str>, input: u64, cache_read: u64| {
+        let mut message = json!({
+            "role": "assistant",
+            "content": "done",
+            "usage": {"input_tokens": input, "output_tokens": 0, "cache_read_input_tokens": cache_read}
+        });
+        if let Some(model) = model {
+            message["model"] = json!(model);
+        }
+        json!({"type": "assistant", "timestamp": "2024-01-15T10:00:00Z", "message": message})
+            .to_string()
+    };
+    let lines = [
+        assistant(Some("claude-sonnet-4"), 1_000_000, 0),
+        assistant(Some("claude-opus-4-1"), 0, 1_000_000),
+        assistant(None, 1_000_000, 0),
+    ];
+    fs::write(project_dir.join("s1.jsonl"), lines.join("\n")).unwrap();
+
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let app = axum::Router::new()
+        .route(
+            "/api/projects/:project/sessions",
+            axum::routing::get(get_sessions),
+        )
+        .route(
+            "/api/projects/:project/stats",
+            axum::routing::get(get_project_stats),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/meta",
+            axum::routing::get(get_session_meta),
+        )
+        .route(
+            "/api/projects/:project/sessions/:session/blocks",
+            axum::routing::get(get_session_blocks),
+        )
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    // $3 of Sonnet input plus $1.50 of Opus cache reads; the unnamed model is free
+    let sessions: Value = server
+        .get("/api/projects/cost-project/sessions")
+        .await
+        .json();
+    assert_close(sessions[0]["estimated_cost_usd"].as_f64().unwrap(), 4.5);
+    assert!(sessions[0].get("usage_by_model").is_none());
+
+    let stats: Value = server.get("/api/projects/cost-project/stats").await.json();
+    assert_close(stats["estimated_cost_usd"].as_f64().unwrap(), 4.5);
+    assert_eq!(
+        stats["usage_by_model"]["claude-sonnet-4"]["input_tokens"],
+        1_000_000
+    );
+    assert_eq!(
+        stats["usage_by_model"]["unknown"]["input_tokens"],
+        1_000_000
+    );
+
+    let meta: Value = server
+        .get("/api/projects/cost-project/sessions/s1/meta")
+        .await
+        .json();
+    assert_close(meta["estimated_cost_usd"].as_f64().unwrap(), 4.5);
+
+    // Each assistant entry carries its own share
+    let blocks: Vec<Value> = server
+        .get("/api/projects/cost-project/sessions/s1/blocks")
+        .await
+        .json();
+    let costs: Vec<f64> = blocks
+        .iter()
+        .map(|b| b["estimated_cost_usd"].as_f64().unwrap())
+        .collect();
+    assert_eq!(costs.len(), 3);
+    assert_close(costs[0], 3.0);
+    assert_close(costs[1], 1.5);
+    assert_close(costs[2], 0.0);
+}
+
+#[test]
+fn test_entry_cost() {
+    let entry = |value: Value| serde_json::from_value::<LogEntry>(value).unwrap();
+    let pricing = PricingTable::default();
+
+    let assistant = entry(json!({
+        "type": "assistant",
+        "message": {
+            "role": "assistant",
+            "model": "claude-sonnet-4",
+            "content": "done",
+            "usage": {"input_tokens": 1_000, "output_tokens": 1_000}
+        }
+    }));
+    assert_close(pricing.entry_cost(&assistant).unwrap(), 0.018);
+
+    let user = entry(json!({
+        "type": "user",
+        "message": {"role": "user", "content": "hi"}
+    }));
+    assert_eq!(pricing.entry_cost(&user), None);
+}
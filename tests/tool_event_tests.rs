@@ -420,7 +420,7 @@ fn test_file_system_integration() {
 
     // Create JSONL file with various tool entries
     let session_file = project_path.join("tools.jsonl");
-    let content = vec![
+    let content = [
         create_bash_tool_entry(),
         create_tool_result_entry(),
         create_read_tool_entry(),
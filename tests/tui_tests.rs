@@ -15,7 +15,7 @@ fn create_test_project_structure(temp_dir: &TempDir) -> std::path::PathBuf {
     fs::create_dir_all(&project_dir).unwrap();
 
     // Create sample session files
-    let session1_content = vec![
+    let session1_content = [
         json!({
             "type": "summary",
             "summary": "Test Session 1",
@@ -42,7 +42,7 @@ fn create_test_project_structure(temp_dir: &TempDir) -> std::path::PathBuf {
     ]
     .join("\n");
 
-    let session2_content = vec![
+    let session2_content = [
         json!({
             "type": "summary",
             "summary": "Test Session 2",
@@ -175,18 +175,66 @@ async fn test_export_functionality() {
         .await
         .unwrap();
 
-    // Test export
+    // Default file name is derived from the project and session
+    assert_eq!(
+        tui_app.default_export_path(),
+        std::path::PathBuf::from(format!("{}-{}.md", project_name, session_id))
+    );
+
+    // Test export to a confirmed path
+    let export_path = temp_dir.path().join("export.md");
+    tui_app.export_path = Some(export_path.clone());
     tui_app.export_conversation().await.unwrap();
 
     // Check that export file was created
-    assert!(std::path::Path::new("conversation_export.txt").exists());
+    assert!(export_path.exists());
+    assert!(tui_app.status_message.contains("export.md"));
 
-    let exported_content = fs::read_to_string("conversation_export.txt").unwrap();
+    let exported_content = fs::read_to_string(&export_path).unwrap();
     assert!(exported_content.contains("Claude Code Conversation Export"));
     assert!(exported_content.contains("Hello from session"));
+}
+
+#[tokio::test]
+async fn test_export_format_selection() {
+    use cc_log_viewer::{export::ExportFormat, tui::AppMode};
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+
+    let app_state = AppState::new(projects_dir).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
 
-    // Cleanup
-    fs::remove_file("conversation_export.txt").unwrap();
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('e')).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::Export);
+    assert_eq!(tui_app.export_format, ExportFormat::Markdown);
+
+    // Right arrow switches to HTML and the path follows the extension
+    tui_app.export_path = Some(temp_dir.path().join("session.md"));
+    tui_app.handle_key_event(KeyCode::Right).await.unwrap();
+    assert_eq!(tui_app.export_format, ExportFormat::Html);
+    assert_eq!(
+        tui_app.export_path,
+        Some(temp_dir.path().join("session.html"))
+    );
+
+    // Enter saves and returns to the conversation
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ConversationView);
+
+    let exported = fs::read_to_string(temp_dir.path().join("session.html")).unwrap();
+    assert!(exported.starts_with("<!DOCTYPE html>"));
+    assert!(exported.contains("Hello from session 2"));
+
+    // Left wraps around from Markdown to plain text
+    tui_app.export_format = ExportFormat::Markdown;
+    tui_app.handle_key_event(KeyCode::Char('e')).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Left).await.unwrap();
+    assert_eq!(tui_app.export_format, ExportFormat::PlainText);
 }
 
 #[tokio::test]
@@ -200,7 +248,9 @@ async fn test_empty_project_handling() {
     tui_app.refresh_projects().await.unwrap();
 
     assert!(tui_app.projects.is_empty());
-    assert!(tui_app.status_message.contains("No projects found"));
+    assert!(tui_app
+        .status_message
+        .contains("No Claude Code projects found in"));
 }
 
 #[tokio::test]
@@ -348,15 +398,15 @@ async fn test_large_conversation_handling() {
 fn test_cli_argument_parsing() {
     use std::process::Command;
 
-    // Test --tui flag is recognized
+    // Test the tui subcommand is recognized
     let output = Command::new("cargo")
-        .args(&["run", "--", "--help"])
+        .args(["run", "--", "--help"])
         .output()
         .expect("Failed to execute command");
 
     let help_text = String::from_utf8(output.stdout).unwrap();
-    assert!(help_text.contains("--tui"));
-    assert!(help_text.contains("Use terminal UI instead of web interface"));
+    assert!(help_text.contains("tui"));
+    assert!(help_text.contains("Browse logs in the terminal UI"));
 }
 
 #[test]
@@ -365,13 +415,13 @@ fn test_cli_default_behavior() {
 
     // Test that help shows both modes
     let output = Command::new("cargo")
-        .args(&["run", "--", "--help"])
+        .args(["run", "--", "--help"])
         .output()
         .expect("Failed to execute command");
 
     let help_text = String::from_utf8(output.stdout).unwrap();
     assert!(help_text.contains("--port"));
-    assert!(help_text.contains("--tui"));
+    assert!(help_text.contains("tui"));
     assert!(help_text.contains("[default: 2006]")); // Default port shown
 }
 
@@ -397,3 +447,1156 @@ async fn test_tui_timeout_handling() {
     .await
     .expect("TUI operations should complete within timeout");
 }
+
+#[tokio::test]
+async fn test_message_detail_view_navigation() {
+    use cc_log_viewer::tui::AppMode;
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+
+    let app_state = AppState::new(projects_dir).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::SessionList);
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ConversationView);
+
+    // Enter opens the focused message in the detail view
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::MessageDetail);
+
+    // Esc returns to the conversation at the same position
+    tui_app.handle_key_event(KeyCode::Esc).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ConversationView);
+}
+
+#[tokio::test]
+async fn test_conversation_search() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    let project_dir = projects_dir.join("search-project");
+    fs::create_dir_all(&project_dir).unwrap();
+
+    let content = (0..10)
+        .map(|i| {
+            let text = if i == 3 || i == 7 {
+                format!("Message {} mentions the deadlock", i)
+            } else {
+                format!("Message {}", i)
+            };
+            json!({
+                "type": "user",
+                "message": {"role": "user", "content": text},
+                "timestamp": "2024-01-15T10:00:00Z",
+                "uuid": format!("msg-{}", i)
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(project_dir.join("search.jsonl"), content).unwrap();
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+
+    // Typing "/deadlock" then Enter jumps to the first match
+    tui_app.handle_key_event(KeyCode::Char('/')).await.unwrap();
+    for c in "DEADLOCK".chars() {
+        tui_app.handle_key_event(KeyCode::Char(c)).await.unwrap();
+    }
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.search_query.as_deref(), Some("DEADLOCK"));
+    assert_eq!(tui_app.scroll_offset, 3);
+
+    // n cycles forward and wraps, N goes back
+    tui_app.handle_key_event(KeyCode::Char('n')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 7);
+    tui_app.handle_key_event(KeyCode::Char('n')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 3);
+    tui_app.handle_key_event(KeyCode::Char('N')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 7);
+
+    // A query with no matches reports it in the status bar
+    tui_app.handle_key_event(KeyCode::Char('/')).await.unwrap();
+    for c in "zebra".chars() {
+        tui_app.handle_key_event(KeyCode::Char(c)).await.unwrap();
+    }
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert!(tui_app.status_message.contains("No matches"));
+}
+
+#[tokio::test]
+async fn test_search_finds_match_on_first_entry() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    let project_dir = projects_dir.join("search-project");
+    fs::create_dir_all(&project_dir).unwrap();
+
+    let content = (0..6)
+        .map(|i| {
+            let text = if i == 0 || i == 4 {
+                format!("Message {} mentions the deadlock", i)
+            } else {
+                format!("Message {}", i)
+            };
+            json!({
+                "type": "user",
+                "message": {"role": "user", "content": text},
+                "timestamp": "2024-01-15T10:00:00Z",
+                "uuid": format!("msg-{}", i)
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(project_dir.join("search.jsonl"), content).unwrap();
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+
+    // The entry already at the top counts as the first match
+    tui_app.handle_key_event(KeyCode::Char('/')).await.unwrap();
+    for c in "deadlock".chars() {
+        tui_app.handle_key_event(KeyCode::Char(c)).await.unwrap();
+    }
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+    assert!(tui_app.status_message.starts_with("Match 1 of 2"));
+
+    tui_app.handle_key_event(KeyCode::Char('n')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 4);
+}
+
+#[tokio::test]
+async fn test_help_overlay_blocks_navigation() {
+    use cc_log_viewer::tui::AppMode;
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+
+    let app_state = AppState::new(projects_dir).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+
+    tui_app.handle_key_event(KeyCode::Char('?')).await.unwrap();
+    assert!(tui_app.show_help);
+
+    // Navigation keys are ignored while help is shown
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ProjectList);
+    assert!(tui_app.show_help);
+
+    // Esc closes the overlay without leaving the current mode
+    tui_app.handle_key_event(KeyCode::Esc).await.unwrap();
+    assert!(!tui_app.show_help);
+    assert_eq!(tui_app.mode, AppMode::ProjectList);
+
+    // '?' toggles it as well
+    tui_app.handle_key_event(KeyCode::Char('?')).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('?')).await.unwrap();
+    assert!(!tui_app.show_help);
+}
+
+#[tokio::test]
+async fn test_vim_style_navigation() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    for name in ["alpha", "beta", "gamma"] {
+        let project_dir = projects_dir.join(name);
+        fs::create_dir_all(&project_dir).unwrap();
+        let content = (0..5)
+            .map(|i| {
+                json!({
+                    "type": "user",
+                    "message": {"role": "user", "content": format!("{} message {}", name, i)},
+                    "timestamp": "2024-01-15T10:00:00Z",
+                    "uuid": format!("{}-{}", name, i)
+                })
+                .to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        fs::write(project_dir.join("session.jsonl"), content).unwrap();
+    }
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(0));
+
+    // j/k move through the project list like Down/Up
+    tui_app.handle_key_event(KeyCode::Char('j')).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(1));
+    tui_app.handle_key_event(KeyCode::Char('k')).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(0));
+    tui_app.handle_key_event(KeyCode::Char('k')).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(0));
+
+    // G jumps to the last project, g back to the first
+    tui_app.handle_key_event(KeyCode::Char('G')).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(2));
+    tui_app.handle_key_event(KeyCode::Char('g')).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(0));
+
+    // Arrow keys keep working
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    assert_eq!(tui_app.selected_project, Some(1));
+
+    // Same keys scroll the conversation
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+    tui_app.handle_key_event(KeyCode::Char('j')).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('j')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 2);
+    tui_app.handle_key_event(KeyCode::Char('k')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 1);
+    tui_app.handle_key_event(KeyCode::Char('G')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 4);
+    tui_app.handle_key_event(KeyCode::Char('j')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 4);
+    tui_app.handle_key_event(KeyCode::Char('g')).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+}
+
+#[tokio::test]
+async fn test_page_and_home_end_scrolling() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    let project_dir = projects_dir.join("paging-project");
+    fs::create_dir_all(&project_dir).unwrap();
+
+    let content = (0..100)
+        .map(|i| {
+            json!({
+                "type": "user",
+                "message": {"role": "user", "content": format!("Message {}", i)},
+                "timestamp": "2024-01-15T10:00:00Z",
+                "uuid": format!("msg-{}", i)
+            })
+            .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(project_dir.join("paging.jsonl"), content).unwrap();
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+
+    // Render once so the page size matches the visible area: 24 rows minus
+    // the breadcrumb, the 3-row status bar and the conversation block's 2
+    // border rows
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+
+    tui_app.handle_key_event(KeyCode::PageDown).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 18);
+    tui_app.handle_key_event(KeyCode::PageDown).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 36);
+    tui_app.handle_key_event(KeyCode::PageUp).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 18);
+
+    tui_app.handle_key_event(KeyCode::End).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 99);
+    // Never scrolls past the last message
+    tui_app.handle_key_event(KeyCode::PageDown).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 99);
+
+    tui_app.handle_key_event(KeyCode::Home).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+    tui_app.handle_key_event(KeyCode::PageUp).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+}
+
+#[tokio::test]
+async fn test_follow_mode_tracks_newest_message() {
+    use crossterm::event::KeyCode;
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(projects_dir.clone()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.scroll_offset, 0);
+    let project_name = tui_app.projects[0].name.clone();
+    let session_id = tui_app.sessions[0].id.clone();
+
+    // Without follow, a refresh keeps the current position
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    tui_app
+        .refresh_conversation(&project_name, &session_id)
+        .await
+        .unwrap();
+    assert_eq!(tui_app.scroll_offset, 1);
+
+    tui_app.handle_key_event(KeyCode::Char('f')).await.unwrap();
+    assert!(tui_app.follow);
+    assert_eq!(tui_app.scroll_offset, tui_app.conversation.len() - 1);
+
+    let session_path = projects_dir
+        .join(&project_name)
+        .join(format!("{}.jsonl", session_id));
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&session_path)
+        .unwrap();
+    write!(
+        file,
+        "\n{}",
+        json!({"type": "user", "message": {"role": "user", "content": "Newest"}, "uuid": "new"})
+    )
+    .unwrap();
+    drop(file);
+
+    tui_app
+        .refresh_conversation(&project_name, &session_id)
+        .await
+        .unwrap();
+    let last = tui_app.conversation.len() - 1;
+    assert_eq!(tui_app.conversation[last].uuid.as_deref(), Some("new"));
+    assert_eq!(tui_app.scroll_offset, last);
+
+    // Scrolling up by hand stops following
+    tui_app.handle_key_event(KeyCode::Up).await.unwrap();
+    assert!(!tui_app.follow);
+}
+
+#[tokio::test]
+async fn test_conversation_updates_from_watcher() {
+    use crossterm::event::KeyCode;
+    use std::io::Write;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(projects_dir.clone()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    let initial_len = tui_app.conversation.len();
+    let project_name = tui_app.projects[0].name.clone();
+    let session_id = tui_app.sessions[0].id.clone();
+
+    let session_path = projects_dir
+        .join(&project_name)
+        .join(format!("{}.jsonl", session_id));
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&session_path)
+        .unwrap();
+    write!(
+        file,
+        "\n{}",
+        json!({"type": "user", "message": {"role": "user", "content": "Live"}, "uuid": "live-1"})
+    )
+    .unwrap();
+    drop(file);
+
+    let arrived = timeout(Duration::from_secs(3), async {
+        loop {
+            tui_app.process_watch_events().await.unwrap();
+            if tui_app.conversation.len() > initial_len {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await;
+    assert!(
+        arrived.is_ok(),
+        "New entry should arrive without polling the file"
+    );
+
+    // Entries replayed by the watcher are not duplicated
+    assert_eq!(tui_app.conversation.len(), initial_len + 1);
+    assert_eq!(
+        tui_app.conversation.last().unwrap().uuid.as_deref(),
+        Some("live-1")
+    );
+}
+
+#[tokio::test]
+async fn test_copy_message() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(projects_dir).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    let index = tui_app
+        .conversation
+        .iter()
+        .position(|e| e.uuid.as_deref().is_some_and(|u| u.starts_with("msg-")))
+        .unwrap();
+    tui_app.scroll_offset = index;
+
+    tui_app.handle_key_event(KeyCode::Char('y')).await.unwrap();
+
+    // Headless test machines have no clipboard, so accept the file fallback
+    let status = tui_app.status_message.clone();
+    if let Some(path) = status.strip_prefix("Clipboard unavailable; saved to ") {
+        let copied = fs::read_to_string(path).unwrap();
+        assert!(copied.contains("Hello from session"));
+    } else {
+        assert_eq!(status, "Copied");
+    }
+}
+
+#[tokio::test]
+async fn test_copy_fallback_file_name_ignores_unsafe_uuid() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("copy-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let entry = json!({
+        "type": "user",
+        "message": {"role": "user", "content": "Copy me"},
+        "timestamp": "2024-01-15T10:00:00Z",
+        "uuid": "../../escaped/name"
+    });
+    fs::write(project_dir.join("copy.jsonl"), entry.to_string()).unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('y')).await.unwrap();
+
+    // Without a clipboard, the file is named after the entry's position
+    let status = tui_app.status_message.clone();
+    if let Some(path) = status.strip_prefix("Clipboard unavailable; saved to ") {
+        let path = std::path::Path::new(path);
+        assert_eq!(path.parent().unwrap(), std::env::temp_dir());
+        assert_eq!(
+            path.file_name().unwrap().to_str().unwrap(),
+            "cc-log-viewer-entry-1.txt"
+        );
+        assert_eq!(fs::read_to_string(path).unwrap(), "Copy me");
+    } else {
+        assert_eq!(status, "Copied");
+    }
+}
+
+#[tokio::test]
+async fn test_archive_session() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(projects_dir.clone()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.sessions.len(), 2);
+    let doomed = tui_app.sessions[0].id.clone();
+
+    // Declining leaves everything in place
+    tui_app.handle_key_event(KeyCode::Char('d')).await.unwrap();
+    assert_eq!(tui_app.confirm_archive.as_deref(), Some(doomed.as_str()));
+    tui_app.handle_key_event(KeyCode::Char('n')).await.unwrap();
+    assert!(tui_app.confirm_archive.is_none());
+    assert_eq!(tui_app.sessions.len(), 2);
+
+    tui_app.handle_key_event(KeyCode::Char('d')).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('y')).await.unwrap();
+
+    let project_dir = projects_dir.join("test-project");
+    assert!(!project_dir.join(format!("{}.jsonl", doomed)).exists());
+    assert!(project_dir
+        .join("archived")
+        .join(format!("{}.jsonl", doomed))
+        .exists());
+    assert_eq!(tui_app.sessions.len(), 1);
+    assert_ne!(tui_app.sessions[0].id, doomed);
+    assert!(tui_app.status_message.contains("Archived"));
+}
+
+#[tokio::test]
+async fn test_archived_sessions_stay_hidden_with_deeper_scan() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(projects_dir.clone())
+        .unwrap()
+        .with_scan_depth(2);
+    let mut events = app_state.watch_manager.subscribe();
+    let mut tui_app = TuiApp::new(app_state);
+
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    let doomed = tui_app.sessions[0].id.clone();
+    tui_app.handle_key_event(KeyCode::Char('d')).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('y')).await.unwrap();
+
+    assert!(projects_dir
+        .join("test-project")
+        .join("archived")
+        .join(format!("{}.jsonl", doomed))
+        .exists());
+    let ids: Vec<&str> = tui_app.sessions.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(ids.len(), 1);
+    assert!(!ids[0].starts_with("archived/"));
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.projects[0].session_count, 1);
+
+    // The move isn't announced as activity in a new session
+    let announced = timeout(Duration::from_millis(500), async {
+        loop {
+            let event = events.recv().await.unwrap();
+            if event.entry.is_some() {
+                return event;
+            }
+        }
+    })
+    .await;
+    assert!(announced.is_err(), "unexpected event: {:?}", announced);
+}
+
+#[tokio::test]
+async fn test_conversation_colors_tools_and_errors() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, style::Color, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    let project_dir = projects_dir.join("color-project");
+    fs::create_dir_all(&project_dir).unwrap();
+
+    let content = [
+        json!({
+            "type": "user",
+            "message": {"role": "user", "content": "Please list files"},
+            "timestamp": "2024-01-15T10:00:00Z",
+            "uuid": "msg-1"
+        }),
+        json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": "t1", "name": "Bash", "input": {"command": "ls"}}
+            ]},
+            "timestamp": "2024-01-15T10:00:01Z",
+            "uuid": "msg-2"
+        }),
+        json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "src"}
+            ]},
+            "timestamp": "2024-01-15T10:00:02Z",
+            "uuid": "msg-3"
+        }),
+        json!({
+            "type": "user",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "No such file", "is_error": true}
+            ]},
+            "timestamp": "2024-01-15T10:00:03Z",
+            "uuid": "msg-4"
+        }),
+        json!({
+            "type": "assistant",
+            "message": {"role": "assistant", "content": "The build failed with an Error"},
+            "timestamp": "2024-01-15T10:00:04Z",
+            "uuid": "msg-5"
+        }),
+    ]
+    .iter()
+    .map(|v| v.to_string())
+    .collect::<Vec<_>>()
+    .join("\n");
+    fs::write(project_dir.join("colors.jsonl"), content).unwrap();
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+
+    // Row 0 is the breadcrumb and row 1 the block border; the first message
+    // starts at row 2. Sample a column inside the timestamp, which every
+    // line shares.
+    let buffer = terminal.backend().buffer();
+    let colors: Vec<Color> = (2..=6).map(|row| buffer[(6, row)].fg).collect();
+    assert_eq!(
+        colors,
+        vec![
+            Color::Cyan,
+            Color::Magenta,
+            Color::Blue,
+            Color::Red,
+            Color::Red
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_missing_projects_dir_message() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    fs::create_dir_all(&projects_dir).unwrap();
+    let app_state = AppState::new(projects_dir.clone()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+
+    // Removed out from under a running viewer
+    fs::remove_dir_all(&projects_dir).unwrap();
+    tui_app.refresh_projects().await.unwrap();
+    assert!(tui_app.projects.is_empty());
+    assert!(tui_app.status_message.contains("does not exist"));
+}
+
+#[tokio::test]
+async fn test_conversation_marks_tool_generated_user_turns() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path();
+    let project_dir = projects_dir.join("turns-project");
+    fs::create_dir_all(&project_dir).unwrap();
+
+    let content = [
+        json!({
+            "type": "user",
+            "userType": "external",
+            "message": {"role": "user", "content": "Please list files"},
+            "timestamp": "2024-01-15T10:00:00Z"
+        }),
+        json!({
+            "type": "user",
+            "userType": "external",
+            "message": {"role": "user", "content": [
+                {"type": "tool_result", "tool_use_id": "t1", "content": "src"}
+            ]},
+            "timestamp": "2024-01-15T10:00:01Z"
+        }),
+        json!({
+            "type": "user",
+            "userType": "internal",
+            "message": {"role": "user", "content": "Injected by the harness"},
+            "timestamp": "2024-01-15T10:00:02Z"
+        }),
+    ]
+    .iter()
+    .map(|v| v.to_string())
+    .collect::<Vec<_>>()
+    .join("\n");
+    fs::write(project_dir.join("turns.jsonl"), content).unwrap();
+
+    let app_state = AppState::new(projects_dir.to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+
+    let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let icons: Vec<&str> = (2..=4).map(|row| buffer[(1, row)].symbol()).collect();
+    assert_eq!(icons, vec!["👤", "📋", "📋"]);
+}
+
+#[tokio::test]
+async fn test_message_detail_shows_model() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("model-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let entry = json!({
+        "type": "assistant",
+        "message": {"role": "assistant", "model": "claude-3-5-sonnet", "content": "Hello"},
+        "timestamp": "2024-01-15T10:00:00Z"
+    });
+    fs::write(project_dir.join("model.jsonl"), entry.to_string()).unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    for _ in 0..3 {
+        tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(120, 10)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..120).map(|x| buffer[(x, 1)].symbol()).collect();
+    assert!(title.contains("assistant (claude-3-5-sonnet)"), "{}", title);
+}
+
+#[tokio::test]
+async fn test_message_detail_scrolls_to_end_of_wrapped_text() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("wrap-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    // One logical line that wraps over many rows of a narrow terminal
+    let long_text = format!("{} the very end", "word ".repeat(200));
+    let entry = json!({
+        "type": "assistant",
+        "message": {"role": "assistant", "content": long_text},
+        "timestamp": "2024-01-15T10:00:00Z"
+    });
+    fs::write(project_dir.join("wrap.jsonl"), entry.to_string()).unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    for _ in 0..3 {
+        tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    tui_app.handle_key_event(KeyCode::End).await.unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..12)
+        .flat_map(|y| (0..40).map(move |x| (x, y)))
+        .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+        .collect();
+    assert!(screen.contains("the very end"), "{}", screen);
+}
+
+#[tokio::test]
+async fn test_message_detail_shows_entry_cost() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("cost-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let entry = json!({
+        "type": "assistant",
+        "message": {
+            "role": "assistant",
+            "model": "claude-sonnet-4",
+            "content": "done",
+            "usage": {"input_tokens": 1_000, "output_tokens": 1_000}
+        },
+        "timestamp": "2024-01-15T10:00:00Z"
+    });
+    fs::write(project_dir.join("cost.jsonl"), entry.to_string()).unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    for _ in 0..3 {
+        tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(160, 12)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen: String = (0..12)
+        .flat_map(|y| (0..160).map(move |x| (x, y)))
+        .map(|(x, y)| buffer[(x, y)].symbol().to_string())
+        .collect();
+    // $0.003 of input plus $0.015 of output
+    assert!(screen.contains("~$0.0180"), "{}", screen);
+}
+
+#[tokio::test]
+async fn test_breadcrumb_tracks_position() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.breadcrumb(), " project 1 of 1  [Projects]");
+
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert!(tui_app
+        .breadcrumb()
+        .starts_with(" test-project / session 1 of "));
+    assert!(tui_app.breadcrumb().ends_with("[Sessions]"));
+
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    let total = tui_app.conversation.len();
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    let breadcrumb = tui_app.breadcrumb();
+    assert!(
+        breadcrumb.ends_with(&format!(" / message 2 of {}  [Conversation]", total)),
+        "{}",
+        breadcrumb
+    );
+
+    // Drawn on the top row of every frame
+    let mut terminal = Terminal::new(TestBackend::new(100, 10)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let top: String = (0..100).map(|x| buffer[(x, 0)].symbol()).collect();
+    assert!(top.contains(&format!("message 2 of {}", total)), "{}", top);
+}
+
+#[tokio::test]
+async fn test_session_list_filter() {
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(tui_app.sessions.len(), 2);
+
+    // Narrows live as the filter is typed, case-insensitively
+    tui_app.handle_key_event(KeyCode::Char('/')).await.unwrap();
+    for c in "session 1".chars() {
+        tui_app.handle_key_event(KeyCode::Char(c)).await.unwrap();
+    }
+    assert_eq!(tui_app.session_filter.as_deref(), Some("session 1"));
+    assert_eq!(tui_app.sessions.len(), 1);
+    assert_eq!(tui_app.sessions[0].summary, "Test Session 1");
+
+    // Session ids match too; nothing matching empties the selection
+    tui_app.handle_key_event(KeyCode::Backspace).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('x')).await.unwrap();
+    assert!(tui_app.sessions.is_empty());
+    assert!(tui_app.breadcrumb().contains("no sessions"));
+    for _ in 0.."session x".len() {
+        tui_app.handle_key_event(KeyCode::Backspace).await.unwrap();
+    }
+    for c in "session2".chars() {
+        tui_app.handle_key_event(KeyCode::Char(c)).await.unwrap();
+    }
+    assert_eq!(tui_app.sessions.len(), 1);
+    assert_eq!(tui_app.sessions[0].id, "session2");
+
+    // Enter keeps the filter, and the selection stays inside it
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    assert_eq!(tui_app.mode, cc_log_viewer::tui::AppMode::SessionList);
+    assert!(tui_app
+        .breadcrumb()
+        .starts_with(" test-project / session 1 of 1"));
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert!(tui_app.conversation.iter().any(|e| e
+        .message
+        .as_ref()
+        .is_some_and(|m| m["content"] == "Hello from session 2")));
+
+    // Back in the list the filter is still applied until Esc clears it
+    tui_app.handle_key_event(KeyCode::Esc).await.unwrap();
+    assert_eq!(tui_app.sessions.len(), 1);
+    tui_app.handle_key_event(KeyCode::Esc).await.unwrap();
+    assert_eq!(tui_app.session_filter, None);
+    assert_eq!(tui_app.sessions.len(), 2);
+    assert_eq!(tui_app.mode, cc_log_viewer::tui::AppMode::SessionList);
+}
+
+#[tokio::test]
+async fn test_session_list_sorting() {
+    use cc_log_viewer::tui::SortKey;
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    let ids = |app: &TuiApp| -> Vec<String> { app.sessions.iter().map(|s| s.id.clone()).collect() };
+
+    // Newest activity first by default
+    assert_eq!(tui_app.session_sort, SortKey::Activity);
+    assert_eq!(ids(&tui_app), ["session2", "session1"]);
+
+    tui_app.handle_key_event(KeyCode::Char('s')).await.unwrap();
+    assert_eq!(tui_app.session_sort, SortKey::MessageCount);
+    let counts: Vec<usize> = tui_app.sessions.iter().map(|s| s.message_count).collect();
+    assert!(counts.windows(2).all(|w| w[0] >= w[1]), "{:?}", counts);
+
+    tui_app.handle_key_event(KeyCode::Char('s')).await.unwrap();
+    assert_eq!(tui_app.session_sort, SortKey::Name);
+    assert_eq!(ids(&tui_app), ["session1", "session2"]);
+
+    // S reverses; the highlighted session follows its row
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Char('S')).await.unwrap();
+    assert_eq!(ids(&tui_app), ["session2", "session1"]);
+    assert!(tui_app.breadcrumb().contains("session 1 of 2"));
+
+    // The sort survives a refresh and is shown in the list title
+    tui_app.handle_key_event(KeyCode::Char('r')).await.unwrap();
+    assert_eq!(ids(&tui_app), ["session2", "session1"]);
+    let mut terminal = Terminal::new(TestBackend::new(160, 10)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let title: String = (0..160).map(|x| buffer[(x, 1)].symbol()).collect();
+    assert!(title.contains("[by name, reversed]"), "{}", title);
+
+    tui_app.handle_key_event(KeyCode::Char('s')).await.unwrap();
+    assert_eq!(tui_app.session_sort, SortKey::Activity);
+}
+
+#[tokio::test]
+async fn test_message_detail_renders_markdown() {
+    use crossterm::event::KeyCode;
+    use ratatui::{
+        backend::TestBackend,
+        style::{Color, Modifier},
+        Terminal,
+    };
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("markdown-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let reply = "# Plan\n\nUse **bold** and `cargo test`.\n\n- first step\n\n```rust\nlet x = 2 * 3; // **raw**\n```\n\nA lone * star";
+    let entry = json!({
+        "type": "assistant",
+        "message": {"role": "assistant", "content": reply},
+        "timestamp": "2024-01-15T10:00:00Z"
+    });
+    fs::write(project_dir.join("md.jsonl"), entry.to_string()).unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    for _ in 0..3 {
+        tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    }
+
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|f| tui_app.ui(f)).unwrap();
+    let buffer = terminal.backend().buffer();
+    let rows: Vec<String> = (0..24)
+        .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect())
+        .collect();
+    let find = |needle: &str| -> (u16, u16) {
+        rows.iter()
+            .enumerate()
+            .find_map(|(y, row)| {
+                row.find(needle)
+                    .map(|byte| (row[..byte].chars().count() as u16, y as u16))
+            })
+            .unwrap_or_else(|| panic!("{:?} not in {:#?}", needle, rows))
+    };
+
+    // Markers are replaced by styling
+    assert!(!rows.iter().any(|r| r.contains("# Plan")), "{:#?}", rows);
+    let (x, y) = find("Plan");
+    assert!(buffer[(x, y)].modifier.contains(Modifier::BOLD));
+    let (x, y) = find("bold and");
+    assert!(buffer[(x, y)].modifier.contains(Modifier::BOLD));
+    assert!(!rows.iter().any(|r| r.contains("**bold**")));
+    let (x, y) = find("cargo test.");
+    assert_eq!(buffer[(x, y)].fg, Color::Cyan);
+    find("• first step");
+
+    // Code blocks and unmatched markers are shown as written
+    let (x, y) = find("let x = 2 * 3; // **raw**");
+    assert_eq!(buffer[(x, y)].fg, Color::Cyan);
+    find("A lone * star");
+}
+
+#[tokio::test]
+async fn test_last_viewed_session_is_restored() {
+    use cc_log_viewer::tui::{AppMode, LastViewed};
+    use crossterm::event::KeyCode;
+
+    let temp_dir = TempDir::new().unwrap();
+    let state_dir = TempDir::new().unwrap();
+    let state_file = state_dir
+        .path()
+        .join("cc-log-viewer")
+        .join("tui-state.json");
+    create_test_project_structure(&temp_dir);
+
+    // Opening a session records it
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state).with_state_file(state_file.clone());
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Down).await.unwrap();
+    let session = tui_app.sessions[1].id.clone();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    assert_eq!(
+        LastViewed::load(&state_file),
+        Some(LastViewed {
+            project: "test-project".to_string(),
+            session: Some(session.clone()),
+        })
+    );
+
+    // The next run lands back in that conversation
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state).with_state_file(state_file.clone());
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ConversationView);
+    assert!(tui_app
+        .breadcrumb()
+        .starts_with(" test-project / Test Session 1 /"));
+    assert!(!tui_app.conversation.is_empty());
+
+    // A session that is gone leaves the project's session list open
+    fs::remove_file(
+        temp_dir
+            .path()
+            .join("test-project")
+            .join(format!("{}.jsonl", session)),
+    )
+    .unwrap();
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state).with_state_file(state_file.clone());
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::SessionList);
+    assert_eq!(tui_app.sessions.len(), 1);
+
+    // A project that is gone falls back to the project list
+    LastViewed {
+        project: "deleted-project".to_string(),
+        session: None,
+    }
+    .save(&state_file)
+    .unwrap();
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state).with_state_file(state_file);
+    tui_app.refresh_projects().await.unwrap();
+    assert_eq!(tui_app.mode, AppMode::ProjectList);
+}
+
+#[tokio::test]
+async fn test_periodic_refresh_interval() {
+    use cc_log_viewer::tui::{DEFAULT_POLL_INTERVAL, DEFAULT_REFRESH_INTERVAL};
+
+    let temp_dir = TempDir::new().unwrap();
+    create_test_project_structure(&temp_dir);
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let tui_app = TuiApp::new(app_state);
+    assert_eq!(tui_app.refresh_interval, Some(DEFAULT_REFRESH_INTERVAL));
+    assert_eq!(tui_app.poll_interval, DEFAULT_POLL_INTERVAL);
+
+    let add_project = |name: &str| {
+        let dir = temp_dir.path().join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("s.jsonl"),
+            json!({"type": "user", "message": {"role": "user", "content": "hi"}, "timestamp": "2024-01-15T10:00:00Z"})
+                .to_string(),
+        )
+        .unwrap();
+    };
+
+    // Disabled: only a manual refresh picks up new projects
+    let mut tui_app = tui_app.with_intervals(None, Duration::from_millis(20));
+    tui_app.refresh_projects().await.unwrap();
+    add_project("second-project");
+    tui_app.refresh_if_due().await.unwrap();
+    assert_eq!(tui_app.projects.len(), 1);
+    tui_app
+        .handle_key_event(crossterm::event::KeyCode::Char('r'))
+        .await
+        .unwrap();
+    assert_eq!(tui_app.projects.len(), 2);
+
+    // A long interval holds off, a short one refreshes
+    let mut tui_app =
+        tui_app.with_intervals(Some(Duration::from_secs(3600)), DEFAULT_POLL_INTERVAL);
+    add_project("third-project");
+    tui_app.refresh_if_due().await.unwrap();
+    assert_eq!(tui_app.projects.len(), 2);
+    let mut tui_app = tui_app.with_intervals(Some(Duration::ZERO), DEFAULT_POLL_INTERVAL);
+    tui_app.refresh_if_due().await.unwrap();
+    assert_eq!(tui_app.projects.len(), 3);
+}
+
+#[tokio::test]
+async fn test_empty_conversation_placeholder() {
+    use crossterm::event::KeyCode;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    let temp_dir = TempDir::new().unwrap();
+    let project_dir = temp_dir.path().join("empty-project");
+    fs::create_dir_all(&project_dir).unwrap();
+    let session_file = project_dir.join("emptied.jsonl");
+    fs::write(
+        &session_file,
+        json!({"type": "user", "message": {"role": "user", "content": "soon gone"}, "timestamp": "2024-01-15T10:00:00Z"})
+            .to_string(),
+    )
+    .unwrap();
+
+    let app_state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let mut tui_app = TuiApp::new(app_state);
+    tui_app.refresh_projects().await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    tui_app.handle_key_event(KeyCode::Enter).await.unwrap();
+    fs::write(&session_file, "").unwrap();
+    tui_app.handle_key_event(KeyCode::Char('r')).await.unwrap();
+    assert!(tui_app.conversation.is_empty());
+
+    let screen = |tui_app: &mut TuiApp| -> String {
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        terminal.draw(|f| tui_app.ui(f)).unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..12)
+            .map(|y| (0..80).map(|x| buffer[(x, y)].symbol()).collect::<String>() + "\n")
+            .collect()
+    };
+
+    let idle = screen(&mut tui_app);
+    assert!(
+        idle.contains("No messages yet — press r to refresh"),
+        "{}",
+        idle
+    );
+    assert!(!idle.contains("Loading"), "{}", idle);
+
+    tui_app.loading = true;
+    let loading = screen(&mut tui_app);
+    assert!(loading.contains("Loading…"), "{}", loading);
+    assert!(!loading.contains("No messages yet"), "{}", loading);
+}
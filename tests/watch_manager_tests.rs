@@ -3,14 +3,14 @@
 
 use serde_json::json;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::{Duration, SystemTime};
+use std::time::Duration;
 use tempfile::TempDir;
 use tokio::time::{sleep, timeout};
 
 // Import types we need to test
-use cc_log_viewer::{SessionState, WatchEvent, WatchManager};
+use cc_log_viewer::WatchManager;
 
 // Helper functions for creating test data
 fn create_test_entry(id: &str, content: &str) -> String {
@@ -54,7 +54,7 @@ async fn test_watch_manager_with_nonexistent_directory() {
     let nonexistent_dir = temp_dir.path().join("does-not-exist");
 
     // Should handle nonexistent directory gracefully
-    let watch_manager = WatchManager::new(nonexistent_dir);
+    let _watch_manager = WatchManager::new(nonexistent_dir);
     // Note: This might succeed or fail depending on notify crate behavior
     // The important thing is it doesn't panic
 }
@@ -115,7 +115,7 @@ async fn test_multiple_session_tracking() {
     let mut rx = watch_manager.subscribe();
 
     // Create multiple session files
-    let sessions = vec!["session-a", "session-b", "session-c"];
+    let sessions = ["session-a", "session-b", "session-c"];
 
     for (i, session_name) in sessions.iter().enumerate() {
         let session_file = project_dir.join(format!("{}.jsonl", session_name));
@@ -201,7 +201,7 @@ async fn test_incremental_file_reading() {
         .unwrap();
 
     // Append more content in stages
-    let entries = vec![
+    let entries = [
         create_test_entry("entry-2", "Second entry"),
         create_test_entry("entry-3", "Third entry"),
         create_test_entry("entry-4", "Fourth entry"),
@@ -291,7 +291,7 @@ async fn test_non_jsonl_file_filtering() {
 
     while let Ok(Ok(event)) = timeout(Duration::from_millis(500), rx.recv()).await {
         total_events += 1;
-        if event.session.as_ref().map_or(false, |s| s == "valid") {
+        if event.session.as_ref().is_some_and(|s| s == "valid") {
             jsonl_events += 1;
         }
         if total_events > 10 {
@@ -391,7 +391,7 @@ async fn test_session_state_persistence() {
     {
         let watch_manager = WatchManager::new(projects_dir.clone()).unwrap();
         let mut rx = watch_manager.subscribe();
-        if let Err(_) = timeout(Duration::from_secs(5), rx.recv()).await {
+        if timeout(Duration::from_secs(5), rx.recv()).await.is_err() {
             eprintln!("Warning: First event timeout in session persistence test - timing issue");
             return;
         }
@@ -403,7 +403,7 @@ async fn test_session_state_persistence() {
         updated_content.push_str(&create_test_entry("appended", "Appended"));
         fs::write(&session_file, updated_content).unwrap();
 
-        if let Err(_) = timeout(Duration::from_secs(5), rx.recv()).await {
+        if timeout(Duration::from_secs(5), rx.recv()).await.is_err() {
             eprintln!("Warning: Second event timeout in session persistence test - timing issue");
             return;
         }
@@ -449,7 +449,7 @@ async fn test_error_handling() {
 
     // Create file with invalid JSON mixed with valid JSON
     let session_file = project_dir.join("error-test.jsonl");
-    let mixed_content = vec![
+    let mixed_content = [
         create_test_entry("valid-1", "Valid entry"),
         "invalid json line".to_string(),
         "{incomplete json".to_string(),
@@ -486,3 +486,313 @@ async fn test_error_handling() {
     );
     assert_eq!(valid_events, 2, "Should skip invalid JSON lines");
 }
+
+// A positions file as WatchManager saves it, with one session entry
+fn saved_positions(session_file: &std::path::Path, position: u64) -> String {
+    let mut saved = serde_json::Map::new();
+    saved.insert(
+        session_file.to_string_lossy().to_string(),
+        json!({
+            "last_position": position,
+            "last_modified": {"secs_since_epoch": 0, "nanos_since_epoch": 0}
+        }),
+    );
+    serde_json::Value::Object(saved).to_string()
+}
+
+#[tokio::test]
+async fn test_positions_resume_after_restart() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    let project_dir = create_test_project_structure(&projects_dir);
+    let positions_file = temp_dir.path().join("cache").join("positions.json");
+
+    let session_file = project_dir.join("session-1.jsonl");
+    let old_content = format!(
+        "{}\n{}\n",
+        create_test_entry("old-1", "Seen before restart"),
+        create_test_entry("old-2", "Also seen before restart")
+    );
+    fs::write(&session_file, &old_content).unwrap();
+    fs::create_dir_all(positions_file.parent().unwrap()).unwrap();
+    fs::write(
+        &positions_file,
+        saved_positions(&session_file, old_content.len() as u64),
+    )
+    .unwrap();
+
+    let watch_manager =
+        WatchManager::with_positions_file(projects_dir.clone(), Some(positions_file.clone()))
+            .unwrap();
+    let mut rx = watch_manager.subscribe();
+
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&session_file)
+        .unwrap();
+    writeln!(file, "{}", create_test_entry("new-1", "After restart")).unwrap();
+    drop(file);
+
+    let event = timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("Should receive the appended entry")
+        .unwrap();
+    assert_eq!(
+        event.entry.unwrap().uuid.as_deref(),
+        Some("new-1"),
+        "Entries from before the restart should not be replayed"
+    );
+
+    // The advanced position is written back after the flush interval.
+    sleep(Duration::from_millis(1500)).await;
+    let saved: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&positions_file).unwrap()).unwrap();
+    let saved_position = saved[session_file.to_string_lossy().as_ref()]["last_position"]
+        .as_u64()
+        .unwrap();
+    assert_eq!(saved_position, fs::metadata(&session_file).unwrap().len());
+}
+
+#[tokio::test]
+async fn test_positions_are_not_rewritten_when_nothing_advanced() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    let project_dir = create_test_project_structure(&projects_dir);
+    let positions_file = temp_dir.path().join("positions.json");
+    let session_file = project_dir.join("session-1.jsonl");
+
+    let watch_manager =
+        WatchManager::with_positions_file(projects_dir.clone(), Some(positions_file.clone()))
+            .unwrap();
+    let _rx = watch_manager.subscribe();
+    sleep(Duration::from_millis(100)).await;
+    fs::write(
+        &session_file,
+        format!("{}\n", create_test_entry("1", "Advances the position")),
+    )
+    .unwrap();
+
+    let saved = timeout(Duration::from_secs(3), async {
+        while !positions_file.exists() {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await;
+    assert!(saved.is_ok(), "The advanced position should be saved");
+    fs::remove_file(&positions_file).unwrap();
+
+    // A line that is not an entry leaves the position where it was
+    let mut file = fs::OpenOptions::new()
+        .append(true)
+        .open(&session_file)
+        .unwrap();
+    writeln!(file, "not json").unwrap();
+    drop(file);
+
+    sleep(Duration::from_millis(1500)).await;
+    assert!(
+        !positions_file.exists(),
+        "Positions should only be saved when one moved"
+    );
+}
+
+#[tokio::test]
+async fn test_stale_position_for_shrunk_file_is_ignored() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().join("projects");
+    let project_dir = create_test_project_structure(&projects_dir);
+    let positions_file = temp_dir.path().join("positions.json");
+
+    let session_file = project_dir.join("session-1.jsonl");
+    fs::write(&session_file, "").unwrap();
+    fs::write(&positions_file, saved_positions(&session_file, 100_000)).unwrap();
+
+    let watch_manager =
+        WatchManager::with_positions_file(projects_dir.clone(), Some(positions_file)).unwrap();
+    let mut rx = watch_manager.subscribe();
+
+    fs::write(
+        &session_file,
+        format!("{}\n", create_test_entry("fresh-1", "Rewritten file")),
+    )
+    .unwrap();
+
+    let event = timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("A shrunk file should be read from the start")
+        .unwrap();
+    assert_eq!(event.entry.unwrap().uuid.as_deref(), Some("fresh-1"));
+}
+
+#[test]
+fn test_read_new_entries_from_position() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("session.jsonl");
+    let first = create_test_entry("1", "first");
+    let second = create_test_entry("2", "second");
+    let third = create_test_entry("3", "third");
+    fs::write(&path, format!("{}\nnot json\n{}\n{}", first, second, third)).unwrap();
+
+    let all = WatchManager::read_new_entries(&path, 0).unwrap();
+    let ends: Vec<u64> = all.iter().map(|(_, pos)| *pos).collect();
+    let second_end = (first.len() + "\nnot json\n".len() + second.len() + 1) as u64;
+    let file_len = fs::metadata(&path).unwrap().len();
+    assert_eq!(ends, vec![first.len() as u64 + 1, second_end, file_len]);
+
+    let rest = WatchManager::read_new_entries(&path, second_end).unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].0.uuid.as_deref(), Some("3"));
+    assert_eq!(rest[0].1, file_len);
+
+    assert!(WatchManager::read_new_entries(&path, file_len)
+        .unwrap()
+        .is_empty());
+    assert!(
+        WatchManager::read_new_entries(&temp_dir.path().join("missing.jsonl"), 0)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_read_new_entries_handles_crlf_and_bom() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("windows.jsonl");
+    let first = create_test_entry("1", "first");
+    let second = create_test_entry("2", "second");
+    let content = format!("\u{feff}{}\r\n{}\r\n", first, second);
+    fs::write(&path, &content).unwrap();
+
+    let entries = WatchManager::read_new_entries(&path, 0).unwrap();
+    let uuids: Vec<_> = entries.iter().map(|(e, _)| e.uuid.as_deref()).collect();
+    assert_eq!(uuids, vec![Some("1"), Some("2")]);
+    // Positions still count the BOM and carriage returns
+    let first_end = ("\u{feff}".len() + first.len() + 2) as u64;
+    assert_eq!(entries[0].1, first_end);
+    assert_eq!(entries[1].1, content.len() as u64);
+
+    let rest = WatchManager::read_new_entries(&path, first_end).unwrap();
+    assert_eq!(rest.len(), 1);
+    assert_eq!(rest[0].0.uuid.as_deref(), Some("2"));
+}
+
+#[tokio::test]
+async fn test_single_write_broadcasts_each_entry_once() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().to_path_buf();
+    let project_dir = create_test_project_structure(projects_dir.as_path());
+
+    let watch_manager = WatchManager::new(projects_dir.clone()).unwrap();
+    let mut rx = watch_manager.subscribe();
+
+    // Creating a file and writing it fires CREATE and MODIFY events
+    let content: String = (1..=3)
+        .map(|i| create_test_entry(&format!("entry-{}", i), "hello") + "\n")
+        .collect();
+    fs::write(project_dir.join("once.jsonl"), content).unwrap();
+
+    let mut uuids = Vec::new();
+    while let Ok(Ok(event)) = timeout(Duration::from_millis(500), rx.recv()).await {
+        assert_eq!(event.event_type, "log_entry");
+        uuids.extend(event.entry.and_then(|e| e.uuid));
+    }
+    assert_eq!(uuids, vec!["entry-1", "entry-2", "entry-3"]);
+}
+
+#[tokio::test]
+async fn test_new_project_directory_is_announced_and_watched() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().to_path_buf();
+    let existing = create_test_project_structure(projects_dir.as_path());
+
+    let watch_manager = WatchManager::new(projects_dir.clone()).unwrap();
+    let mut rx = watch_manager.subscribe();
+
+    // Only directories directly under projects_dir are projects
+    fs::create_dir(existing.join("nested")).unwrap();
+    fs::write(projects_dir.join("stray.jsonl"), "").unwrap();
+    fs::create_dir(projects_dir.join("new-project")).unwrap();
+
+    let event = timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .expect("no project_created event")
+        .unwrap();
+    assert_eq!(event.event_type, "project_created");
+    assert_eq!(event.project, "new-project");
+    assert!(event.session.is_none());
+
+    // Sessions in the new project are followed without a restart
+    sleep(Duration::from_millis(100)).await;
+    fs::write(
+        projects_dir.join("new-project").join("s.jsonl"),
+        create_test_entry("fresh", "hello") + "\n",
+    )
+    .unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(Ok(event)) = timeout(Duration::from_millis(500), rx.recv()).await {
+        events.push(event);
+    }
+    assert!(events.iter().all(|e| e.event_type == "log_entry"));
+    assert!(events
+        .iter()
+        .any(|e| e.project == "new-project" && e.session.as_deref() == Some("s")));
+}
+
+#[tokio::test]
+async fn test_broadcast_capacity_limits_buffered_events() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().to_path_buf();
+    let project_dir = create_test_project_structure(projects_dir.as_path());
+
+    assert!(WatchManager::with_options(projects_dir.clone(), None, 0).is_err());
+
+    let watch_manager = WatchManager::with_options(projects_dir.clone(), None, 4).unwrap();
+    assert_eq!(watch_manager.broadcast_capacity(), 4);
+    let mut rx = watch_manager.subscribe();
+
+    let content: String = (1..=8)
+        .map(|i| create_test_entry(&format!("entry-{}", i), "hello") + "\n")
+        .collect();
+    fs::write(project_dir.join("busy.jsonl"), content).unwrap();
+    sleep(Duration::from_millis(500)).await;
+
+    // A subscriber that didn't keep up misses all but the newest 4 events
+    match rx.recv().await {
+        Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => assert_eq!(skipped, 4),
+        other => panic!("expected a lag, got {:?}", other.map(|e| e.event_type)),
+    }
+    let event = rx.recv().await.unwrap();
+    assert_eq!(event.entry.unwrap().uuid.as_deref(), Some("entry-5"));
+}
+
+#[tokio::test]
+async fn test_nested_session_events_name_project_and_relative_session() {
+    let temp_dir = TempDir::new().unwrap();
+    let projects_dir = temp_dir.path().to_path_buf();
+    let dated_dir = create_test_project_structure(projects_dir.as_path()).join("2024-06-01");
+    fs::create_dir_all(&dated_dir).unwrap();
+
+    let watch_manager = WatchManager::new(projects_dir.clone()).unwrap();
+    let mut rx = watch_manager.subscribe();
+    sleep(Duration::from_millis(100)).await;
+
+    fs::write(
+        dated_dir.join("session-1.jsonl"),
+        create_test_entry("entry-1", "Nested message"),
+    )
+    .unwrap();
+
+    let event = timeout(Duration::from_secs(2), async {
+        loop {
+            let event = rx.recv().await.unwrap();
+            if event.entry.is_some() {
+                return event;
+            }
+        }
+    })
+    .await
+    .expect("Should receive an entry event");
+    assert_eq!(event.project, "test-project");
+    assert_eq!(event.session, Some("2024-06-01/session-1".to_string()));
+}
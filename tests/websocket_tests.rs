@@ -2,7 +2,7 @@
 // ABOUTME: Tests the /ws/watch endpoint and live streaming capabilities
 
 use axum_test::TestServer;
-use futures_util::stream::StreamExt;
+use futures_util::{SinkExt, StreamExt};
 use serde_json::json;
 use std::fs;
 use std::time::Duration;
@@ -12,8 +12,8 @@ use tokio_tungstenite::{connect_async, tungstenite::protocol::Message as WsMessa
 
 // Import our app functions and types - using the crate directly since tests are integration tests
 use cc_log_viewer::{
-    get_projects, get_session_logs, get_sessions, index, live_activity, websocket_handler,
-    AppState, WatchEvent, WatchManager,
+    get_projects, get_session_logs, get_sessions, index, live_activity, sse_handler,
+    websocket_handler, AppState, WatchEvent, WatchManager,
 };
 
 // Helper to create test app state
@@ -33,9 +33,19 @@ async fn create_test_app(projects_dir: std::path::PathBuf) -> axum::Router {
             axum::routing::get(get_session_logs),
         )
         .route("/ws/watch", axum::routing::get(websocket_handler))
+        .route("/events", axum::routing::get(sse_handler))
         .with_state(state)
 }
 
+// Helper to serve an app over a real listener; the mock transport has no
+// address for a WebSocket client to connect to
+async fn serve_app(app: axum::Router) -> std::net::SocketAddr {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    addr
+}
+
 // Helper to create sample log entry with tool use
 fn create_tool_use_entry() -> String {
     json!({
@@ -374,7 +384,7 @@ async fn test_malformed_jsonl_handling() {
 
     // Write file with mix of valid and invalid JSON
     let session_file = project_path.join("mixed.jsonl");
-    let mixed_content = vec![
+    let mixed_content = [
         "invalid json line",
         &create_tool_use_entry(),
         "{incomplete json",
@@ -400,3 +410,467 @@ async fn test_malformed_jsonl_handling() {
         "Should process exactly 2 valid JSON entries (tool use and tool result)"
     );
 }
+
+#[tokio::test]
+async fn test_websocket_project_subscription() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let server = TestServer::new(app).unwrap();
+
+    let wanted = temp_dir.path().join("wanted");
+    let other = temp_dir.path().join("other");
+    fs::create_dir_all(&wanted).unwrap();
+    fs::create_dir_all(&other).unwrap();
+
+    let server_addr = match server.server_address() {
+        Some(addr) => addr,
+        None => {
+            eprintln!("Warning: Cannot get server address, skipping WebSocket test");
+            return;
+        }
+    };
+    let ws_url = format!("ws://{}/ws/watch", server_addr);
+    let (ws_stream, _) = connect_async(&ws_url).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    ws_sender
+        .send(WsMessage::Text(
+            json!({"subscribe": {"project": "wanted"}}).to_string(),
+        ))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    fs::write(other.join("noise.jsonl"), create_tool_use_entry()).unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    fs::write(wanted.join("signal.jsonl"), create_tool_use_entry()).unwrap();
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(3);
+    let mut saw_wanted = false;
+    while let Ok(Some(Ok(msg))) = tokio::time::timeout_at(deadline, ws_receiver.next()).await {
+        if let WsMessage::Text(text) = msg {
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(
+                event["project"], "wanted",
+                "unsubscribed project leaked through"
+            );
+            saw_wanted = true;
+            break;
+        }
+    }
+    assert!(
+        saw_wanted,
+        "Should receive events for the subscribed project"
+    );
+}
+
+#[tokio::test]
+async fn test_websocket_catchup_replays_recent_entries() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_path = temp_dir.path().join("catchup");
+    fs::create_dir_all(&project_path).unwrap();
+    let lines: Vec<String> = (1..=3)
+        .map(|i| {
+            json!({
+                "type": "user",
+                "uuid": format!("entry-{}", i),
+                "message": {"role": "user", "content": format!("message {}", i)},
+                "timestamp": format!("2024-01-15T10:0{}:00Z", i)
+            })
+            .to_string()
+        })
+        .collect();
+    fs::write(project_path.join("s1.jsonl"), lines.join("\n")).unwrap();
+
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let server_addr = serve_app(app).await;
+    let ws_url = format!("ws://{}/ws/watch", server_addr);
+    let (ws_stream, _) = connect_async(&ws_url).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    ws_sender
+        .send(WsMessage::Text(
+            json!({"catchup": {"project": "catchup", "session": "s1", "last_n": 2}}).to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let mut uuids = Vec::new();
+    while uuids.len() < 2 {
+        let msg = timeout(Duration::from_secs(3), ws_receiver.next())
+            .await
+            .expect("Should receive catch-up entries")
+            .unwrap()
+            .unwrap();
+        if let WsMessage::Text(text) = msg {
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+            assert_eq!(event["type"], "log_entry");
+            assert_eq!(event["session"], "s1");
+            uuids.push(event["entry"]["uuid"].as_str().unwrap().to_string());
+        }
+    }
+    assert_eq!(uuids, vec!["entry-2", "entry-3"]);
+}
+
+#[tokio::test]
+async fn test_websocket_catchup_is_capped_and_reads_from_the_end() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_path = temp_dir.path().join("catchup");
+    fs::create_dir_all(&project_path).unwrap();
+    // Large enough to span several read chunks, with a padded line that
+    // crosses chunk boundaries and a non-entry line mixed in.
+    let mut lines: Vec<String> = (1..=1500)
+        .map(|i| {
+            json!({
+                "type": "user",
+                "uuid": format!("entry-{}", i),
+                "message": {"role": "user", "content": format!("message {} {}", i, "x".repeat(i % 300))},
+                "timestamp": "2024-01-15T10:00:00Z"
+            })
+            .to_string()
+        })
+        .collect();
+    lines.insert(1200, "not json".to_string());
+    fs::write(project_path.join("big.jsonl"), lines.join("\n") + "\n").unwrap();
+
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let server_addr = serve_app(app).await;
+    let (ws_stream, _) = connect_async(format!("ws://{}/ws/watch", server_addr))
+        .await
+        .unwrap();
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    ws_sender
+        .send(WsMessage::Text(
+            json!({"catchup": {"project": "catchup", "session": "big", "last_n": 5000}})
+                .to_string(),
+        ))
+        .await
+        .unwrap();
+
+    let mut uuids = Vec::new();
+    while let Ok(Some(Ok(msg))) = timeout(Duration::from_millis(500), ws_receiver.next()).await {
+        if let WsMessage::Text(text) = msg {
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+            uuids.push(event["entry"]["uuid"].as_str().unwrap().to_string());
+        }
+    }
+    let expected: Vec<String> = (501..=1500).map(|i| format!("entry-{}", i)).collect();
+    assert_eq!(uuids, expected);
+}
+
+#[tokio::test]
+async fn test_slow_client_gets_lag_notice_and_stays_connected() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let watch_manager = state.watch_manager.clone();
+    let app = axum::Router::new()
+        .route("/ws/watch", axum::routing::get(websocket_handler))
+        .with_state(state);
+    let server_addr = serve_app(app).await;
+
+    let ws_url = format!("ws://{}/ws/watch", server_addr);
+    let (ws_stream, _) = connect_async(&ws_url).await.unwrap();
+    let (_ws_sender, mut ws_receiver) = ws_stream.split();
+
+    // Give the server a moment to subscribe this connection to the channel.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    // Publish well past the channel capacity without yielding, so the
+    // connection's receiver is guaranteed to fall behind.
+    for i in 0..1500 {
+        watch_manager.publish(WatchEvent {
+            event_type: "project_activity".to_string(),
+            project: format!("flood-{}", i),
+            session: None,
+            entry: None,
+            timestamp: chrono::Utc::now(),
+        });
+    }
+
+    let notice = loop {
+        let msg = timeout(Duration::from_secs(5), ws_receiver.next())
+            .await
+            .expect("Should receive a lag notice")
+            .expect("Connection should stay open")
+            .unwrap();
+        if let WsMessage::Text(text) = msg {
+            let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+            if event["type"] == "lagged" {
+                break event;
+            }
+        }
+    };
+    assert!(notice["skipped"].as_u64().unwrap() > 0);
+
+    // After the notice the stale backlog is skipped, so the next event the
+    // client sees is one published after it caught up.
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    watch_manager.publish(WatchEvent {
+        event_type: "project_activity".to_string(),
+        project: "fresh".to_string(),
+        session: None,
+        entry: None,
+        timestamp: chrono::Utc::now(),
+    });
+
+    let msg = timeout(Duration::from_secs(5), ws_receiver.next())
+        .await
+        .expect("Connection should keep delivering events")
+        .expect("Connection should stay open")
+        .unwrap();
+    let WsMessage::Text(text) = msg else {
+        panic!("Expected a text message, got {:?}", msg);
+    };
+    let event: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(event["project"], "fresh");
+}
+
+#[tokio::test]
+async fn test_websocket_tool_filter() {
+    let temp_dir = TempDir::new().unwrap();
+    let state = AppState::new(temp_dir.path().to_path_buf()).unwrap();
+    let watch_manager = state.watch_manager.clone();
+    let app = axum::Router::new()
+        .route("/ws/watch", axum::routing::get(websocket_handler))
+        .with_state(state);
+    let server = TestServer::new(app).unwrap();
+
+    let server_addr = match server.server_address() {
+        Some(addr) => addr,
+        None => {
+            eprintln!("Warning: Cannot get server address, skipping WebSocket test");
+            return;
+        }
+    };
+    let ws_url = format!("ws://{}/ws/watch", server_addr);
+    let (ws_stream, _) = connect_async(&ws_url).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    ws_sender
+        .send(WsMessage::Text(
+            json!({"filter": {"tools": ["Bash"]}}).to_string(),
+        ))
+        .await
+        .unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let tool_event = |uuid: &str, tool: &str| {
+        let entry = serde_json::from_value(json!({
+            "type": "assistant",
+            "uuid": uuid,
+            "message": {"role": "assistant", "content": [
+                {"type": "tool_use", "id": uuid, "name": tool, "input": {}}
+            ]}
+        }))
+        .unwrap();
+        WatchEvent {
+            event_type: "log_entry".to_string(),
+            project: "p".to_string(),
+            session: Some("s".to_string()),
+            entry: Some(entry),
+            timestamp: chrono::Utc::now(),
+        }
+    };
+    watch_manager.publish(tool_event("edit-1", "Edit"));
+    watch_manager.publish(tool_event("bash-1", "Bash"));
+
+    let msg = timeout(Duration::from_secs(3), ws_receiver.next())
+        .await
+        .expect("Should receive the Bash entry")
+        .unwrap()
+        .unwrap();
+    let event: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+    assert_eq!(event["entry"]["uuid"], "bash-1");
+}
+
+#[tokio::test]
+async fn test_sse_stream_filters_by_project() {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let temp_dir = TempDir::new().unwrap();
+    let wanted = temp_dir.path().join("wanted");
+    let other = temp_dir.path().join("other");
+    fs::create_dir_all(&wanted).unwrap();
+    fs::create_dir_all(&other).unwrap();
+
+    // A streaming body needs a real connection rather than the mock transport
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(b"GET /events?project=wanted HTTP/1.1\r\nHost: localhost\r\n\r\n")
+        .await
+        .unwrap();
+
+    let mut received = String::new();
+    let mut buf = [0u8; 4096];
+    while !received.contains("\r\n\r\n") {
+        let n = timeout(Duration::from_secs(3), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    }
+    assert!(received.starts_with("HTTP/1.1 200"));
+    assert!(received.contains("text/event-stream"));
+
+    fs::write(other.join("noise.jsonl"), create_tool_use_entry() + "\n").unwrap();
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    fs::write(wanted.join("signal.jsonl"), create_tool_use_entry() + "\n").unwrap();
+
+    let data = loop {
+        if let Some(line) = received.lines().find(|l| l.starts_with("data: ")) {
+            break line["data: ".len()..].to_string();
+        }
+        let n = timeout(Duration::from_secs(3), stream.read(&mut buf))
+            .await
+            .unwrap()
+            .unwrap();
+        received.push_str(&String::from_utf8_lossy(&buf[..n]));
+    };
+    let event: serde_json::Value = serde_json::from_str(&data).unwrap();
+    assert_eq!(event["type"], "log_entry");
+    assert_eq!(event["project"], "wanted");
+    assert_eq!(event["session"], "signal");
+}
+
+#[tokio::test]
+async fn test_websocket_lists_projects_and_sessions() {
+    let temp_dir = TempDir::new().unwrap();
+    let project_path = temp_dir.path().join("listed");
+    fs::create_dir_all(&project_path).unwrap();
+    fs::write(
+        project_path.join("s1.jsonl"),
+        json!({"type": "summary", "summary": "Listed session", "leafUuid": "x"}).to_string(),
+    )
+    .unwrap();
+
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let server = TestServer::new(app).unwrap();
+    let server_addr = match server.server_address() {
+        Some(addr) => addr,
+        None => {
+            eprintln!("Warning: Cannot get server address, skipping WebSocket test");
+            return;
+        }
+    };
+    let ws_url = format!("ws://{}/ws/watch", server_addr);
+    let (ws_stream, _) = connect_async(&ws_url).await.unwrap();
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    macro_rules! ask {
+        ($message:expr) => {{
+            ws_sender
+                .send(WsMessage::Text($message.to_string()))
+                .await
+                .unwrap();
+            loop {
+                let msg = timeout(Duration::from_secs(3), ws_receiver.next())
+                    .await
+                    .expect("Should receive a reply")
+                    .unwrap()
+                    .unwrap();
+                if let WsMessage::Text(text) = msg {
+                    break serde_json::from_str::<serde_json::Value>(&text).unwrap();
+                }
+            }
+        }};
+    }
+
+    let projects = ask!(json!({"list": "projects"}));
+    assert_eq!(projects["type"], "project_list");
+    assert_eq!(projects["projects"][0]["name"], "listed");
+    assert_eq!(projects["projects"][0]["session_count"], 1);
+
+    let sessions = ask!(json!({"list": "sessions", "project": "listed"}));
+    assert_eq!(sessions["type"], "session_list");
+    assert_eq!(sessions["project"], "listed");
+    assert_eq!(sessions["sessions"][0]["id"], "s1");
+    assert_eq!(sessions["sessions"][0]["summary"], "Listed session");
+
+    let missing = ask!(json!({"list": "sessions", "project": "missing"}));
+    assert_eq!(missing["type"], "error");
+    assert_eq!(missing["code"], "project_not_found");
+
+    assert_eq!(ask!(json!({"list": "sessions"}))["code"], "bad_request");
+}
+
+/// Whatever the server sends next on `stream`, empty once it hangs up.
+/// Polls rather than awaiting, so it fails after a few seconds of real
+/// time even while the test holds the clock paused.
+async fn read_available(stream: &tokio::net::TcpStream) -> Vec<u8> {
+    let started = std::time::Instant::now();
+    let mut buf = [0u8; 1024];
+    loop {
+        match stream.try_read(&mut buf) {
+            Ok(n) => return buf[..n].to_vec(),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                assert!(
+                    started.elapsed() < Duration::from_secs(5),
+                    "server sent nothing"
+                );
+                tokio::task::yield_now().await;
+            }
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_websocket_closes_silent_client_after_pong_timeout() {
+    use tokio::io::AsyncWriteExt;
+
+    // Keep the runtime busy so the paused clock only moves on `advance`,
+    // never on its own while the sockets wait
+    tokio::spawn(async {
+        loop {
+            tokio::task::yield_now().await;
+        }
+    });
+
+    let temp_dir = TempDir::new().unwrap();
+    let app = create_test_app(temp_dir.path().to_path_buf()).await;
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    // A bare socket never answers pings, unlike a WebSocket client library
+    let mut stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+    stream
+        .write_all(
+            b"GET /ws/watch HTTP/1.1\r\nHost: localhost\r\nUpgrade: websocket\r\n\
+              Connection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\n\
+              Sec-WebSocket-Version: 13\r\n\r\n",
+        )
+        .await
+        .unwrap();
+    let mut handshake = Vec::new();
+    while !handshake.windows(4).any(|w| w == b"\r\n\r\n") {
+        handshake.extend(read_available(&stream).await);
+    }
+    assert!(handshake.starts_with(b"HTTP/1.1 101"));
+
+    // Pinged after 30s of quiet
+    tokio::time::advance(Duration::from_secs(30)).await;
+    assert_eq!(read_available(&stream).await, [0x89, 0x00]);
+
+    // Still open just short of the 10s pong timeout
+    tokio::time::advance(Duration::from_secs(9)).await;
+    for _ in 0..100 {
+        tokio::task::yield_now().await;
+    }
+    let mut buf = [0u8; 16];
+    assert_eq!(
+        stream.try_read(&mut buf).unwrap_err().kind(),
+        std::io::ErrorKind::WouldBlock
+    );
+
+    // Then closed, and the socket released rather than held by a stray task
+    tokio::time::advance(Duration::from_secs(2)).await;
+    assert_eq!(read_available(&stream).await, [0x88, 0x00]);
+    assert!(read_available(&stream).await.is_empty());
+}